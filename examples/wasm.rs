@@ -0,0 +1,55 @@
+//! ファイルシステムや非同期ランタイムを使わずに参照情報を抽出する例
+//!
+//! `cli`機能を外せばライブラリ部分だけをWebAssembly向けにビルドできる:
+//!
+//! ```sh
+//! cargo build --example wasm --no-default-features --target wasm32-unknown-unknown
+//! ```
+use gen_eli::law::{Date, Law, ParseConfig, analyze};
+use std::collections::HashMap;
+
+const XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Law Era="Reiwa" Lang="ja" LawType="Act" Num="1" Year="7"><LawNum>令和七年法律第一号</LawNum><LawBody><LawTitle>テスト法</LawTitle>
+<MainProvision>
+<Article Num="1"><ArticleTitle>第一条</ArticleTitle>
+<Paragraph Num="1"><ParagraphNum/><ParagraphSentence><Sentence Num="1">民法第九十条の規定により、無効とする。</Sentence></ParagraphSentence></Paragraph>
+</Article>
+</MainProvision>
+</LawBody></Law>"#;
+
+const MINPOU_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Law Era="Meiji" Lang="ja" LawType="Act" Num="089" Year="29"><LawNum>明治二十九年法律第八十九号</LawNum><LawBody><LawTitle>民法</LawTitle>
+<MainProvision>
+<Article Num="90"><ArticleTitle>第九十条</ArticleTitle>
+<Paragraph Num="1"><ParagraphNum/><ParagraphSentence><Sentence Num="1">公の秩序又は善良の風俗に反する法律行為は、無効とする。</Sentence></ParagraphSentence></Paragraph>
+</Article>
+</MainProvision>
+</LawBody></Law>"#;
+
+fn main() -> Result<(), gen_eli::error::EliError> {
+  // 参照先となる法令の一覧を作る
+  let mut law_map: HashMap<String, Law> = HashMap::new();
+  let minpou = gen_eli::law::egov_xml_parse(
+    MINPOU_XML.as_bytes(),
+    Date::new_ad(2025, 4, 1),
+    None,
+    String::from("129AC0000000089"),
+    None,
+  )?;
+  let minpou = minpou.root().clone();
+  law_map.insert(minpou.get_name().unwrap(), minpou);
+
+  let finds = analyze(
+    XML.as_bytes(),
+    Date::new_ad(2025, 4, 1),
+    None,
+    String::from("507AC0000000001"),
+    None,
+    &law_map,
+    &ParseConfig::default(),
+  )?;
+  for find in finds.iter() {
+    println!("{:?}", find.get_relation());
+  }
+  Ok(())
+}
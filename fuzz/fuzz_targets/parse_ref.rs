@@ -0,0 +1,61 @@
+//! 任意のテキストを項の本文として`parse_ref_with_config`に与え，panicしないことを確かめる
+//! `cargo +nightly fuzz run parse_ref`で実行する
+#![no_main]
+
+use gen_eli::law::{Date, Law, ParseConfig, parse_ref_with_config};
+use japanese_law_xml_schema::{article_number::ArticleNumber, law::LawType};
+use libfuzzer_sys::fuzz_target;
+use std::collections::HashMap;
+
+fuzz_target!(|data: &[u8]| {
+  let Ok(text) = std::str::from_utf8(data) else {
+    return;
+  };
+  // 一文字の略称や法令番号など，検索の分岐を一通り通るような法令名を用意する
+  let mut law_map = HashMap::new();
+  for name in [
+    "民法",
+    "法",
+    "令",
+    "規則",
+    "民法施行令",
+    "明治二十九年法律第八十九号",
+  ] {
+    law_map.insert(
+      name.to_string(),
+      Law::new(
+        Date::new_ad(2025, 1, 1),
+        Some(name.to_string()),
+        String::from("129AC0000000089"),
+        String::from("明治二十九年法律第八十九号"),
+        LawType::Act,
+      ),
+    );
+  }
+  // 略称が次の項に引き継がれるように，改行で項に分ける
+  let mut target = HashMap::new();
+  let root = Law::new(
+    Date::new_ad(2025, 1, 1),
+    Some(String::from("テスト法")),
+    String::from("507AC0000000001"),
+    String::from("令和七年法律第一号"),
+    LawType::Act,
+  );
+  target.insert(String::new(), root.clone());
+  for (i, line) in text.split('\n').enumerate() {
+    let mut paragraph = root.clone();
+    paragraph.set_paragraph_number(ArticleNumber::from_num_str(&(i + 1).to_string()).unwrap());
+    paragraph.set_paragraph_text(line.to_string());
+    // 附則として扱われる項も混ぜる
+    if i % 2 == 1 {
+      paragraph.set_suppl_provision(String::new());
+    }
+    target.insert(i.to_string(), paragraph);
+  }
+  let config = ParseConfig {
+    expand_enforcement_orders: true,
+    strict: true,
+    ..Default::default()
+  };
+  let _ = parse_ref_with_config(&target, &law_map, &config);
+});
@@ -0,0 +1,239 @@
+//! e-govの法令XMLを取得し，テストで使う小さなフィクスチャに切り詰める開発用のツール
+//!
+//! ```sh
+//! cargo run --features fixtures --bin gen-fixture -- --law-id 129AC0000000089 --date 20230614 --article 90 --article 709
+//! ```
+//!
+//! 取得したXMLは`<out_dir>/raw/`に保存し，次回からはそれを使うので，一度取得すればオフラインで作り直せる．
+//! 切り詰めたXMLは`<out_dir>/<法令ID>_<日付>.xml`に書き出す．
+
+use anyhow::{Context, Result, bail};
+use clap::Parser;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// e-gov法令API（v2）の法令本文ファイル取得のURL
+const EGOV_LAW_FILE_API_URL: &str = "https://laws.e-gov.go.jp/api/2/law_file/xml";
+
+/// 切り詰めるときに取り除く，本則以外の要素
+const DROP_TAGS: [&str; 9] = [
+  "TOC",
+  "SupplProvision",
+  "AppdxTable",
+  "AppdxNote",
+  "AppdxStyle",
+  "AppdxFormat",
+  "AppdxFig",
+  "Appdx",
+  "Column",
+];
+
+/// 条を含まなくなったら取り除く，本則の階層の要素
+const CONTAINER_TAGS: [&str; 5] = ["Part", "Chapter", "Section", "Subsection", "Division"];
+
+#[derive(Parser, Debug)]
+#[command(about = "e-govの法令XMLからテスト用のフィクスチャを作る")]
+struct Args {
+  /// 法令ID
+  #[arg(long)]
+  law_id: String,
+  /// 時点（YYYYMMDD）
+  #[arg(long)]
+  date: String,
+  /// 残す条の`Num`属性（`90`や`3_2`など）．指定しなければ本則の条をすべて残す
+  #[arg(long)]
+  article: Vec<String>,
+  /// 取得の代わりに使うローカルの法令XML
+  #[arg(long)]
+  from: Option<PathBuf>,
+  /// 保存先のフォルダ
+  #[arg(long, default_value = "tests/fixtures")]
+  out_dir: PathBuf,
+}
+
+/// `YYYYMMDD`を法令APIの`asof`に渡す`YYYY-MM-DD`にする
+fn asof(date: &str) -> Result<String> {
+  if date.len() != 8 || !date.chars().all(|c| c.is_ascii_digit()) {
+    bail!("date must be YYYYMMDD: {date}");
+  }
+  Ok(format!("{}-{}-{}", &date[0..4], &date[4..6], &date[6..8]))
+}
+
+/// 法令XMLを取得する
+/// 依存クレートを増やさないよう，`curl`コマンドを使う
+fn fetch(law_id: &str, date: &str) -> Result<Vec<u8>> {
+  let url = format!("{EGOV_LAW_FILE_API_URL}/{law_id}?asof={}", asof(date)?);
+  let output = Command::new("curl")
+    .args(["-fsSL", &url])
+    .output()
+    .context("failed to run curl")?;
+  if !output.status.success() {
+    bail!(
+      "failed to fetch {url}: {}",
+      String::from_utf8_lossy(&output.stderr)
+    );
+  }
+  Ok(output.stdout)
+}
+
+/// 取得済みのものがあればそれを，無ければ取得して`raw_path`に保存したものを返す
+fn fetch_cached(law_id: &str, date: &str, raw_path: &Path) -> Result<Vec<u8>> {
+  if raw_path.exists() {
+    return Ok(std::fs::read(raw_path)?);
+  }
+  let buf = fetch(law_id, date)?;
+  if let Some(dir) = raw_path.parent() {
+    std::fs::create_dir_all(dir)?;
+  }
+  std::fs::write(raw_path, &buf)?;
+  Ok(buf)
+}
+
+/// `xml`の中の`tag`要素を順に見て，`keep`が`false`を返したものを取り除く
+/// 同名の要素の入れ子は深さを数えて対応する閉じタグを探す
+fn remove_elements(xml: &str, tag: &str, keep: impl Fn(&str) -> bool) -> String {
+  let open = format!("<{tag}");
+  let close = format!("</{tag}>");
+  let is_open_at = |i: usize| {
+    xml[i..].starts_with(&open)
+      && matches!(xml[i + open.len()..].chars().next(), Some(' ' | '>' | '/'))
+  };
+  // `from`以降で最初に出てくる`tag`要素の開始タグの位置
+  let find_open = |from: usize| {
+    let mut j = from;
+    while let Some(offset) = xml[j..].find(&open) {
+      if is_open_at(j + offset) {
+        return Some(j + offset);
+      }
+      j += offset + open.len();
+    }
+    None
+  };
+  let mut result = String::new();
+  let mut i = 0;
+  while let Some(start) = find_open(i) {
+    // 要素の終わりを探す
+    let tag_end = start + xml[start..].find('>').unwrap_or(xml.len() - start) + 1;
+    let end = if xml[..tag_end].ends_with("/>") {
+      tag_end
+    } else {
+      let mut depth = 1;
+      let mut j = tag_end;
+      loop {
+        let next_close = xml[j..].find(&close).map(|p| j + p);
+        let next_open = find_open(j);
+        match (next_open, next_close) {
+          (Some(o), Some(c)) if o < c => {
+            depth += 1;
+            j = o + open.len();
+          }
+          (_, Some(c)) => {
+            depth -= 1;
+            j = c + close.len();
+            if depth == 0 {
+              break j;
+            }
+          }
+          (_, None) => break xml.len(),
+        }
+      }
+    };
+    result.push_str(&xml[i..start]);
+    if keep(&xml[start..end]) {
+      result.push_str(&xml[start..end]);
+    } else {
+      // 取り除いた要素の行の字下げと改行も取り除き，空行を残さない
+      result.truncate(result.trim_end_matches([' ', '\t']).len());
+      if result.ends_with('\n') {
+        result.pop();
+      }
+    }
+    i = end;
+  }
+  result.push_str(&xml[i..]);
+  result
+}
+
+/// 法令XMLを`articles`の条だけを含むものに切り詰める
+/// 目次・附則・別表などを取り除き，条を含まなくなった編・章・節・款・目も取り除く
+fn trim_law_xml(xml: &str, articles: &[String]) -> String {
+  let mut xml = xml.to_string();
+  for tag in DROP_TAGS {
+    xml = remove_elements(&xml, tag, |_| false);
+  }
+  if !articles.is_empty() {
+    xml = remove_elements(&xml, "Article", |element| {
+      articles
+        .iter()
+        .any(|num| element.starts_with(&format!("<Article Num=\"{num}\"")))
+    });
+  }
+  // 下の階層から順に空になったものを取り除く
+  for tag in CONTAINER_TAGS.iter().rev() {
+    xml = remove_elements(&xml, tag, |element| element.contains("<Article "));
+  }
+  xml
+}
+
+fn main() -> Result<()> {
+  let args = Args::parse();
+  let raw = match &args.from {
+    Some(path) => std::fs::read(path)?,
+    None => {
+      let raw_path = args
+        .out_dir
+        .join("raw")
+        .join(format!("{}_{}.xml", args.law_id, args.date));
+      fetch_cached(&args.law_id, &args.date, &raw_path)?
+    }
+  };
+  let xml = String::from_utf8(raw).context("law XML is not UTF-8")?;
+  let trimmed = trim_law_xml(&xml, &args.article);
+  std::fs::create_dir_all(&args.out_dir)?;
+  let path = args
+    .out_dir
+    .join(format!("{}_{}.xml", args.law_id, args.date));
+  std::fs::write(&path, trimmed)?;
+  println!("{}", path.display());
+  Ok(())
+}
+
+#[test]
+fn check_trim_law_xml() {
+  let xml = concat!(
+    "<Law><LawBody><TOC><TOCLabel>目次</TOCLabel></TOC><MainProvision>",
+    "<Chapter Num=\"1\"><ChapterTitle>第一章</ChapterTitle>",
+    "<Article Num=\"1\"><ArticleTitle>第一条</ArticleTitle></Article>",
+    "<Article Num=\"2\"><ArticleTitle>第二条</ArticleTitle></Article>",
+    "</Chapter>",
+    "<Chapter Num=\"2\"><ChapterTitle>第二章</ChapterTitle>",
+    "<Article Num=\"3\"><ArticleTitle>第三条</ArticleTitle></Article>",
+    "<Article Num=\"3_2\"><ArticleTitle>第三条の二</ArticleTitle></Article>",
+    "</Chapter>",
+    "</MainProvision>",
+    "<SupplProvision><Article Num=\"1\"></Article></SupplProvision>",
+    "</LawBody></Law>",
+  );
+  assert_eq!(
+    trim_law_xml(xml, &[String::from("3_2")]),
+    concat!(
+      "<Law><LawBody><MainProvision>",
+      "<Chapter Num=\"2\"><ChapterTitle>第二章</ChapterTitle>",
+      "<Article Num=\"3_2\"><ArticleTitle>第三条の二</ArticleTitle></Article>",
+      "</Chapter>",
+      "</MainProvision>",
+      "</LawBody></Law>",
+    )
+  );
+  // ArticleRangeのように名前の先頭が同じ要素は対象にしない
+  assert_eq!(
+    remove_elements(
+      "<ArticleRange>（第一条）</ArticleRange><Article Num=\"1\"/>",
+      "Article",
+      |_| false
+    ),
+    "<ArticleRange>（第一条）</ArticleRange>"
+  );
+  assert_eq!(asof("20230614").unwrap(), "2023-06-14");
+  assert!(asof("2023-06-14").is_err());
+}
@@ -1,4 +1,5 @@
 pub use oxrdf::{NamedNode, Triple};
+use serde::{Deserialize, Serialize};
 
 /// 法令等の公開先
 pub enum Published {
@@ -20,9 +21,22 @@ pub trait Eli {
   fn published(&self) -> Published;
 }
 
+/// ELI Ontologyの名前空間
+pub const ELI_ONTOLOGY_NAMESPACE: &str = "http://data.europa.eu/eli/ontology#";
+
+/// `eli:legal_value`の値のうち，公的機関が公開したものを表す値
+pub const LEGAL_VALUE_OFFICIAL: &str = "http://data.europa.eu/eli/ontology#LegalValue-official";
+
+/// `eli:format`・`eli:media_type`の値のうち，XMLを表すIANAのメディアタイプ
+pub const MEDIA_TYPE_XML: &str = "http://www.iana.org/assignments/media-types/application/xml";
+
 /// ELIで使用されるオントロジー
 /// 定義となるRDFファイル: <http://data.europa.eu/eli/ontology>
 /// 作成時(2025-10-21)ではバージョン1.5
+///
+/// JSONではURIの文字列として表す
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
 pub enum EliOntology {
   /// 被参照を表す(<http://data.europa.eu/eli/ontology#amended_by>)
   ///
@@ -305,6 +319,91 @@ pub enum EliOntology {
 }
 
 impl EliOntology {
+  /// 全ての語彙
+  pub const ALL: [EliOntology; 81] = [
+    Self::AmendedBy,
+    Self::Ammends,
+    Self::AppliedBy,
+    Self::Applies,
+    Self::BasedOn,
+    Self::BasisFor,
+    Self::ChangedBy,
+    Self::Changes,
+    Self::CitedBy,
+    Self::CitedByCaseLaw,
+    Self::Cites,
+    Self::CommencedBy,
+    Self::Commences,
+    Self::ConsolidatedBy,
+    Self::Consolidates,
+    Self::CorrectedBy,
+    Self::Corrects,
+    Self::CountersignedBy,
+    Self::Embodies,
+    Self::EnsuresImplementationOf,
+    Self::Format,
+    Self::HasAnnex,
+    Self::HasDerivative,
+    Self::HasMember,
+    Self::HasPart,
+    Self::HasTranslation,
+    Self::Implements,
+    Self::InForce,
+    Self::IsAbout,
+    Self::IsAnnexOf,
+    Self::IsAnotherPublicationOf,
+    Self::IsDerivativeOf,
+    Self::IsEmbodiedBy,
+    Self::IsExemplifiedBy,
+    Self::IsMemberOf,
+    Self::IsPartOf,
+    Self::IsRealizedBy,
+    Self::IsReferredToBy,
+    Self::IsTranslationOf,
+    Self::Jurisdiction,
+    Self::Language,
+    Self::LegalValue,
+    Self::License,
+    Self::MediaType,
+    Self::PassedBy,
+    Self::PublishedInFormat,
+    Self::PublisherAgent,
+    Self::Publishes,
+    Self::Realizes,
+    Self::RefersTo,
+    Self::RelatedTo,
+    Self::RelevantFor,
+    Self::RepealedBy,
+    Self::Repeals,
+    Self::ResponsibilityOfAgent,
+    Self::RightsholderAgent,
+    Self::TransposedBy,
+    Self::Transposes,
+    Self::TypeDocument,
+    Self::TypeSubdivision,
+    Self::UriSchema,
+    Self::Version,
+    Self::WorkType,
+    Self::CitedByCaseLawReference,
+    Self::DateApplicability,
+    Self::DateDocument,
+    Self::DateNoLongerInForce,
+    Self::DatePublication,
+    Self::Description,
+    Self::FirstDateEntryInForce,
+    Self::IdLocal,
+    Self::Number,
+    Self::PublishedIn,
+    Self::Publisher,
+    Self::ResponsibilityOf,
+    Self::Rights,
+    Self::Rightscholder,
+    Self::Title,
+    Self::TitleAlternative,
+    Self::TitleShort,
+    Self::VersionDate,
+  ];
+
   /// ELI Ontologyで定義されているURIにする
   pub fn uri(&self) -> String {
     match self {
@@ -324,7 +423,7 @@ impl EliOntology {
       Self::ConsolidatedBy => String::from("http://data.europa.eu/eli/ontology#consolidated_by"),
       Self::Consolidates => String::from("http://data.europa.eu/eli/ontology#consolidates"),
       Self::CorrectedBy => String::from("http://data.europa.eu/eli/ontology#corrected_by"),
-      Self::Corrects => String::from("http://data.europa.eu/eli/ontology#correccts"),
+      Self::Corrects => String::from("http://data.europa.eu/eli/ontology#corrects"),
       Self::CountersignedBy => String::from("http://data.europa.eu/eli/ontology#countersigned_by"),
       Self::Embodies => String::from("http://data.europa.eu/eli/ontology#embodies"),
       Self::EnsuresImplementationOf => {
@@ -352,7 +451,7 @@ impl EliOntology {
       Self::IsReferredToBy => String::from("http://data.europa.eu/eli/ontology#is_referred_to_by"),
       Self::IsTranslationOf => String::from("http://data.europa.eu/eli/ontology#is_translation_of"),
       Self::Jurisdiction => String::from("http://data.europa.eu/eli/ontology#jurisdiction"),
-      Self::Language => String::from("http://data.europa.eu/eli/ontology#lanuguage"),
+      Self::Language => String::from("http://data.europa.eu/eli/ontology#language"),
       Self::LegalValue => String::from("http://data.europa.eu/eli/ontology#legal_value"),
       Self::License => String::from("http://data.europa.eu/eli/ontology#license"),
       Self::MediaType => String::from("http://data.europa.eu/eli/ontology#media_type"),
@@ -414,6 +513,11 @@ impl EliOntology {
     }
   }
 
+  /// `uri`の逆で，URIから語彙を得る
+  pub fn from_uri(uri: &str) -> Option<Self> {
+    Self::ALL.into_iter().find(|o| o.uri() == uri)
+  }
+
   pub fn named_node(&self) -> NamedNode {
     let uri = self.uri();
     NamedNode::new(uri).unwrap()
@@ -427,3 +531,49 @@ impl EliOntology {
     }
   }
 }
+
+impl From<EliOntology> for String {
+  fn from(value: EliOntology) -> Self {
+    value.uri()
+  }
+}
+
+impl TryFrom<String> for EliOntology {
+  type Error = String;
+  fn try_from(value: String) -> Result<Self, Self::Error> {
+    Self::from_uri(&value).ok_or_else(|| format!("unknown ELI ontology URI: {value}"))
+  }
+}
+
+#[test]
+fn check_eli_ontology_serde() {
+  use std::collections::HashMap;
+  // URIが重複していなければ，全ての語彙がURIから元に戻せる
+  for o in EliOntology::ALL {
+    assert_eq!(EliOntology::from_uri(&o.uri()), Some(o));
+  }
+  // 以前の綴り間違いのURIは語彙として扱わない
+  assert_eq!(
+    EliOntology::Corrects.uri(),
+    "http://data.europa.eu/eli/ontology#corrects"
+  );
+  assert_eq!(
+    EliOntology::from_uri("http://data.europa.eu/eli/ontology#lanuguage"),
+    None
+  );
+  assert_eq!(
+    serde_json::to_string(&EliOntology::CitedBy).unwrap(),
+    "\"http://data.europa.eu/eli/ontology#cited_by\""
+  );
+  let map = HashMap::from([
+    (EliOntology::Cites, String::from("参照")),
+    (EliOntology::Repeals, String::from("廃止")),
+  ]);
+  let json = serde_json::to_string(&map).unwrap();
+  assert!(json.contains("\"http://data.europa.eu/eli/ontology#repeals\":\"廃止\""));
+  assert_eq!(
+    serde_json::from_str::<HashMap<EliOntology, String>>(&json).unwrap(),
+    map
+  );
+  assert!(serde_json::from_str::<EliOntology>("\"http://example.com/\"").is_err());
+}
@@ -0,0 +1,73 @@
+//! ライブラリが返すエラー
+//!
+//! 呼び出し側がエラーの種類ごとに処理を分けられるよう，`anyhow`ではなくこの型を返す
+
+use japanese_law_xml_schema::result::Error as SchemaError;
+use thiserror::Error;
+
+pub type Result<T> = std::result::Result<T, EliError>;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum EliError {
+  /// XMLとして読めなかった
+  #[error("failed to parse law XML")]
+  XmlSyntax,
+  /// 法令XMLのスキーマで必須の要素・属性が無かった
+  #[error("missing required {}", match attribute {
+    Some(attribute) => format!("attribute {attribute} at {tag} tag"),
+    None => format!("tag {tag}"),
+  })]
+  MissingElement {
+    tag: String,
+    attribute: Option<String>,
+  },
+  /// XMLとしては読めたが法令XMLのスキーマに合わなかった
+  #[error("law XML does not match the schema: {0}")]
+  Schema(String),
+  /// 対応していない版の法令標準XMLスキーマで書かれていて，解析できなかった
+  #[error("law XML schema version {found} is not supported (supported: {supported}): {reason}")]
+  UnsupportedSchemaVersion {
+    found: u32,
+    supported: u32,
+    reason: String,
+  },
+  /// 上の階層の番号が無いのに下の階層の番号が設定されていた
+  #[error("{law_id}: {child} is set without {parent}")]
+  NumberHierarchy {
+    law_id: String,
+    child: String,
+    parent: String,
+  },
+  /// 索引ファイルの形式の版が違う
+  #[error("unsupported law index schema version: {found} (expected {expected})")]
+  IndexSchemaVersion { found: u32, expected: u32 },
+  /// 索引ファイルが別の版のツールで作られた
+  #[error("law index was built by version {found} (expected {expected})")]
+  IndexCrateVersion { found: String, expected: String },
+  /// 索引ファイルの内容が書き換わっている
+  #[error("law index content hash mismatch: {found} (expected {expected})")]
+  IndexHashMismatch { found: String, expected: String },
+  /// ファイルを開けなかった
+  #[error("failed to open {path}: {message}")]
+  Io { path: String, message: String },
+}
+
+impl From<SchemaError> for EliError {
+  fn from(err: SchemaError) -> Self {
+    match err {
+      SchemaError::XMLParsing => EliError::XmlSyntax,
+      SchemaError::MissingRequiredTag { tag_name } => EliError::MissingElement {
+        tag: tag_name,
+        attribute: None,
+      },
+      SchemaError::MissingRequiredAttribute {
+        tag_name,
+        attribute_name,
+      } => EliError::MissingElement {
+        tag: tag_name,
+        attribute: Some(attribute_name),
+      },
+      err => EliError::Schema(err.to_string()),
+    }
+  }
+}
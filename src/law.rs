@@ -1,29 +1,83 @@
 use crate::eli::{self, EliOntology};
-use anyhow::Result;
+use crate::error::{EliError, Result};
 pub use japanese_law_id::Date;
+use japanese_law_id::{Era, Wareki};
 use japanese_law_xml_schema::{
+  article::{
+    Article, Chapter, ChapterContents, Division, Part, PartContents, Section, SectionContents,
+    Subsection, SubsectionContents,
+  },
   article_number::{ArticleNumber, parse_article_number},
-  law::LawType,
+  law::{LawType, MainProvision, MainProvisionContents},
+  paragraph::Paragraph,
   utils::{
-    Toc, WithNumberArticle, text_from_paragraph_list, toc_list_from_main_provision,
+    Toc, WithNumberArticle, article_list_from_suppl_provision, text_from_paragraph_list,
+    text_info_list_from_paragraph, toc_list_from_main_provision,
     with_number_article_list_from_main_provision,
   },
 };
-pub use oxrdf::Triple;
+pub use oxrdf::{BlankNode, Literal, NamedNode, Quad, Triple};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::{cmp::Ordering, collections::HashMap};
+use std::{
+  cmp::Ordering,
+  collections::{HashMap, HashSet},
+  ops::ControlFlow,
+  sync::LazyLock,
+};
 use tracing::trace;
 
+#[cfg(test)]
+mod test_fixtures;
+#[cfg(test)]
+use test_fixtures::{minpou, paragraph};
+
+/// `Find::reified_triples`で参照の位置（`start`・`end`）を表す述語の名前空間
+pub const REFERENCE_NAMESPACE: &str = "https://github.com/puripuri2100-research/eli/reference#";
+
+/// e-govの法令ページのベースURL
+pub const EGOV_LAW_BASE_URL: &str = "https://laws.e-gov.go.jp/law";
+
+/// e-Gov法令API（Version 2）で法令本文のXMLファイルを取得するURLのベース
+pub const EGOV_LAW_FILE_API_URL: &str = "https://laws.e-gov.go.jp/api/2/law_file/xml";
+
+/// `Eli::eli_uri`で生成するURIのベース
+pub const ELI_BASE_URI: &str = "https://github.com/puripuri2100-research/eli";
+
+/// 改正が無い（制定時の）版を表す改正法令ID
+/// e-govの一括ダウンロードのフォルダ名やURLではこの値が使われる
+pub const NO_PATCH_ID: &str = "000000000000000";
+
+/// 項の中の柱書きや号ごとのテキスト
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ItemText {
+  /// 号・細分の番号を上の階層から並べたもの
+  /// 項の柱書きの場合は空
+  pub item_numbers: Vec<Option<ArticleNumber>>,
+  pub text: String,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct Law {
   date: Date,
   // 法令名が無く，法令番号だけの時がある
   name: Option<String>,
+  // 法令ID（`129AC0000000089`のような英数字）
+  // URIやファイル名には必ずこちらを使う
   law_id: String,
+  // 法令番号（公布番号，`明治二十九年法律第八十九号`のような文字列）
+  // 出力では法令IDと必ず対で書き出し，片方だけを変えないようにする
   law_id_text: String,
   patch_id: Option<String>,
   law_type: LawType,
+  // 附則の場合は改正法令番号（制定時の附則の場合は空文字列）
+  suppl_provision: Option<String>,
+  // 前文の場合は`true`
+  // 前文を持つ法令は少ないので，`false`のときは出力しない
+  #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+  preamble: bool,
+  // 別表の場合は`1_2`のような番号（番号の無い別表の場合は空文字列）
+  appdx_table: Option<String>,
   part_number: Option<ArticleNumber>,
   chapter_number: Option<ArticleNumber>,
   section_number: Option<ArticleNumber>,
@@ -31,10 +85,74 @@ pub struct Law {
   division_number: Option<ArticleNumber>,
   article_number: Option<ArticleNumber>,
   paragraph_number: Option<ArticleNumber>,
+  // 「第○号」で参照される号（項の中の号の区切りは`paragraph_items`が持つ）
+  item_number: Option<ArticleNumber>,
   paragraph_text: Option<String>,
+  // 号ごとに分けたテキスト
+  // つなげると（区切りは改行）paragraph_textと一致する
+  paragraph_items: Vec<ItemText>,
   egov_link: Option<String>,
 }
 
+/// 法令名などの後に続く「第三条第二項」・「別表第一」・「附則第二条」のような条項番号
+/// 書かれていない階層は`None`になる
+/// `Law::with_locator`で参照先の法令に当てはめる
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JoukouRef {
+  /// 「附則」・「附則第二条」のように，制定時の附則を指すかどうか
+  pub suppl_provision: bool,
+  pub appdx_table: Option<String>,
+  pub part_number: Option<ArticleNumber>,
+  pub chapter_number: Option<ArticleNumber>,
+  pub section_number: Option<ArticleNumber>,
+  pub subsection_number: Option<ArticleNumber>,
+  pub division_number: Option<ArticleNumber>,
+  pub article_number: Option<ArticleNumber>,
+  pub paragraph_number: Option<ArticleNumber>,
+  pub item_number: Option<ArticleNumber>,
+}
+
+/// 条項を識別するためのkey
+/// `Law`から本文やリンクなどを除いた，日付・法令ID・改正法令ID・各階層の番号だけを持つ
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LawKey {
+  pub date: Date,
+  pub law_id: String,
+  pub patch_id: Option<String>,
+  pub suppl_provision: Option<String>,
+  pub preamble: bool,
+  pub appdx_table: Option<String>,
+  pub part_number: Option<ArticleNumber>,
+  pub chapter_number: Option<ArticleNumber>,
+  pub section_number: Option<ArticleNumber>,
+  pub subsection_number: Option<ArticleNumber>,
+  pub division_number: Option<ArticleNumber>,
+  pub article_number: Option<ArticleNumber>,
+  pub paragraph_number: Option<ArticleNumber>,
+  pub item_number: Option<ArticleNumber>,
+}
+
+impl From<&Law> for LawKey {
+  fn from(law: &Law) -> Self {
+    LawKey {
+      date: law.date,
+      law_id: law.law_id.clone(),
+      patch_id: law.patch_id.clone(),
+      suppl_provision: law.suppl_provision.clone(),
+      preamble: law.preamble,
+      appdx_table: law.appdx_table.clone(),
+      part_number: law.part_number.clone(),
+      chapter_number: law.chapter_number.clone(),
+      section_number: law.section_number.clone(),
+      subsection_number: law.subsection_number.clone(),
+      division_number: law.division_number.clone(),
+      article_number: law.article_number.clone(),
+      paragraph_number: law.paragraph_number.clone(),
+      item_number: law.item_number.clone(),
+    }
+  }
+}
+
 impl Law {
   pub fn new(
     date: Date,
@@ -50,6 +168,9 @@ impl Law {
       law_id_text,
       patch_id: None,
       law_type,
+      suppl_provision: None,
+      preamble: false,
+      appdx_table: None,
       part_number: None,
       chapter_number: None,
       section_number: None,
@@ -57,7 +178,9 @@ impl Law {
       division_number: None,
       article_number: None,
       paragraph_number: None,
+      item_number: None,
       paragraph_text: None,
+      paragraph_items: Vec::new(),
       egov_link: None,
     }
   }
@@ -67,19 +190,54 @@ impl Law {
   pub fn get_name(&self) -> Option<String> {
     self.name.clone()
   }
+  pub fn get_date(&self) -> Date {
+    self.date
+  }
+  pub fn get_law_type(&self) -> LawType {
+    self.law_type.clone()
+  }
+  /// 法令IDと法令番号は対になっているので，必ず一緒に設定する
   pub fn set_law_id(&mut self, id: String, text: String) {
     self.law_id_text = text;
     self.law_id = id;
   }
+  /// 法令ID（`129AC0000000089`など）
   pub fn get_law_id(&self) -> String {
     self.law_id.clone()
   }
+  /// 法令番号（`明治二十九年法律第八十九号`など）
   pub fn get_law_id_text(&self) -> String {
     self.law_id_text.clone()
   }
   pub fn set_patch_id(&mut self, patch_id: String) {
     self.patch_id = Some(patch_id)
   }
+  pub fn get_patch_id(&self) -> Option<String> {
+    self.patch_id.clone()
+  }
+  /// 附則であることを設定する
+  /// 制定時の附則の場合は改正法令番号を空文字列とする
+  pub fn set_suppl_provision(&mut self, amend_law_num: String) {
+    self.suppl_provision = Some(amend_law_num)
+  }
+  pub fn get_suppl_provision(&self) -> Option<String> {
+    self.suppl_provision.clone()
+  }
+  /// 前文であることを設定する
+  pub fn set_preamble(&mut self) {
+    self.preamble = true
+  }
+  pub fn is_preamble(&self) -> bool {
+    self.preamble
+  }
+  /// 別表であることを設定する
+  /// 番号は`1_2`のような形式で，番号の無い別表の場合は空文字列とする
+  pub fn set_appdx_table(&mut self, num: String) {
+    self.appdx_table = Some(num)
+  }
+  pub fn get_appdx_table(&self) -> Option<String> {
+    self.appdx_table.clone()
+  }
   pub fn set_numbers(&mut self, number: &WithNumberArticle) {
     self.part_number = number.get_part();
     self.chapter_number = number.get_chapter();
@@ -97,16 +255,192 @@ impl Law {
     self.division_number = toc.get_division();
     self.article_number = toc.get_article();
     self.paragraph_number = None;
+    // `Toc::set_article`は条番号を目の番号として設定してしまい，その後の章や節にも残ってしまう
+    // 題名の最初の語から階層を判断し，条のものは条に付け替え，それより下の階層の番号は消す
+    let level = toc
+      .get_title()
+      .and_then(|title| title.split_whitespace().next().map(|word| word.to_string()))
+      .and_then(|word| {
+        ['条', '目', '款', '節', '章', '編']
+          .into_iter()
+          .find(|c| word.contains(*c))
+      });
+    match level {
+      Some('条') if self.article_number.is_none() => {
+        self.article_number = self.division_number.take();
+      }
+      Some('款') => self.division_number = None,
+      Some('節') => {
+        self.subsection_number = None;
+        self.division_number = None;
+      }
+      Some('章') => {
+        self.section_number = None;
+        self.subsection_number = None;
+        self.division_number = None;
+      }
+      Some('編') => {
+        self.chapter_number = None;
+        self.section_number = None;
+        self.subsection_number = None;
+        self.division_number = None;
+      }
+      _ => (),
+    }
+  }
+  /// 日本国憲法の条から章を補う
+  /// 憲法は章の構成が変わらず，e-govのURLのフラグメントに章が必要なので，条番号だけの参照でも章を決めておく
+  pub fn fill_constitution_chapter(&mut self) {
+    if self.law_type != LawType::Constitution || self.chapter_number.is_some() {
+      return;
+    }
+    // 各章の最後の条
+    let last_articles = [8, 9, 40, 64, 75, 82, 91, 95, 96, 99, 103];
+    if let Some(article) = &self.article_number
+      && article.eda_numbers.is_empty()
+      && let Some(chapter) = last_articles
+        .iter()
+        .position(|last| article.base_number <= *last)
+    {
+      self.chapter_number = ArticleNumber::from_num_str(&(chapter + 1).to_string()).ok();
+    }
+  }
+
+  /// 編・章・節・款・目の番号が，上の階層の番号が無いのに設定されていないかを確かめる
+  /// 編は省略されることがあるので，章から下の階層だけを見る
+  /// `set_numbers`と`set_numbers_from_toc`で中途半端な状態になっていないかを調べるのに使う
+  pub fn validate_number_hierarchy(&self) -> Result<()> {
+    let levels = [
+      ("章", &self.chapter_number),
+      ("節", &self.section_number),
+      ("款", &self.subsection_number),
+      ("目", &self.division_number),
+    ];
+    for pair in levels.windows(2) {
+      let (parent_name, parent) = pair[0];
+      let (child_name, child) = pair[1];
+      if parent.is_none() && child.is_some() {
+        return Err(EliError::NumberHierarchy {
+          law_id: self.law_id.clone(),
+          child: child_name.to_string(),
+          parent: parent_name.to_string(),
+        });
+      }
+    }
+    Ok(())
   }
+
   pub fn set_paragraph_number(&mut self, paragraph_number: ArticleNumber) {
     self.paragraph_number = Some(paragraph_number);
   }
   pub fn set_paragraph_text(&mut self, text: String) {
     self.paragraph_text = Some(text);
   }
+  pub fn get_paragraph_text(&self) -> Option<String> {
+    self.paragraph_text.clone()
+  }
+  pub fn set_paragraph_items(&mut self, items: Vec<ItemText>) {
+    self.paragraph_items = items;
+  }
+  pub fn get_paragraph_items(&self) -> Vec<ItemText> {
+    self.paragraph_items.clone()
+  }
+  /// 日付と改正法令IDを除いて，同じ法令の同じ条項を指しているかどうか
+  /// 異なる版の同じ条項を比べるのに使う
+  pub fn same_target(&self, other: &Law) -> bool {
+    LawKey {
+      date: other.date,
+      patch_id: other.patch_id.clone(),
+      ..LawKey::from(self)
+    } == LawKey::from(other)
+  }
+  /// 項のテキストと号ごとのテキストを持たない複製を返す
+  /// 参照関係のグラフのように，条項の番号だけが必要なところでメモリを節約するのに使う
+  pub fn without_text(&self) -> Law {
+    // 本文を複製してから捨てないよう，本文以外のフィールドだけを複製する
+    Law {
+      date: self.date,
+      name: self.name.clone(),
+      law_id: self.law_id.clone(),
+      law_id_text: self.law_id_text.clone(),
+      patch_id: self.patch_id.clone(),
+      law_type: self.law_type.clone(),
+      suppl_provision: self.suppl_provision.clone(),
+      preamble: self.preamble,
+      appdx_table: self.appdx_table.clone(),
+      part_number: self.part_number.clone(),
+      chapter_number: self.chapter_number.clone(),
+      section_number: self.section_number.clone(),
+      subsection_number: self.subsection_number.clone(),
+      division_number: self.division_number.clone(),
+      article_number: self.article_number.clone(),
+      paragraph_number: self.paragraph_number.clone(),
+      item_number: self.item_number.clone(),
+      paragraph_text: None,
+      paragraph_items: Vec::new(),
+      egov_link: self.egov_link.clone(),
+    }
+  }
   pub fn set_egov_link(&mut self, egov_link: String) {
     self.egov_link = Some(egov_link);
   }
+  /// 法令全体のe-govのページのURL（`{ベースURL}/{法令ID}`）
+  /// ベースURLは通常`EGOV_LAW_BASE_URL`で，ミラーなどを参照するときは差し替える（末尾の`/`は取り除く）
+  /// 版を指定しないので，e-govでは最新の版が表示される
+  pub fn egov_law_url(&self, base_url: &str) -> String {
+    format!("{}/{}", base_url.trim_end_matches('/'), self.law_id)
+  }
+  /// 版によらない抽象的な法令（`{ELI_BASE_URI}/{種類}/{法令ID}/`）のURI
+  /// 各版の`eli_uri`は`eli:is_member_of`でこれに属する
+  pub fn work_uri(&self) -> String {
+    format!(
+      "{ELI_BASE_URI}/{}/{}/",
+      uri_segment(&self.law_type_str()),
+      uri_segment(&self.law_id)
+    )
+  }
+  /// この版の法令全体のe-govのページのURL（`{ベースURL}/{法令ID}/{YYYYMMDD}_{改正法令ID}`）
+  /// `published_at`から条項のアンカーを除いたもので，改正法令IDが無い場合は`NO_PATCH_ID`を使う
+  pub fn egov_version_url(&self, base_url: &str) -> String {
+    format!(
+      "{}/{:0>4}{:0>2}{:0>2}_{}",
+      self.egov_law_url(base_url),
+      self.date.get_ad_year(),
+      self.date.get_month(),
+      self.date.get_day(),
+      self.patch_id.clone().unwrap_or(NO_PATCH_ID.to_string()),
+    )
+  }
+  /// `base_url`の下の，e-govの版ごとのページ
+  /// `{ベースURL}/{法令ID}/{YYYYMMDD}_{改正法令ID}{条項のID}`の形式で，
+  /// 日付は施行日を区切り無しの8桁で表し，改正法令IDが無い場合は`NO_PATCH_ID`を使う
+  /// `set_egov_link`でリンクを設定している場合はそれを使う
+  pub fn published_at(&self, base_url: &str) -> eli::Published {
+    if let Some(link) = &self.egov_link {
+      eli::Published::Uri(link.clone())
+    } else {
+      eli::Published::Uri(format!(
+        "{}{}",
+        self.egov_version_url(base_url),
+        self
+          .egov_xml_id()
+          .map(|id| format!("#{id}"))
+          .unwrap_or_default()
+      ))
+    }
+  }
+  /// この版の法令本文のXMLファイルのURL（`{EGOV_LAW_FILE_API_URL}/{法令ID}_{YYYYMMDD}_{改正法令ID}`）
+  /// 法令履歴IDで版を指定するので，`asof`を付けなくてもこの版のXMLが返る
+  pub fn egov_xml_url(&self) -> String {
+    format!(
+      "{EGOV_LAW_FILE_API_URL}/{}_{:0>4}{:0>2}{:0>2}_{}",
+      self.law_id,
+      self.date.get_ad_year(),
+      self.date.get_month(),
+      self.date.get_day(),
+      self.patch_id.clone().unwrap_or(NO_PATCH_ID.to_string()),
+    )
+  }
 
   pub fn law_type_str(&self) -> String {
     let s = match self.law_type {
@@ -130,22 +464,67 @@ impl Law {
   pub fn article_number_str(&self) -> Option<String> {
     self
       .article_number
-      .clone()
-      .map(|num| format!("article{}", num.num_str()))
+      .as_ref()
+      .map(|num| format!("article{}", uri_number_str(num)))
   }
   pub fn paragraph_number_str(&self) -> Option<String> {
     self
       .paragraph_number
-      .clone()
-      .map(|num| format!("paragraph{}", num.num_str()))
+      .as_ref()
+      .map(|num| format!("paragraph{}", uri_number_str(num)))
+  }
+  pub fn item_number_str(&self) -> Option<String> {
+    self
+      .item_number
+      .as_ref()
+      .map(|num| format!("item{}", uri_number_str(num)))
+  }
+
+  /// 条項番号`locator`を当てはめた法令を返す
+  /// 書かれている最も上の階層から下はすべて`locator`のものに置き換え，書かれていない下の階層は消す
+  /// 「同条第二項」のように上の階層だけを引き継ぐ場合は，その上の階層は元のままにする
+  /// 別表の場合は本則の階層をすべて消す
+  /// 附則の場合は制定時の附則とし，その中の書かれている階層だけを当てはめる
+  pub fn with_locator(&self, locator: JoukouRef) -> Law {
+    let mut law = self.clone();
+    let levels = [
+      (&mut law.part_number, locator.part_number),
+      (&mut law.chapter_number, locator.chapter_number),
+      (&mut law.section_number, locator.section_number),
+      (&mut law.subsection_number, locator.subsection_number),
+      (&mut law.division_number, locator.division_number),
+      (&mut law.article_number, locator.article_number),
+      (&mut law.paragraph_number, locator.paragraph_number),
+      (&mut law.item_number, locator.item_number),
+    ];
+    let mut replaced = locator.suppl_provision || locator.appdx_table.is_some();
+    for (level, number) in levels {
+      replaced |= number.is_some();
+      if replaced {
+        *level = number;
+      }
+    }
+    if locator.suppl_provision {
+      law.suppl_provision = Some(String::new());
+    }
+    if replaced {
+      law.preamble = false;
+      law.appdx_table = locator.appdx_table;
+      law.paragraph_text = None;
+      law.paragraph_items = Vec::new();
+    }
+    law
   }
 
   // 番号を検索して親要素を生成する
   pub fn parent(&self) -> Self {
     let mut parent = self.clone();
-    if self.paragraph_number.is_some() {
+    if self.item_number.is_some() {
+      parent.item_number = None;
+    } else if self.paragraph_number.is_some() {
       parent.paragraph_number = None;
       parent.paragraph_text = None;
+      parent.paragraph_items = Vec::new();
     } else if self.article_number.is_some() {
       parent.article_number = None;
     } else if self.division_number.is_some() {
@@ -158,23 +537,21 @@ impl Law {
       parent.chapter_number = None;
     } else if self.part_number.is_some() {
       parent.part_number = None;
+    } else if self.appdx_table.is_some() {
+      parent.appdx_table = None;
+    } else if self.suppl_provision.is_some() {
+      parent.suppl_provision = None;
+    } else if self.preamble {
+      parent.preamble = false;
     }
     parent
   }
 
   /// 第○章，第○条第△項といった条項番号のテキストを生成する
+  /// 条番号は本則・附則の中でそれぞれ一意なので，条がある場合は編・章などを含めない
+  /// 附則の場合は先頭に「附則」と改正法令番号を，前文の場合は「前文」を付ける
   fn number_text(&self) -> String {
-    if let Some(num) = &self.part_number {
-      num.part_text()
-    } else if let Some(num) = &self.chapter_number {
-      num.chapter_text()
-    } else if let Some(num) = &self.section_number {
-      num.section_text()
-    } else if let Some(num) = &self.subsection_number {
-      num.subsection_text()
-    } else if let Some(num) = &self.division_number {
-      num.division_text()
-    } else if let Some(num) = &self.article_number {
+    let s = if let Some(num) = &self.article_number {
       if let Some(para_num) = &self.paragraph_number {
         format!("{}{}", num.article_text(), para_num.paragraph_text())
       } else {
@@ -182,15 +559,113 @@ impl Law {
       }
     } else if let Some(num) = &self.paragraph_number {
       num.paragraph_text()
+    } else if let Some(s) = self.appdx_table_text() {
+      s
+    } else {
+      self.hierarchy_text()
+    };
+    let s = match &self.item_number {
+      Some(num) => format!("{s}{}", num.article_text().replace('条', "号")),
+      None => s,
+    };
+    match &self.suppl_provision {
+      Some(amend_law_num) if amend_law_num.is_empty() => format!("附則{s}"),
+      Some(amend_law_num) => format!("附則（{amend_law_num}）{s}"),
+      None if self.preamble => format!("前文{s}"),
+      None => s,
+    }
+  }
+
+  /// 「民法第九十条第一項」のような，法令名と条項番号をつなげた引用表記を生成する
+  /// 法令全体の場合は法令名のみとなり，法令名が無い場合は法令番号を代わりに使う
+  pub fn citation_text(&self) -> String {
+    format!("{}{}", self.display_name(), self.locator_text())
+  }
+
+  /// 引用表記やログで使う法令名
+  /// 法令名が無い場合は法令番号を代わりに使う
+  pub fn display_name(&self) -> String {
+    self
+      .name
+      .clone()
+      .unwrap_or_else(|| self.law_id_text.clone())
+  }
+
+  /// 引用表記で使う条項番号のテキストを生成する
+  /// 条があるときは条以下を，そうでないときは編から目までを上から順につなげる
+  pub fn locator_text(&self) -> String {
+    let mut s = String::new();
+    if self.suppl_provision.is_some() {
+      s.push_str("附則");
+    }
+    if self.preamble {
+      s.push_str("前文");
+    }
+    if let Some(num) = &self.article_number {
+      s.push_str(&num.article_text());
+    } else if let Some(appdx_table) = self.appdx_table_text() {
+      s.push_str(&appdx_table);
     } else {
-      String::new()
+      s.push_str(&self.hierarchy_text());
+    }
+    if let Some(num) = &self.paragraph_number {
+      // `paragraph_text`は「２」のような全角数字になるので，条の表記から「第二項」を作る
+      s.push_str(&num.article_text().replace('条', "項"));
+    }
+    if let Some(num) = &self.item_number {
+      s.push_str(&num.article_text().replace('条', "号"));
+    }
+    s
+  }
+
+  /// 項・号を除いた，条までの条項番号のテキストを生成する
+  /// 参照元を条ごとにまとめて表示するときの見出しに使う
+  pub fn article_locator_text(&self) -> String {
+    let mut article = self.without_text();
+    article.paragraph_number = None;
+    article.item_number = None;
+    article.locator_text()
+  }
+
+  /// 「別表第一の二」のような別表の番号のテキストを生成する
+  fn appdx_table_text(&self) -> Option<String> {
+    let num = self.appdx_table.as_ref()?;
+    let num_text = ArticleNumber::from_num_str(num)
+      .map(|n| n.article_text().replacen('条', "", 1))
+      .unwrap_or_default();
+    Some(format!("別表{num_text}"))
+  }
+
+  /// 編から目までの番号を上から順につなげたテキストを生成する
+  fn hierarchy_text(&self) -> String {
+    let mut s = String::new();
+    if let Some(num) = &self.part_number {
+      s.push_str(&num.part_text());
+    }
+    if let Some(num) = &self.chapter_number {
+      s.push_str(&num.chapter_text());
+    }
+    if let Some(num) = &self.section_number {
+      s.push_str(&num.section_text());
+    }
+    if let Some(num) = &self.subsection_number {
+      s.push_str(&num.subsection_text());
     }
+    if let Some(num) = &self.division_number {
+      s.push_str(&num.division_text());
+    }
+    s
   }
 
-  /// `#Mp-Pa_2-Ch_40`のような，条項に振られているIDを生成する．
+  /// e-govの法令ページで条項に振られている`Mp-Pa_2-Ch_40`のようなIDを生成する．
+  /// 編・章・節・款・目・条・項・号の順に`Pa`・`Ch`・`Se`・`Ss`・`Di`・`At`・`Pr`・`It`を並べ，
+  /// 枝番号は`At_3_2`のように`_`で繋げる．
   /// 具体的な例: <https://laws.e-gov.go.jp/law/129AC0000000089#Mp-Pa_3-Ch_1-Se_2-Ss_3-Di_4>
   /// まずはMainProvisionだけ対応．
-  fn egov_id(&self) -> Option<String> {
+  pub fn egov_xml_id(&self) -> Option<String> {
+    if self.suppl_provision.is_some() || self.appdx_table.is_some() || self.preamble {
+      return None;
+    }
     let mut s = String::new();
     if let Some(num) = &self.part_number {
       s.push_str(&format!("-Pa_{}", num.num_str()))
@@ -212,50 +687,72 @@ impl Law {
     }
     if let Some(num) = &self.paragraph_number {
       s.push_str(&format!("-Pr_{}", num.num_str()))
+    } else if self.item_number.is_some() {
+      // 「第九百条第一号」のように項が省略されている号は第一項のもの
+      s.push_str("-Pr_1")
+    }
+    if let Some(num) = &self.item_number {
+      s.push_str(&format!("-It_{}", num.num_str()))
     }
     if s.is_empty() {
       None
     } else {
-      Some(format!("#Mp{s}"))
+      Some(format!("Mp{s}"))
     }
   }
 }
 
+/// ログやエラーメッセージで使う「民法（129AC0000000089）」のような表記
+/// 法令名は`citation_text`と同じく，無い場合は法令番号を使う
+impl std::fmt::Display for Law {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}（{}）", self.display_name(), self.law_id)
+  }
+}
+
 impl eli::Eli for Law {
+  /// `EGOV_LAW_BASE_URL`の下の，e-govの版ごとのページを指す
   fn published(&self) -> eli::Published {
-    if let Some(link) = &self.egov_link {
-      eli::Published::Uri(link.clone())
-    } else {
-      eli::Published::Uri(format!(
-        "https://laws.e-gov.go.jp/law/{}/{}_{}{}",
-        self.law_id,
-        self.date.joined_str(),
-        self
-          .patch_id
-          .clone()
-          .unwrap_or("000000000000000".to_string()),
-        self.egov_id().unwrap_or_default()
-      ))
-    }
+    self.published_at(EGOV_LAW_BASE_URL)
   }
 
   /// `/eli/2024/12/12/mo/506M60000100140/000000000000000/article2/paragraph2`のような感じ
-  /// 日付，法令の種類，法令ID，改正法令ID，条番号，段落番号
+  /// 日付，法令の種類，法令ID，改正法令ID，条番号，段落番号，号番号
   fn eli_uri(&self) -> String {
     format!(
-      "https://github.com/puripuri2100-research/eli/{:0>4}/{:0>2}/{:0>2}/{}/{}/{}{}",
+      "{}/{:0>4}/{:0>2}/{:0>2}/{}/{}/{}{}{}{}{}{}",
+      ELI_BASE_URI,
       self.date.get_ad_year(),
       self.date.get_month(),
       self.date.get_day(),
-      self.law_type_str(),
-      self.law_id,
+      uri_segment(&self.law_type_str()),
+      uri_segment(&self.law_id),
+      if self.suppl_provision.is_some() {
+        "/suppl_provision"
+      } else {
+        ""
+      },
+      if self.preamble { "/preamble" } else { "" },
+      if let Some(num) = &self.appdx_table {
+        format!("/appdx_table{}", uri_segment(num))
+      } else {
+        String::new()
+      },
       if let Some(s) = self.article_number_str() {
-        format!("/{s}")
+        format!("/{}", uri_segment(&s))
       } else {
         String::new()
       },
       if let Some(s) = self.paragraph_number_str() {
-        format!("/{s}")
+        format!("/{}", uri_segment(&s))
+      } else if self.item_number.is_some() {
+        // `egov_xml_id`と同じく，項が省略されている号は第一項のものとする
+        String::from("/paragraph1")
+      } else {
+        String::new()
+      },
+      if let Some(s) = self.item_number_str() {
+        format!("/{}", uri_segment(&s))
       } else {
         String::new()
       }
@@ -263,707 +760,7246 @@ impl eli::Eli for Law {
   }
 }
 
-pub fn egov_xml_parse(
-  buf: &[u8],
-  date: Date,
-  law_name: Option<String>,
-  law_id: String,
-  patch_id: Option<String>,
-) -> Result<(HashMap<String, Law>, Vec<Triple>)> {
-  let parsed_law = japanese_law_xml_schema::parse_xml(buf)?;
-  let law_id_text = parsed_law.law_num;
-  let mut law = Law::new(date, law_name, law_id, law_id_text, parsed_law.law_type);
-  if let Some(patch_id) = patch_id {
-    law.set_patch_id(patch_id);
+/// URIの中で使う番号の文字列（`3_2`や，範囲の場合の`3:5`）
+/// `ArticleNumber::num_str`は枝番号の無い範囲の終わりを落とすので，
+/// `ArticleNumber::from_num_str`で元に戻せるように自前で組み立てる
+fn uri_number_str(num: &ArticleNumber) -> String {
+  let join = |l: &[usize]| {
+    l.iter()
+      .map(|n| n.to_string())
+      .collect::<Vec<_>>()
+      .join("_")
+  };
+  let mut s = num.base_number.to_string();
+  if !num.eda_numbers.is_empty() {
+    s.push_str(&format!("_{}", join(&num.eda_numbers)));
   }
-  let mut law_data = HashMap::new();
-  law_data.insert(String::new(), law.clone());
-
-  let mut v_triple = Vec::new();
-
-  // 編番号・章番号・条番号などを登録
-  let toc_list = toc_list_from_main_provision(&parsed_law.law_body.main_provision);
-  for toc in toc_list.iter() {
-    let mut law_tmp = law.clone();
-    law_tmp.set_numbers_from_toc(toc);
-    law_data.insert(law_tmp.number_text(), law_tmp.clone());
-    let parent = law_tmp.parent();
-    v_triple.push(EliOntology::HasPart.triple(parent.clone(), law_tmp.clone()));
-    v_triple.push(EliOntology::IsPartOf.triple(law_tmp.clone(), parent.clone()));
+  if !num.range_end_numbers.is_empty() {
+    s.push_str(&format!(":{}", join(&num.range_end_numbers)));
   }
+  s
+}
 
-  // 段落番号を登録する
-  let (with_number_articles, paragraphs) =
-    with_number_article_list_from_main_provision(&parsed_law.law_body.main_provision);
-  for a in with_number_articles.iter() {
-    let mut law_tmp = law.clone();
-    law_tmp.set_numbers(a);
-    for para in a.get_article().paragraph.iter() {
-      let mut law_tmp2 = law_tmp.clone();
-      law_tmp2.set_paragraph_number(para.num.clone());
-      law_tmp2.set_paragraph_text(text_from_paragraph_list(std::slice::from_ref(para)));
-      law_data.insert(law_tmp2.number_text(), law_tmp2.clone());
-      v_triple.push(EliOntology::HasPart.triple(law_tmp.clone(), law_tmp2.clone()));
-      v_triple.push(EliOntology::IsPartOf.triple(law_tmp2.clone(), law_tmp.clone()));
-    }
-  }
-  for para_list in paragraphs.iter() {
-    for para in para_list.iter() {
-      let mut law_tmp = law.clone();
-      law_tmp.set_paragraph_number(para.num.clone());
-      law_tmp.set_paragraph_text(text_from_paragraph_list(std::slice::from_ref(para)));
-      law_data.insert(para.num.paragraph_text(), law_tmp.clone());
-      v_triple.push(EliOntology::HasPart.triple(law.clone(), law_tmp.clone()));
-      v_triple.push(EliOntology::IsPartOf.triple(law_tmp.clone(), law.clone()));
+/// URIのパスの一つの区切りとしてそのまま使えない文字をパーセントエンコードする
+/// 法令IDや別表の`Num`属性のようにXMLやフォルダ名から来る文字列も，常に正しいIRIになるようにする
+/// 英数字と`-._~`・`!$&'()*+,;=`・`:@`はそのまま残すので，今の番号の表記（`3_2`・`3:5`）は変わらない
+fn uri_segment(s: &str) -> String {
+  let mut encoded = String::new();
+  for b in s.bytes() {
+    if b.is_ascii_alphanumeric() || b"-._~!$&'()*+,;=:@".contains(&b) {
+      encoded.push(b as char);
+    } else {
+      encoded.push_str(&format!("%{b:02X}"));
     }
   }
-  Ok((law_data, v_triple))
+  encoded
 }
 
-fn ord_article_number(a: &ArticleNumber, b: &ArticleNumber) -> Ordering {
-  if a.base_number == b.base_number {
-    for (a_e, b_e) in a.eda_numbers.iter().zip(&b.eda_numbers) {
-      if a_e != b_e {
-        return a_e.cmp(b_e);
-      }
+/// `uri_segment`でパーセントエンコードした文字列を元に戻す
+/// 正しくエンコードされていない場合は`None`を返す
+fn decode_uri_segment(s: &str) -> Option<String> {
+  let mut bytes = Vec::new();
+  let mut rest = s.as_bytes();
+  while let Some((&b, tail)) = rest.split_first() {
+    if b == b'%' {
+      let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+      bytes.push(u8::from_str_radix(hex, 16).ok()?);
+      rest = &tail[2..];
+    } else {
+      bytes.push(b);
+      rest = tail;
     }
-    a.eda_numbers.len().cmp(&b.eda_numbers.len())
-  } else {
-    a.base_number.cmp(&b.base_number)
   }
+  String::from_utf8(bytes).ok()
 }
 
-fn ord_article(a: &Law, b: &Law) -> Ordering {
-  let a_num = &a.article_number;
-  let b_num = &b.article_number;
-  match (a_num, b_num) {
-    (None, Some(_)) => {
-      // Noneは前文の可能性が高い
-      Ordering::Less
-    }
-    (Some(_), None) => Ordering::Greater,
-    (None, None) => {
-      let a_p = &a.paragraph_number;
-      let b_p = &b.paragraph_number;
-      match (a_p, b_p) {
-        (None, None) => Ordering::Equal,
-        (None, Some(_)) => Ordering::Less,
-        (Some(_), None) => Ordering::Greater,
-        (Some(a_pa), Some(b_pa)) => ord_article_number(a_pa, b_pa),
-      }
-    }
-    (Some(a_a), Some(b_a)) => {
-      let o = ord_article_number(a_a, b_a);
-      if o == Ordering::Equal {
-        let a_p = &a.paragraph_number;
-        let b_p = &b.paragraph_number;
-        match (a_p, b_p) {
-          (None, None) => Ordering::Equal,
-          (None, Some(_)) => Ordering::Less,
-          (Some(_), None) => Ordering::Greater,
-          (Some(a_pa), Some(b_pa)) => ord_article_number(a_pa, b_pa),
-        }
-      } else {
-        o
-      }
+/// `Eli::eli_uri`で生成したURIから読み取った情報
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EliUri {
+  pub date: Date,
+  /// `act`や`co`のような法令の種類（`Law::law_type_str`）
+  pub law_type: String,
+  pub law_id: String,
+  pub suppl_provision: bool,
+  pub preamble: bool,
+  pub appdx_table: Option<String>,
+  pub article_number: Option<ArticleNumber>,
+  pub paragraph_number: Option<ArticleNumber>,
+  pub item_number: Option<ArticleNumber>,
+}
+
+/// `Eli::eli_uri`で生成したURIを読み取る
+/// 形式に合わない場合は`None`を返す
+pub fn parse_eli_uri(uri: &str) -> Option<EliUri> {
+  let rest = uri.strip_prefix(ELI_BASE_URI)?.strip_prefix('/')?;
+  // 法令IDの後ろは空の区切りが入るので，空の部分は飛ばす
+  let mut segments = rest.split('/').filter(|s| !s.is_empty());
+  let mut next_number = || segments.next()?.parse::<usize>().ok();
+  let date = Date::new_ad(next_number()?, next_number()?, next_number()?);
+  let law_type = decode_uri_segment(segments.next()?)?;
+  let law_id = decode_uri_segment(segments.next()?)?;
+  let mut eli_uri = EliUri {
+    date,
+    law_type,
+    law_id,
+    suppl_provision: false,
+    preamble: false,
+    appdx_table: None,
+    article_number: None,
+    paragraph_number: None,
+    item_number: None,
+  };
+  let number = |s: &str| ArticleNumber::from_num_str(s).ok();
+  for segment in segments {
+    if segment == "suppl_provision" {
+      eli_uri.suppl_provision = true;
+    } else if segment == "preamble" {
+      eli_uri.preamble = true;
+    } else if let Some(num) = segment.strip_prefix("appdx_table") {
+      eli_uri.appdx_table = Some(decode_uri_segment(num)?);
+    } else if let Some(num) = segment.strip_prefix("article") {
+      eli_uri.article_number = Some(number(num)?);
+    } else if let Some(num) = segment.strip_prefix("paragraph") {
+      eli_uri.paragraph_number = Some(number(num)?);
+    } else if let Some(num) = segment.strip_prefix("item") {
+      eli_uri.item_number = Some(number(num)?);
+    } else {
+      return None;
     }
   }
+  Some(eli_uri)
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
-pub struct Position {
-  start: usize,
-  end: usize,
+#[test]
+fn check_parse_eli_uri() {
+  use eli::Eli;
+  let mut law = minpou(Date::new_ad(2025, 11, 26));
+  law.article_number = parse_article_number("第三条の二");
+  law.set_paragraph_number(parse_article_number("第二項").unwrap());
+  law.item_number = parse_article_number("第一号の二の三");
+  let uri = law.eli_uri();
+  assert_eq!(
+    uri,
+    format!("{ELI_BASE_URI}/2025/11/26/act/129AC0000000089//article3_2/paragraph2/item1_2_3")
+  );
+  let parsed = parse_eli_uri(&uri).unwrap();
+  assert_eq!(parsed.date, law.date);
+  assert_eq!(parsed.law_type, "act");
+  assert_eq!(parsed.law_id, "129AC0000000089");
+  assert!(!parsed.suppl_provision);
+  assert_eq!(parsed.article_number, law.article_number);
+  assert_eq!(parsed.paragraph_number, law.paragraph_number);
+  assert_eq!(parsed.item_number, law.item_number);
+  // 範囲の終わりも落とさない
+  let range = ArticleNumber {
+    base_number: 3,
+    eda_numbers: Vec::new(),
+    range_end_numbers: vec![5],
+  };
+  law.article_number = Some(range.clone());
+  law.paragraph_number = None;
+  law.item_number = None;
+  law.set_suppl_provision(String::new());
+  let uri = law.eli_uri();
+  assert!(uri.ends_with("/suppl_provision/article3:5"));
+  let parsed = parse_eli_uri(&uri).unwrap();
+  assert!(parsed.suppl_provision);
+  assert_eq!(parsed.article_number, Some(range));
+  // 枝番号の条の別表や，URIに使えない文字を含む法令IDでも，oxrdfで読める正しいIRIになる
+  law.article_number = parse_article_number("第十二条の三");
+  law.set_appdx_table(String::from("2_3 附"));
+  law.set_law_id(
+    String::from("129AC/0000000089#?"),
+    String::from("明治二十九年法律第八十九号"),
+  );
+  let uri = law.eli_uri();
+  assert!(NamedNode::new(&uri).is_ok(), "{uri}");
+  assert!(
+    uri.contains(
+      "/129AC%2F0000000089%23%3F//suppl_provision/appdx_table2_3%20%E9%99%84/article12_3"
+    ),
+    "{uri}"
+  );
+  let parsed = parse_eli_uri(&uri).unwrap();
+  assert_eq!(parsed.law_id, "129AC/0000000089#?");
+  assert_eq!(parsed.appdx_table.as_deref(), Some("2_3 附"));
+  assert_eq!(parsed.article_number, law.article_number);
+  assert!(NamedNode::new(law.work_uri()).is_ok());
+  assert!(decode_uri_segment("%E9%99").is_none());
+  assert!(decode_uri_segment("%2").is_none());
+  assert!(parse_eli_uri("https://example.com/2025/11/26/act/test").is_none());
+  assert!(parse_eli_uri(&format!("{ELI_BASE_URI}/2025/11/26/act/test/chapter1")).is_none());
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
-pub struct FindLawName {
-  position: Position,
-  find_law: Option<Law>,
-  match_string: String,
+/// トリプルを生成した元の法令とこのツールのバージョンを表すグラフのIRI
+/// `https://github.com/puripuri2100-research/eli/provenance/{バージョン}/{法令ID}_{YYYYMMDD}_{改正法令ID}`の形式
+pub fn provenance_graph(law: &Law) -> NamedNode {
+  NamedNode::new_unchecked(format!(
+    "https://github.com/puripuri2100-research/eli/provenance/{}/{}_{:0>4}{:0>2}{:0>2}_{}",
+    env!("CARGO_PKG_VERSION"),
+    law.law_id,
+    law.date.get_ad_year(),
+    law.date.get_month(),
+    law.date.get_day(),
+    law.patch_id.clone().unwrap_or(NO_PATCH_ID.to_string()),
+  ))
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
-pub struct Find {
-  /// 参照先
-  to: Law,
-  /// 参照元
-  from: Law,
-  /// fromの中でのテキスト内の位置
-  position: Position,
+/// トリプルを`graph`を名前付きグラフとするクワッドにする
+pub fn with_provenance(triples: &[Triple], graph: &NamedNode) -> Vec<Quad> {
+  triples
+    .iter()
+    .map(|t| {
+      Quad::new(
+        t.subject.clone(),
+        t.predicate.clone(),
+        t.object.clone(),
+        graph.clone(),
+      )
+    })
+    .collect()
 }
 
-/// 参照情報を抽出する
-/// - target: 解析対象の法令の情報
-/// - law_map: 法令名や法令IDのテキストをkeyとし，法令全体を表すLawをvalueとするhashmap
-pub fn parse_ref(target: &HashMap<String, Law>, law_map: &HashMap<String, Law>) -> Vec<Find> {
-  // 段落を順番で並べ替える
-  let mut paragraph_list = target
-    .values()
-    .filter(|l| l.paragraph_text.is_some())
-    .cloned()
-    .collect::<Vec<_>>();
-  paragraph_list.sort_by(ord_article);
-
-  // テキストから
-  // - 法令名の出現箇所
-  // - 略称が定義されている箇所
-  // - 同法・同令が出現する箇所
-  // を検索する
-  let mut law_name_list = Vec::new();
+#[test]
+fn check_provenance_graph() {
+  let mut law = minpou(Date::new_ad(2025, 11, 26));
+  law.set_patch_id(String::from("505AC0000000053"));
+  let graph = provenance_graph(&law);
+  assert_eq!(
+    graph.as_str(),
+    format!(
+      "https://github.com/puripuri2100-research/eli/provenance/{}/129AC0000000089_20251126_505AC0000000053",
+      env!("CARGO_PKG_VERSION")
+    )
+  );
+  let mut article = law.clone();
+  article.article_number = parse_article_number("第一条");
+  let triple = EliOntology::HasPart.triple(law.clone(), article);
+  let quads = with_provenance(std::slice::from_ref(&triple), &graph);
+  assert_eq!(quads[0].subject, triple.subject);
+  assert_eq!(quads[0].object, triple.object);
+  assert_eq!(quads[0].graph_name, graph.into());
+}
 
-  let mut result = Vec::new();
+/// 「昭和四十三年」のような和暦の年に一致する正規表現
+const WAREKI_YEAR_PATTERN: &str =
+  "(?<era>明治|大正|昭和|平成|令和)(?<year>元|[一二三四五六七八九十百]+)年";
 
-  for paragraph in paragraph_list.iter() {
-    trace!(
-      "[START] parse paragraph {:?} - {:?}",
-      paragraph.article_number_str(),
-      paragraph.paragraph_number_str()
-    );
-    if let Some(text) = &paragraph.paragraph_text {
-      // 正式名称の一覧を持ってテキスト内検索を行う
-      let mut find_law_name_result = find_law_name(text, law_map, &law_name_list);
-
-      // 略称の定義箇所を検索する
-      let find_abb_def_result = find_abb_def(text);
-      // 今までの項で見つかった法令名と，この項で見つかった略称を紐付けていく
-      let mut linked_abb_def_result = find_abb_def_result
-        .iter()
-        .filter_map(|l| linking_abb_and_full_name(l, &find_law_name_result))
-        .collect::<Vec<_>>();
+/// `WAREKI_YEAR_PATTERN`に一致した元号と年を読む
+/// 「元年」は一年とし，その元号の最後の年より後の年（「大正十六年」など）は`None`とする
+fn wareki_year(caps: &regex::Captures) -> Option<(Era, usize)> {
+  let era = Era::from_text(&caps["era"])?;
+  let year = if &caps["year"] == "元" {
+    1
+  } else {
+    kansuji_to_number(&caps["year"])?
+  };
+  let last_year = match era {
+    Era::Meiji => 45,
+    Era::Taisho => 15,
+    Era::Showa => 64,
+    Era::Heisei => 31,
+    Era::Reiwa => usize::MAX,
+  };
+  (1..=last_year).contains(&year).then_some((era, year))
+}
 
-      // 同法に紐付けさせるためのリスト
-      let mut linked_abb_def_result2 = linked_abb_def_result.clone();
-      find_law_name_result.append(&mut linked_abb_def_result2);
+/// 「昭和四十三年」・「令和元年」のような和暦の年で始まるテキストから，その年を西暦で求める
+/// 「昭和四十三年法律第百号」のように後ろに続くものは無視する
+pub fn ad_year_from_wareki_text(text: &str) -> Option<usize> {
+  static RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(&format!("^{WAREKI_YEAR_PATTERN}")).unwrap());
+  let (era, year) = wareki_year(&RE.captures(text)?)?;
+  Some(Wareki::new(era, year).to_ad())
+}
 
-      // 同法・同令の出現位置を検索する
-      let find_douhou_result = find_douhou(text);
-      // 今までの項で見つかった法令名と略称の情報と，この項で見つかった「同法」・「同令」を紐付けていく
-      let mut linked_douhou_result = find_douhou_result
-        .iter()
-        .filter_map(|l| linking_abb_and_full_name(l, &find_law_name_result))
-        .collect::<Vec<_>>();
+/// 「令和元年五月一日」のような和暦の日付のテキストから日付を作る
+/// `Date`は内部で西暦として持つので，西暦から作った日付とそのまま比較できる
+pub fn date_from_wareki_text(text: &str) -> Option<Date> {
+  static RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(&format!(
+      "^{WAREKI_YEAR_PATTERN}(?<month>[一二三四五六七八九十]+)月(?<day>[一二三四五六七八九十]+)日$"
+    ))
+    .unwrap()
+  });
+  let caps = RE.captures(text)?;
+  let (era, year) = wareki_year(&caps)?;
+  let month = kansuji_to_number(&caps["month"])?;
+  let day = kansuji_to_number(&caps["day"])?;
+  Some(Date::new_wareki(era, year, month, day))
+}
 
-      find_law_name_result.append(&mut linked_douhou_result);
-
-      for find_law_name in find_law_name_result.iter() {
-        if let Some(l) = &find_law_name.find_law {
-          // 条項の検索をする
-          let mut to_law = l.clone();
-          let end = find_joukou(text, &find_law_name.position, &mut to_law);
-          // 結果を返す
-          result.push(Find {
-            to: to_law.clone(),
-            from: paragraph.clone(),
-            position: Position {
-              start: find_law_name.position.start,
-              end,
-            },
-          });
-        }
+/// 「三十一」のような百未満の漢数字を数値にする
+fn kansuji_to_number(s: &str) -> Option<usize> {
+  let digit = |c: char| "〇一二三四五六七八九".chars().position(|d| d == c);
+  let mut total = 0;
+  let mut current = 0;
+  for c in s.chars() {
+    match c {
+      '百' => {
+        total += current.max(1) * 100;
+        current = 0;
       }
-
-      // 略称は他の項でも見るので追加
-      law_name_list.append(&mut linked_abb_def_result);
+      '十' => {
+        total += current.max(1) * 10;
+        current = 0;
+      }
+      c => current = digit(c)?,
     }
-    trace!(
-      "[END] parse paragraph {:?} - {:?}",
-      paragraph.article_number_str(),
-      paragraph.paragraph_number_str()
-    );
   }
-  result
+  Some(total + current)
 }
 
-/// 法令名の検索
-fn find_law_name(
-  text: &str,
-  law_map: &HashMap<String, Law>,
-  find_lst: &[FindLawName],
-) -> Vec<FindLawName> {
-  let text_chars = text.chars().collect::<Vec<_>>();
-  let byte_to_char_map = byte_to_char_index_map(text);
-  let mut lst = Vec::new();
-  let mut v1 = law_map
-    .iter()
-    .map(|(k, v)| (k.clone(), v.clone()))
-    .collect::<Vec<_>>();
-  let mut v2 = find_lst
-    .iter()
-    .map(|v| (v.match_string.clone(), v.find_law.clone().unwrap()))
-    .collect::<Vec<_>>();
-  v1.append(&mut v2);
-  for (find_law_name, law) in v1.iter() {
-    for (start, s) in text.match_indices(find_law_name.as_str()) {
-      let end = start + s.len();
-      let start = byte_to_char_map[start];
-      let end = byte_to_char_map[end];
-      let match_text = s;
-      // law_nameが「法」や「令」のときは隣の文字をチェックする
-      // 隣の文字も含めて「同法」・「方法」・「法人」・「命令」、「政令」・「同令」・「法令」だった時は普遍的過ぎて法令名ではないことが多いので除外
-      // 「〇〇年法律第〇〇号」や「〇〇年〇〇省令第〇〇号」や「〇〇年〇〇委員会規則第〇〇号」や「〇〇年〇〇院規則第〇〇号」なども排除
-      let mut is_universal_name = false;
-      if *find_law_name == "法"
-        && start != 0
-        && (text_chars[start - 1] == '方'
-          || text_chars[start - 1] == '同'
-          || text_chars[start - 1] == '旧')
-      {
-        is_universal_name = true
-      }
-      if find_law_name.ends_with('法') && end < text_chars.len() - 1 && text_chars[end] == '人' {
-        is_universal_name = true
-      }
-      if *find_law_name == "法"
-        && end < text_chars.len() - 1
-        && (text_chars[end] == '令' || text_chars[end] == '律')
-      {
-        is_universal_name = true
-      }
-      if find_law_name.ends_with('法')
-        && end < text_chars.len() - 2
-        && text_chars[end] == '律'
-        && text_chars[end + 1] == '第'
-      {
-        is_universal_name = true
-      }
-      if *find_law_name == "令"
-        && start != 0
-        && (text_chars[start - 1] == '命'
-          || text_chars[start - 1] == '政'
-          || text_chars[start - 1] == '同'
-          || text_chars[start - 1] == '法'
-          || text_chars[start - 1] == '省'
-          || text_chars[start - 1] == '府'
-          || text_chars[start - 1] == '勅'
-          || text_chars[start - 1] == '旧')
-      {
-        is_universal_name = true
-      }
-      if *find_law_name == "令"
-        && end < text_chars.len() - 1
-        && text_chars[end] == '第'
-        && start != 0
-        && (text_chars[start - 1] == '省'
-          || text_chars[start - 1] == '政'
-          || text_chars[start - 1] == '勅'
-          || text_chars[start - 1] == '府')
-      {
-        is_universal_name = true
-      }
-      if find_law_name.ends_with('則')
-        && end < text_chars.len() - 1
-        && text_chars[end] == '第'
-        && start > 2
-        && (text_chars[start - 1] == '規')
-        && (text_chars[start - 2] == '院' || text_chars[start - 2] == '会')
-      {
-        is_universal_name = true
-      }
-
-      if end < text_chars.len() - 1 && text_chars[end] == '」' {
-        is_universal_name = true
-      }
+#[test]
+fn check_date_from_wareki_text() {
+  assert_eq!(kansuji_to_number("三十一"), Some(31));
+  assert_eq!(kansuji_to_number("十"), Some(10));
+  assert_eq!(kansuji_to_number("百二"), Some(102));
+  assert_eq!(
+    date_from_wareki_text("令和元年五月一日"),
+    Some(Date::new_ad(2019, 5, 1))
+  );
+  // 改元の前後でも西暦と同じ順序になる
+  let heisei_last = date_from_wareki_text("平成三十一年四月三十日").unwrap();
+  let reiwa_first = date_from_wareki_text("令和元年五月一日").unwrap();
+  assert!(heisei_last < reiwa_first);
+  assert!(heisei_last < Date::new_ad(2019, 5, 1));
+  let showa_last = date_from_wareki_text("昭和六十四年一月七日").unwrap();
+  assert_eq!(showa_last, Date::new_ad(1989, 1, 7));
+  assert!(showa_last < date_from_wareki_text("平成元年一月八日").unwrap());
+  // 施行日の比較
+  let law = Law::new(
+    Date::new_ad(2025, 4, 1),
+    None,
+    String::from("test"),
+    String::new(),
+    LawType::Act,
+  );
+  assert!(law.get_date() >= date_from_wareki_text("令和七年四月一日").unwrap());
+  assert!(law.get_date() < date_from_wareki_text("令和七年四月二日").unwrap());
+  assert_eq!(date_from_wareki_text("令和七年四月"), None);
+}
 
-      if !is_universal_name {
-        let find = FindLawName {
-          position: Position { start, end },
-          match_string: match_text.to_string(),
-          find_law: Some(law.clone().clone()),
-        };
-        lst = resolve_duplicates(&lst, &find);
-      }
-    }
+#[test]
+fn check_ad_year_from_wareki_text() {
+  for (text, year) in [
+    ("明治二十九年", 1896),
+    ("明治元年", 1868),
+    ("大正元年", 1912),
+    ("大正十五年", 1926),
+    ("昭和元年", 1926),
+    ("昭和四十三年", 1968),
+    ("昭和六十四年", 1989),
+    ("平成元年", 1989),
+    ("平成三十一年", 2019),
+    ("令和元年", 2019),
+    ("令和七年", 2025),
+  ] {
+    assert_eq!(ad_year_from_wareki_text(text), Some(year), "{text}");
   }
-  // 「内閣は、消防施設強化促進法（昭和二十八年法律第八十七号）第三条の規定に基き、この政令を制定する。」
-  // のような文における，法令番号の抽出を抑制したい．
-  // 具体的には，次のパターンに該当するかどうかをチェックする．
-  // - 同一の法令を指し示す法令名のendと法令番号のstartの差が1
-  // - 当該endと当該startの間の文字が'（'
-  // 該当したときに法令番号側を削除する
-  lst = resolve_name_and_number(&lst, text);
+  // 法令番号の先頭の年
+  assert_eq!(
+    ad_year_from_wareki_text("昭和四十三年法律第百号"),
+    Some(1968)
+  );
+  // その元号に無い年や，元号で始まらないもの
+  assert_eq!(ad_year_from_wareki_text("大正十六年"), None);
+  assert_eq!(ad_year_from_wareki_text("平成三十二年"), None);
+  assert_eq!(ad_year_from_wareki_text("昭和〇年"), None);
+  assert_eq!(ad_year_from_wareki_text("同年"), None);
+  assert_eq!(date_from_wareki_text("大正十六年一月一日"), None);
+}
 
-  // 最終的な法令名探索結果
-  lst
+/// 編・章・節・款・目の番号（この順に並べる）
+type StructureNumbers = [Option<ArticleNumber>; 5];
+
+fn push_with_numbers(
+  v: &mut Vec<WithNumberArticle>,
+  numbers: &StructureNumbers,
+  article: &Article,
+) {
+  let mut w = WithNumberArticle::new(article.clone());
+  w.set_part(numbers[0].clone());
+  w.set_chapter(numbers[1].clone());
+  w.set_section(numbers[2].clone());
+  w.set_subsection(numbers[3].clone());
+  w.set_division(numbers[4].clone());
+  v.push(w);
 }
 
-/// 「内閣は、消防施設強化促進法（昭和二十八年法律第八十七号）第三条の規定に基き、この政令を制定する。」
-/// のような文における，法令名と法令番号の重複を解消するために，法令番号を削除する．
-fn resolve_name_and_number(lst: &[FindLawName], text: &str) -> Vec<FindLawName> {
-  let chars = text.chars().collect::<Vec<char>>();
-  // 削除対象
-  let mut remove_index: Vec<usize> = Vec::new();
-  let mut iter = lst.iter().enumerate().peekable();
-  while let Some((i, law_name)) = iter.next() {
-    if let Some(&(j, next_law_name)) = iter.peek() {
-      // 順序の保証がないため，どっちであっても対応できるようにする
-      let (i2, law1, law2) = if law_name.position.end < next_law_name.position.start {
-        (j, law_name, next_law_name)
-      } else {
-        (i, next_law_name, law_name)
-      };
-      if law1.position.end.abs_diff(law2.position.start) == 1 // 差が1
-        && chars.get(law1.position.end) == Some(&'（') // 間にある文字が全角かっこ
-        && law1.find_law.clone().map(|l| l.get_law_id())
-        == law2.find_law.clone().map(|l| l.get_law_id())
-        && !law1.match_string.ends_with("号") // 前側が号で終わらず
-        && law2.match_string.ends_with("号")
-      // 後ろ側が号で終わる
-      {
-        // 削除対象に登録
-        remove_index.push(i2)
-      }
-    }
+fn article_list_from_division(
+  t: &Division,
+  mut numbers: StructureNumbers,
+  v: &mut Vec<WithNumberArticle>,
+) {
+  numbers[4] = Some(t.num.clone());
+  for a in t.children.iter() {
+    push_with_numbers(v, &numbers, a);
   }
-  lst
-    .iter()
-    .enumerate()
-    .filter(|(i, _)| !remove_index.contains(i))
-    .map(|(_, v)| v.clone())
-    .collect::<Vec<_>>()
 }
 
-#[test]
-fn check_resolve_name_and_number() {
-  let s = "陸上交通事業調整法（以下「法」という。）第二条第一項の政令で定める審議会等は、交通政策審議会とする。ただし、法第二条第一項の規定に基づき、国土交通大臣が都市計画法（昭和四十三年法律第百号）第四条第二項に規定する都市計画区域内において調整の区域を決定しようとするときは、当該調整の区域について交通政策審議会及び社会資本整備審議会とする。";
-  let v = vec![
-    FindLawName {
-      position: Position { start: 0, end: 25 },
-      match_string: String::from("陸上交通事業調整法"),
-      find_law: Some(Law::new(
-        Date::new_ad(2000, 1, 1),
-        Some(String::from("陸上交通事業調整法")),
-        String::from("313AC0000000071"),
-        String::from("昭和十三年法律第七十一号"),
-        LawType::Act,
-      )),
-    },
-    FindLawName {
-      position: Position { start: 82, end: 93 },
-      match_string: String::from("昭和四十三年法律第百号"),
-      find_law: Some(Law::new(
-        Date::new_ad(2000, 1, 1),
-        Some(String::from("都市計画法")),
-        String::from("343AC0000000100"),
-        String::from("昭和四十三年法律第百号"),
-        LawType::Act,
-      )),
-    },
-    FindLawName {
-      position: Position { start: 76, end: 81 },
-      match_string: String::from("都市計画法"),
-      find_law: Some(Law::new(
-        Date::new_ad(2000, 1, 1),
-        Some(String::from("都市計画法")),
-        String::from("343AC0000000100"),
-        String::from("昭和四十三年法律第百号"),
-        LawType::Act,
-      )),
-    },
-    FindLawName {
-      position: Position { start: 13, end: 14 },
-      match_string: String::from("法"),
-      find_law: Some(Law::new(
-        Date::new_ad(2000, 1, 1),
-        Some(String::from("陸上交通事業調整法")),
-        String::from("313AC0000000071"),
-        String::from("昭和十三年法律第七十一号"),
-        LawType::Act,
-      )),
-    },
-  ];
-
-  let v2 = resolve_name_and_number(&v, s);
+fn article_list_from_subsection(
+  t: &Subsection,
+  mut numbers: StructureNumbers,
+  v: &mut Vec<WithNumberArticle>,
+) {
+  numbers[3] = Some(t.num.clone());
+  for contents in t.children.iter() {
+    match contents {
+      SubsectionContents::Division(t) => article_list_from_division(t, numbers.clone(), v),
+      SubsectionContents::Article(a) => push_with_numbers(v, &numbers, a),
+    }
+  }
+}
 
-  let v3 = vec![
-    FindLawName {
-      position: Position { start: 0, end: 25 },
-      match_string: String::from("陸上交通事業調整法"),
-      find_law: Some(Law::new(
-        Date::new_ad(2000, 1, 1),
-        Some(String::from("陸上交通事業調整法")),
-        String::from("313AC0000000071"),
-        String::from("昭和十三年法律第七十一号"),
-        LawType::Act,
-      )),
-    },
-    FindLawName {
-      position: Position { start: 76, end: 81 },
-      match_string: String::from("都市計画法"),
-      find_law: Some(Law::new(
-        Date::new_ad(2000, 1, 1),
-        Some(String::from("都市計画法")),
-        String::from("343AC0000000100"),
-        String::from("昭和四十三年法律第百号"),
-        LawType::Act,
-      )),
-    },
-    FindLawName {
-      position: Position { start: 13, end: 14 },
-      match_string: String::from("法"),
-      find_law: Some(Law::new(
-        Date::new_ad(2000, 1, 1),
-        Some(String::from("陸上交通事業調整法")),
-        String::from("313AC0000000071"),
-        String::from("昭和十三年法律第七十一号"),
-        LawType::Act,
-      )),
-    },
-  ];
-  assert_eq!(v2, v3)
+fn article_list_from_section(
+  t: &Section,
+  mut numbers: StructureNumbers,
+  v: &mut Vec<WithNumberArticle>,
+) {
+  numbers[2] = Some(t.num.clone());
+  for contents in t.children.iter() {
+    match contents {
+      SectionContents::Subsection(t) => article_list_from_subsection(t, numbers.clone(), v),
+      SectionContents::Division(t) => article_list_from_division(t, numbers.clone(), v),
+      SectionContents::Article(a) => push_with_numbers(v, &numbers, a),
+    }
+  }
 }
 
-// 略称の定義を検索
-fn find_abb_def(text: &str) -> Vec<FindLawName> {
-  let text_chars = text.chars().collect::<Vec<_>>();
-  let byte_to_char_map = byte_to_char_index_map(text);
-  let mut lst = Vec::new();
-  let abb_re = Regex::new(r"以下「([^」]*(法|令|規則))」").unwrap();
-  for m in abb_re.find_iter(text) {
-    // "以下「"の分足す
-    let start = m.start() + "以下「".len();
-    // 鉤括弧の分引く
-    let end = m.end() - "」".len();
-    // charでのインデックスにマップする
-    let start = byte_to_char_map[start];
-    let end = byte_to_char_map[end];
-    let abb = text_chars[start..end].iter().collect::<String>();
-    // 方法や命令は今回の対象ではないので除く
-    if !(abb.ends_with("方法") || abb.ends_with("命令")) {
-      let find = FindLawName {
-        position: Position { start, end },
-        match_string: abb,
-        find_law: None,
-      };
-      lst = resolve_duplicates(&lst, &find);
+fn article_list_from_chapter(
+  t: &Chapter,
+  mut numbers: StructureNumbers,
+  v: &mut Vec<WithNumberArticle>,
+) {
+  numbers[1] = Some(t.num.clone());
+  for contents in t.children.iter() {
+    match contents {
+      ChapterContents::Section(t) => article_list_from_section(t, numbers.clone(), v),
+      ChapterContents::Article(a) => push_with_numbers(v, &numbers, a),
     }
   }
-  lst
 }
 
-// 「同法」と「同令」で再度検索する
-fn find_douhou(text: &str) -> Vec<FindLawName> {
-  let text_chars = text.chars().collect::<Vec<char>>();
-  let byte_to_char_map = byte_to_char_index_map(text);
-  let mut lst = Vec::new();
-  let douhou_re = Regex::new(r"同法|同令").unwrap();
-  for m in douhou_re.find_iter(text) {
-    let start = byte_to_char_map[m.start()];
-    let end = byte_to_char_map[m.end()];
-    let match_text = m.as_str();
-    if !((end < text_chars.len() - 1 && text_chars[end] == '人')
-      || (end < text_chars.len() - 2 && text_chars[end] == '律' && text_chars[end + 1] == '第'))
-    {
-      let find = FindLawName {
-        position: Position { start, end },
-        match_string: match_text.to_string(),
-        find_law: None,
-      };
-      lst.push(find);
+fn article_list_from_part(t: &Part, mut numbers: StructureNumbers, v: &mut Vec<WithNumberArticle>) {
+  numbers[0] = Some(t.num.clone());
+  for contents in t.children.iter() {
+    match contents {
+      PartContents::Chapter(t) => article_list_from_chapter(t, numbers.clone(), v),
+      PartContents::Article(a) => push_with_numbers(v, &numbers, a),
     }
   }
-  lst
 }
 
-/// 条項番号を検索する
-/// 法令名の後の括弧がきを飛ばし，その後に「第一条」のような文字列が出るのを期待する
-/// "第"が出なかったら法令名だけなので処理を打ち切り
-/// 引数として可変のLawを受け取って内部の情報を更新する
-/// 返り値は最終的な範囲のend
-fn find_joukou(text: &str, position: &Position, law: &mut Law) -> usize {
-  let mut s = String::new();
-  let mut paren_depth = 0_usize;
-  let target_c = [
-    '第', '条', '項', 'の', 'ノ', '一', '二', '三', '四', '五', '六', '七', '八', '九', '十', '百',
-    '千',
-  ];
-  let mut end = position.end;
-  for (i, c) in text.chars().enumerate() {
-    if i < position.end {
-      continue;
-    }
-    if c == '（' {
-      paren_depth += 1;
-      continue;
-    }
-    if c == '）' {
-      paren_depth -= 1;
-      continue;
+/// 本則の条を，その条を含む編・章・節・款・目の番号と一緒に並べる
+/// `with_number_article_list_from_main_provision`は各階層に一つ下の階層の番号を設定してしまうため，ここで辿り直す
+fn with_number_article_list(main_provision: &MainProvision) -> Vec<WithNumberArticle> {
+  let numbers = StructureNumbers::default();
+  let mut v = Vec::new();
+  for contents in main_provision.children.iter() {
+    match contents {
+      MainProvisionContents::Part(t) => article_list_from_part(t, numbers.clone(), &mut v),
+      MainProvisionContents::Chapter(t) => article_list_from_chapter(t, numbers.clone(), &mut v),
+      MainProvisionContents::Section(t) => article_list_from_section(t, numbers.clone(), &mut v),
+      MainProvisionContents::Article(a) => push_with_numbers(&mut v, &numbers, a),
+      MainProvisionContents::Paragraph(_) => (),
     }
-    if paren_depth > 0 {
-      continue;
+  }
+  v
+}
+
+/// `egov_xml_parse`で解析した法令
+#[derive(Debug, Clone)]
+pub struct ParsedLaw {
+  root: Law,
+  /// 法令の各部分（編・章・条・項・附則・別表など）を`number_text`をkeyにして持つ
+  /// `parse_ref`にそのまま渡せるよう，法令全体も空文字列のkeyで含む
+  pub parts: HashMap<String, Law>,
+  /// 法令の構造を表すトリプル
+  pub triples: Vec<Triple>,
+}
+
+impl ParsedLaw {
+  /// 法令全体
+  pub fn root(&self) -> &Law {
+    &self.root
+  }
+
+  /// 法令全体についての題名・法令番号・法令ID・版の日付のトリプル
+  /// `triples`の構造のトリプルと合わせると，法令の構造と書誌情報をELIで表せる
+  pub fn metadata_triples(&self) -> Vec<Triple> {
+    let subject = NamedNode::new(eli::Eli::eli_uri(&self.root)).unwrap();
+    let date = format!(
+      "{:0>4}-{:0>2}-{:0>2}",
+      self.root.date.get_ad_year(),
+      self.root.date.get_month(),
+      self.root.date.get_day()
+    );
+    let mut literals = vec![
+      (
+        EliOntology::IdLocal,
+        Literal::new_simple_literal(&self.root.law_id),
+      ),
+      (
+        EliOntology::VersionDate,
+        Literal::new_typed_literal(date, oxrdf::vocab::xsd::DATE),
+      ),
+    ];
+    if let Some(name) = &self.root.name {
+      literals.push((EliOntology::Title, Literal::new_simple_literal(name)));
     }
-    if paren_depth == 0 && target_c.contains(&c) {
-      s.push(c);
-      end = i;
-      continue;
+    if !self.root.law_id_text.is_empty() {
+      literals.push((
+        EliOntology::Number,
+        Literal::new_simple_literal(&self.root.law_id_text),
+      ));
     }
-    break;
+    literals
+      .into_iter()
+      .map(|(predicate, object)| Triple::new(subject.clone(), predicate.named_node(), object))
+      .collect()
   }
-  // 末尾が'の', 'ノ'ならばそれを取り除く
-  if s.ends_with('の') {
-    s = s.trim_end_matches('の').to_string();
-    end -= 1;
+
+  /// 法令全体のe-govでの公開先（manifestation）についてのトリプル
+  /// e-govの法令XMLは公式の電子版なので，`egov_xml_url`のXMLファイルを公開先とし，
+  /// `eli:legal_value`を公式，形式をXMLとする
+  pub fn manifestation_triples(&self) -> Vec<Triple> {
+    let expression = NamedNode::new(eli::Eli::eli_uri(&self.root)).unwrap();
+    let manifestation = NamedNode::new(self.root.egov_xml_url()).unwrap();
+    let xml = NamedNode::new(eli::MEDIA_TYPE_XML).unwrap();
+    vec![
+      Triple::new(
+        manifestation.clone(),
+        EliOntology::Embodies.named_node(),
+        expression.clone(),
+      ),
+      Triple::new(
+        expression,
+        EliOntology::IsEmbodiedBy.named_node(),
+        manifestation.clone(),
+      ),
+      Triple::new(
+        manifestation.clone(),
+        EliOntology::LegalValue.named_node(),
+        NamedNode::new(eli::LEGAL_VALUE_OFFICIAL).unwrap(),
+      ),
+      Triple::new(
+        manifestation.clone(),
+        EliOntology::Format.named_node(),
+        xml.clone(),
+      ),
+      Triple::new(manifestation, EliOntology::MediaType.named_node(), xml),
+    ]
   }
-  if s.ends_with('ノ') {
-    s = s.trim_end_matches('ノ').to_string();
-    end -= 1;
+
+  /// 本則にある条番号の一覧
+  /// `LawVersions::insert_articles`で版ごとに登録するのに使う
+  pub fn main_articles(&self) -> Vec<ArticleNumber> {
+    let mut articles = self
+      .parts
+      .values()
+      .filter(|l| l.suppl_provision.is_none() && l.appdx_table.is_none())
+      .filter_map(|l| l.article_number.clone())
+      .collect::<Vec<_>>();
+    articles.sort_by(ord_article_number);
+    articles.dedup();
+    articles
   }
-  trace!("find joukou number string: {s}");
-  for a in s.split("第") {
-    if !a.is_empty() {
-      let s2 = format!("第{a}");
-      trace!("find joukou number string(split): {s2}");
-      let num = parse_article_number(&s2);
-      trace!("parsed article number: {num:?}");
-      if let Some(num) = num {
-        if a.ends_with("条") {
-          law.article_number = Some(num)
-        } else if a.ends_with("項") {
-          law.paragraph_number = Some(num)
-        } else if a.ends_with("編") {
-          law.part_number = Some(num)
-        } else if a.ends_with("章") {
-          law.chapter_number = Some(num)
-        } else if a.ends_with("節") {
-          law.section_number = Some(num)
-        } else if a.ends_with("款") {
-          law.subsection_number = Some(num)
-        } else if a.ends_with("目") {
-          law.division_number = Some(num)
+
+  /// 本則の`start`から`end`までの条のうち，この法令に実際にある条を順に返す
+  /// 番号を一つずつ増やすのではなく条の一覧から選ぶので，「第三条の二」のような枝番号の条も含み，
+  /// 存在しない「第三条の一」のような条は含まない
+  pub fn article_range(&self, start: &ArticleNumber, end: &ArticleNumber) -> Vec<ArticleNumber> {
+    let mut articles = self
+      .parts
+      .values()
+      .filter(|l| l.suppl_provision.is_none() && l.appdx_table.is_none())
+      .filter_map(|l| l.article_number.clone())
+      .filter(|num| {
+        ord_article_number(start, num) != Ordering::Greater
+          && ord_article_number(num, end) != Ordering::Greater
+      })
+      .collect::<Vec<_>>();
+    articles.sort_by(ord_article_number);
+    articles.dedup();
+    articles
+  }
+
+  /// 「第三条から第五条まで」のように範囲を持つ条番号を，`article_range`で実際にある条に展開する
+  /// 範囲を持たない場合は，その条があればそれだけを返す
+  pub fn expand_article_range(&self, num: &ArticleNumber) -> Vec<ArticleNumber> {
+    let start = ArticleNumber {
+      range_end_numbers: Vec::new(),
+      ..num.clone()
+    };
+    let end = match num.range_end_numbers.split_first() {
+      Some((base_number, eda_numbers)) => ArticleNumber {
+        base_number: *base_number,
+        eda_numbers: eda_numbers.to_vec(),
+        range_end_numbers: Vec::new(),
+      },
+      None => start.clone(),
+    };
+    self.article_range(&start, &end)
+  }
+}
+
+#[test]
+fn check_article_range() {
+  // 第三条の二・第九十条・第七百九条・第七百十条だけを持つ民法
+  let law = egov_xml_parse(
+    include_bytes!("../tests/fixtures/129AC0000000089_20230614.xml"),
+    Date::new_ad(2023, 6, 14),
+    Some(String::from("民法")),
+    String::from("129AC0000000089"),
+    Some(String::from("505AC0000000053")),
+  )
+  .unwrap();
+  let range = |start: &str, end: &str| {
+    law
+      .article_range(
+        &parse_article_number(start).unwrap(),
+        &parse_article_number(end).unwrap(),
+      )
+      .iter()
+      .map(|num| num.num_str())
+      .collect::<Vec<_>>()
+  };
+  // 範囲の中の枝番号の条は含み，無い条は含まない
+  assert_eq!(range("第三条", "第七百九条"), vec!["3_2", "90", "709"]);
+  assert_eq!(range("第三条の二", "第九十条"), vec!["3_2", "90"]);
+  assert_eq!(range("第三条の三", "第七百十条"), vec!["90", "709", "710"]);
+  assert!(range("第四条", "第八十九条").is_empty());
+  // 条番号の範囲をそのまま展開する
+  let expand = |num: ArticleNumber| {
+    law
+      .expand_article_range(&num)
+      .iter()
+      .map(|num| num.num_str())
+      .collect::<Vec<_>>()
+  };
+  assert_eq!(
+    expand(ArticleNumber {
+      base_number: 3,
+      eda_numbers: Vec::new(),
+      range_end_numbers: vec![90],
+    }),
+    vec!["3_2", "90"]
+  );
+  assert_eq!(
+    expand(parse_article_number("第九十条").unwrap()),
+    vec!["90"]
+  );
+  assert!(expand(parse_article_number("第九十一条").unwrap()).is_empty());
+}
+
+/// `japanese_law_xml_schema`が対応している法令標準XMLスキーマの版
+pub const SUPPORTED_LAW_XML_SCHEMA_VERSION: u32 = 3;
+
+/// 法令XMLの根要素で宣言されている法令標準XMLスキーマの版を調べる
+/// `xsi:noNamespaceSchemaLocation="XMLSchemaForJapaneseLaw_v3.xsd"`のような宣言から読み取る
+/// e-govの法令XMLの多くは宣言を持たないので，そのときは`None`を返す
+pub fn law_xml_schema_version(buf: &[u8]) -> Option<u32> {
+  let text = String::from_utf8_lossy(buf);
+  let start = text.find("<Law ")?;
+  let end = start + text[start..].find('>')?;
+  static RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"XMLSchemaForJapaneseLaw_v(\d+)\.xsd").unwrap());
+  RE.captures(&text[start..end])?[1].parse().ok()
+}
+
+pub fn egov_xml_parse(
+  buf: &[u8],
+  date: Date,
+  law_name: Option<String>,
+  law_id: String,
+  patch_id: Option<String>,
+) -> Result<ParsedLaw> {
+  // 対応していない版のスキーマで書かれたXMLは，解析に失敗したときにその版を示すエラーにする
+  // 版が違っても構造が同じ部分しか使っていなければ解析できるので，先に弾くことはしない
+  let parsed_law =
+    japanese_law_xml_schema::parse_xml(buf).map_err(|err| match law_xml_schema_version(buf) {
+      Some(found) if found != SUPPORTED_LAW_XML_SCHEMA_VERSION => {
+        EliError::UnsupportedSchemaVersion {
+          found,
+          supported: SUPPORTED_LAW_XML_SCHEMA_VERSION,
+          reason: EliError::from(err).to_string(),
         }
       }
-    }
+      _ => EliError::from(err),
+    })?;
+  let law_id_text = parsed_law.law_num;
+  // 法令名が与えられなかったときはXML中の題名を使う
+  let law_name = law_name.or_else(|| {
+    parsed_law
+      .law_body
+      .law_title
+      .as_ref()
+      .map(|title| title.text.to_string())
+      .filter(|title| !title.is_empty())
+  });
+  let mut law = Law::new(date, law_name, law_id, law_id_text, parsed_law.law_type);
+  if let Some(patch_id) = patch_id {
+    law.set_patch_id(patch_id);
   }
-  end
-}
+  let mut law_data = HashMap::new();
+  law_data.insert(String::new(), law.clone());
 
-// 各charの始まりに該当するバイト位置をcharの位置に変換するためのマップ
-fn byte_to_char_index_map(text: &str) -> Vec<usize> {
-  // 各バイト位置に対する char インデックス
-  let mut map = vec![0; text.len() + 1];
-  let mut char_index = 0;
-  for (byte_index, _) in text.char_indices() {
-    map[byte_index] = char_index;
-    char_index += 1;
+  let mut v_triple = Vec::new();
+
+  // 編番号・章番号・条番号などを登録
+  let toc_list = toc_list_from_main_provision(&parsed_law.law_body.main_provision);
+  for toc in toc_list.iter() {
+    let mut law_tmp = law.clone();
+    law_tmp.set_numbers_from_toc(toc);
+    law_data.insert(law_tmp.number_text(), law_tmp.clone());
+    let parent = law_tmp.parent();
+    v_triple.push(EliOntology::HasPart.triple(parent.clone(), law_tmp.clone()));
+    v_triple.push(EliOntology::IsPartOf.triple(law_tmp.clone(), parent.clone()));
   }
-  map[text.len()] = char_index;
-  map
-}
 
-/// 範囲が重複した法令について、重複を解消する
-/// 原則として範囲が大きい方が優先
-/// 同じ範囲だった場合は後から見つかったものを優先（法令名中の略称の場合なので優先される）
-/// 入力のfind_lstは常に解消済みのものであるとする
-fn resolve_duplicates(find_lst: &[FindLawName], find: &FindLawName) -> Vec<FindLawName> {
-  let mut lst = Vec::new();
-  let mut ok = false;
-  for f in find_lst.iter() {
-    if ok {
-      // 重複する物が見つかったのであとはスルー
-      lst.push(f.clone())
-    } else if f.position.start == find.position.start && f.position.end == find.position.end {
-      // 完全に一致するものが見つかったので置き換え
-      if !ok {
-        lst.push(find.clone());
-        ok = true;
-      }
-    } else if f.position.start >= find.position.start && f.position.end <= find.position.end {
-      // 見つかったものの方が小さかったので置き換え
-      if !ok {
-        lst.push(find.clone());
-        ok = true
-      }
-    } else if f.position.start <= find.position.start && f.position.end >= find.position.end {
-      // 見つかったものの方が大きかったので置き換えない
-      lst.push(f.clone());
-      ok = true
-    } else {
-      // 無関係だった
-      lst.push(f.clone())
+  // 前文の段落を登録する
+  // 前文は条を持たないので，項と同じように段落番号で登録する
+  if let Some(preamble) = &parsed_law.law_body.preamble {
+    let mut preamble_law = law.clone();
+    preamble_law.set_preamble();
+    law_data.insert(preamble_law.number_text(), preamble_law.clone());
+    v_triple.push(EliOntology::HasPart.triple(law.clone(), preamble_law.clone()));
+    v_triple.push(EliOntology::IsPartOf.triple(preamble_law.clone(), law.clone()));
+    for para in preamble.children.iter() {
+      let mut law_tmp = preamble_law.clone();
+      law_tmp.set_paragraph_number(para.num.clone());
+      law_tmp.set_paragraph_text(text_from_paragraph_list(std::slice::from_ref(para)));
+      law_tmp.set_paragraph_items(item_text_list(para));
+      law_data.insert(law_tmp.number_text(), law_tmp.clone());
+      v_triple.push(EliOntology::HasPart.triple(preamble_law.clone(), law_tmp.clone()));
+      v_triple.push(EliOntology::IsPartOf.triple(law_tmp.clone(), preamble_law.clone()));
     }
   }
-  // 重複は無かったので追加
-  if !ok {
-    lst.push(find.clone())
+
+  // 段落番号を登録する
+  let (_, paragraphs) =
+    with_number_article_list_from_main_provision(&parsed_law.law_body.main_provision);
+  let with_number_articles = with_number_article_list(&parsed_law.law_body.main_provision);
+  for a in with_number_articles.iter() {
+    let mut law_tmp = law.clone();
+    law_tmp.set_numbers(a);
+    for para in a.get_article().paragraph.iter() {
+      let mut law_tmp2 = law_tmp.clone();
+      law_tmp2.set_paragraph_number(para.num.clone());
+      law_tmp2.set_paragraph_text(text_from_paragraph_list(std::slice::from_ref(para)));
+      law_tmp2.set_paragraph_items(item_text_list(para));
+      law_data.insert(law_tmp2.number_text(), law_tmp2.clone());
+      v_triple.push(EliOntology::HasPart.triple(law_tmp.clone(), law_tmp2.clone()));
+      v_triple.push(EliOntology::IsPartOf.triple(law_tmp2.clone(), law_tmp.clone()));
+    }
+  }
+  for para_list in paragraphs.iter() {
+    for para in para_list.iter() {
+      let mut law_tmp = law.clone();
+      law_tmp.set_paragraph_number(para.num.clone());
+      law_tmp.set_paragraph_text(text_from_paragraph_list(std::slice::from_ref(para)));
+      law_tmp.set_paragraph_items(item_text_list(para));
+      law_data.insert(para.num.paragraph_text(), law_tmp.clone());
+      v_triple.push(EliOntology::HasPart.triple(law.clone(), law_tmp.clone()));
+      v_triple.push(EliOntology::IsPartOf.triple(law_tmp.clone(), law.clone()));
+    }
   }
-  lst
-}
 
-// find_abb_defとsearch_douhouの実行結果と、find_law_nameの実行結果を比較して、抽出位置が直前のものを紐づける
-fn linking_abb_and_full_name(
-  abb_info: &FindLawName,
-  full_name_info_list: &[FindLawName],
-) -> Option<FindLawName> {
-  let mut result: Option<FindLawName> = None;
-  for full_name_info in full_name_info_list.iter() {
-    if full_name_info.position.end <= abb_info.position.start {
-      if let Some(ref old_result) = result
-        && full_name_info.position.end < old_result.position.end
-      {
-        // すでに見つかったものよりも遠い場合は上書きしない
-        continue;
-      } else {
-        result = Some(full_name_info.clone())
+  // 別表を登録する
+  for appdx_table in parsed_law.law_body.appdx_table.iter() {
+    let mut law_tmp = law.clone();
+    law_tmp.set_appdx_table(appdx_table.num.clone().unwrap_or_default());
+    law_data.insert(law_tmp.number_text(), law_tmp.clone());
+    v_triple.push(EliOntology::HasAnnex.triple(law.clone(), law_tmp.clone()));
+    v_triple.push(EliOntology::IsAnnexOf.triple(law_tmp.clone(), law.clone()));
+  }
+
+  // 附則の条・項を登録する
+  for suppl_provision in parsed_law.law_body.suppl_provision.iter() {
+    let mut suppl_law = law.clone();
+    suppl_law.set_suppl_provision(suppl_provision.amend_law_num.clone().unwrap_or_default());
+    law_data.insert(suppl_law.number_text(), suppl_law.clone());
+    v_triple.push(EliOntology::HasPart.triple(law.clone(), suppl_law.clone()));
+    v_triple.push(EliOntology::IsPartOf.triple(suppl_law.clone(), law.clone()));
+    let (articles, paragraphs) = article_list_from_suppl_provision(suppl_provision);
+    for article in articles.iter() {
+      let mut law_tmp = suppl_law.clone();
+      law_tmp.article_number = Some(article.num.clone());
+      law_data.insert(law_tmp.number_text(), law_tmp.clone());
+      v_triple.push(EliOntology::HasPart.triple(suppl_law.clone(), law_tmp.clone()));
+      v_triple.push(EliOntology::IsPartOf.triple(law_tmp.clone(), suppl_law.clone()));
+      for para in article.paragraph.iter() {
+        let mut law_tmp2 = law_tmp.clone();
+        law_tmp2.set_paragraph_number(para.num.clone());
+        law_tmp2.set_paragraph_text(text_from_paragraph_list(std::slice::from_ref(para)));
+        law_tmp2.set_paragraph_items(item_text_list(para));
+        law_data.insert(law_tmp2.number_text(), law_tmp2.clone());
+        v_triple.push(EliOntology::HasPart.triple(law_tmp.clone(), law_tmp2.clone()));
+        v_triple.push(EliOntology::IsPartOf.triple(law_tmp2.clone(), law_tmp.clone()));
       }
     }
+    for para in paragraphs.iter().flatten() {
+      let mut law_tmp = suppl_law.clone();
+      law_tmp.set_paragraph_number(para.num.clone());
+      law_tmp.set_paragraph_text(text_from_paragraph_list(std::slice::from_ref(para)));
+      law_tmp.set_paragraph_items(item_text_list(para));
+      law_data.insert(law_tmp.number_text(), law_tmp.clone());
+      v_triple.push(EliOntology::HasPart.triple(suppl_law.clone(), law_tmp.clone()));
+      v_triple.push(EliOntology::IsPartOf.triple(law_tmp.clone(), suppl_law.clone()));
+    }
   }
-  if let Some(result) = result {
-    Some(FindLawName {
-      find_law: result.find_law.clone(),
-      ..abb_info.clone()
+  Ok(ParsedLaw {
+    root: law,
+    parts: law_data,
+    triples: v_triple,
+  })
+}
+
+/// 項を柱書きと号ごとのテキストに分ける
+fn item_text_list(para: &Paragraph) -> Vec<ItemText> {
+  text_info_list_from_paragraph(std::slice::from_ref(para))
+    .into_iter()
+    .map(|(index, text)| ItemText {
+      item_numbers: index.items,
+      text,
     })
-  } else {
-    None
+    .collect()
+}
+
+/// 項のテキストの`position`の位置の文字が含まれる号の番号を返す
+/// 号ごとのテキストは改行でつながれて項のテキストになっている
+fn item_numbers_at(items: &[ItemText], position: usize) -> Vec<Option<ArticleNumber>> {
+  let mut start = 0;
+  for item in items.iter() {
+    let end = start + item.text.chars().count();
+    if position < end {
+      return item.item_numbers.clone();
+    }
+    // 区切りの改行の分
+    start = end + 1;
   }
+  Vec::new()
+}
+
+#[cfg(test)]
+const TEST_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Law Era="Reiwa" Lang="ja" LawType="Act" Num="1" Year="7"><LawNum>令和七年法律第一号</LawNum><LawBody><LawTitle Kana="てすとほう">テスト法</LawTitle>
+<MainProvision>
+<Article Num="1"><ArticleCaption>（目的）</ArticleCaption><ArticleTitle>第一条</ArticleTitle>
+<Paragraph Num="1"><ParagraphNum/><ParagraphSentence><Sentence Num="1">この法律は、試験のために定める。</Sentence></ParagraphSentence></Paragraph>
+<Paragraph Num="2"><ParagraphNum>２</ParagraphNum><ParagraphSentence><Sentence Num="1">民法第九十条の規定により、無効とする。</Sentence></ParagraphSentence></Paragraph>
+</Article>
+<Article Num="2"><ArticleCaption>（準用）</ArticleCaption><ArticleTitle>第二条</ArticleTitle>
+<Paragraph Num="1"><ParagraphNum/><ParagraphSentence><Sentence Num="1">民法第九十一条の規定は、前条の場合について準用する。</Sentence></ParagraphSentence></Paragraph>
+</Article>
+<Article Num="3"><ArticleCaption>（適用除外）</ArticleCaption><ArticleTitle>第三条</ArticleTitle>
+<Paragraph Num="1"><ParagraphNum/><ParagraphSentence><Sentence Num="1">次に掲げる規定は、適用しない。</Sentence></ParagraphSentence>
+<Item Num="1"><ItemTitle>一</ItemTitle><ItemSentence><Sentence Num="1">この法律の第一条</Sentence></ItemSentence></Item>
+<Item Num="2"><ItemTitle>二</ItemTitle><ItemSentence><Sentence Num="1">民法第九十二条</Sentence></ItemSentence></Item>
+</Paragraph>
+</Article>
+<Article Num="4"><ArticleCaption>（別表）</ArticleCaption><ArticleTitle>第四条</ArticleTitle>
+<Paragraph Num="1"><ParagraphNum/><ParagraphSentence><Sentence Num="1">別表第一に掲げる者については、民法別表第二の規定は、適用しない。</Sentence></ParagraphSentence></Paragraph>
+</Article>
+</MainProvision>
+<SupplProvision><SupplProvisionLabel>附　則</SupplProvisionLabel>
+<Article Num="1"><ArticleCaption>（施行期日）</ArticleCaption><ArticleTitle>第一条</ArticleTitle>
+<Paragraph Num="1"><ParagraphNum/><ParagraphSentence><Sentence Num="1">この法律は、令和八年四月一日から施行する。ただし、第三条及び附則第二条の規定は、公布の日から施行する。</Sentence></ParagraphSentence></Paragraph>
+</Article>
+<Article Num="2"><ArticleCaption>（経過措置）</ArticleCaption><ArticleTitle>第二条</ArticleTitle>
+<Paragraph Num="1"><ParagraphNum/><ParagraphSentence><Sentence Num="1">この法律の施行前にした行為については、第一条第二項の規定にかかわらず、なお従前の例による。</Sentence></ParagraphSentence></Paragraph>
+<Paragraph Num="2"><ParagraphNum>２</ParagraphNum><ParagraphSentence><Sentence Num="1">第九条の規定は、民法第九十三条の場合には適用しない。</Sentence></ParagraphSentence></Paragraph>
+</Article>
+</SupplProvision>
+<AppdxTable Num="1"><AppdxTableTitle>別表第一（第四条関係）</AppdxTableTitle><TableStruct><Table><TableRow><TableColumn><Sentence>試験者</Sentence></TableColumn></TableRow></Table></TableStruct></AppdxTable>
+</LawBody></Law>"#;
+
+/// テストで使う，`paragraph(text)`の段落だけを解析した参照
+#[cfg(test)]
+fn refs(text: &str, law_map: &HashMap<String, Law>) -> Vec<Find> {
+  parse_ref(&HashMap::from([(String::new(), paragraph(text))]), law_map)
 }
 
 #[test]
-fn check_linking() {
-  let f = FindLawName {
-    position: Position { start: 20, end: 27 },
-    match_string: String::new(),
-    find_law: None,
+fn check_find_from_paragraph() {
+  let minpou = minpou(Date::new_ad(2023, 6, 14));
+  let law_map = HashMap::from([(String::from("民法"), minpou)]);
+  let finds = analyze(
+    TEST_XML.as_bytes(),
+    Date::new_ad(2025, 4, 1),
+    None,
+    String::from("507AC0000000001"),
+    None,
+    &law_map,
+    &ParseConfig::default(),
+  )
+  .unwrap();
+  assert!(!finds.is_empty());
+  // 参照元は法令全体や条ではなく，参照を含む項
+  for find in finds.iter() {
+    assert!(find.from.paragraph_number.is_some(), "{find:?}");
+    assert!(find.from.article_number.is_some(), "{find:?}");
+  }
+  let from = |to: &str| {
+    let find = finds.iter().find(|f| f.to.citation_text() == to).unwrap();
+    (find.from.number_text(), find.from_item.clone())
   };
-  let lst = vec![
-    FindLawName {
-      position: Position { start: 5, end: 7 },
-      match_string: String::new(),
-      find_law: Some(Law::new(
-        Date::new_ad(2025, 11, 26),
-        None,
-        String::from("test1"),
-        String::new(),
-        LawType::Act,
-      )),
-    },
-    FindLawName {
-      position: Position { start: 8, end: 10 },
-      match_string: String::new(),
-      find_law: Some(Law::new(
-        Date::new_ad(2025, 11, 26),
-        None,
-        String::from("test2"),
-        String::new(),
-        LawType::Act,
-      )),
-    },
-    FindLawName {
-      position: Position { start: 29, end: 31 },
-      match_string: String::new(),
-      find_law: Some(Law::new(
-        Date::new_ad(2025, 11, 26),
-        None,
-        String::from("test3"),
-        String::new(),
-        LawType::Act,
-      )),
-    },
-  ];
-  let result = linking_abb_and_full_name(&f, &lst);
-  assert!(result.is_some());
+  assert_eq!(from("民法第九十条"), (String::from("第一条２"), Vec::new()));
+  // 号の中の参照は，項と号で区別する
   assert_eq!(
-    result.unwrap().find_law.map(|l| l.get_law_id()),
-    Some(String::from("test2"))
+    from("民法第九十二条"),
+    (
+      String::from("第三条１"),
+      vec![parse_article_number("第二号")]
+    )
+  );
+  assert_eq!(
+    from("民法第九十三条"),
+    (String::from("附則第二条２"), Vec::new())
+  );
+}
+
+#[test]
+fn check_suppl_provision() {
+  let law_data = egov_xml_parse(
+    TEST_XML.as_bytes(),
+    Date::new_ad(2025, 4, 1),
+    None,
+    String::from("507AC0000000001"),
+    None,
+  )
+  .unwrap()
+  .parts;
+  // 本則と附則の第一条第一項は別々に登録される
+  let first_paragraphs = law_data
+    .values()
+    .filter(|l| {
+      l.article_number == parse_article_number("第一条")
+        && l.paragraph_number == parse_article_number("第一項")
+    })
+    .map(|l| l.get_suppl_provision())
+    .collect::<HashSet<_>>();
+  assert_eq!(first_paragraphs, HashSet::from([None, Some(String::new())]));
+
+  let minpou = minpou(Date::new_ad(2025, 11, 26));
+  let mut law_map = HashMap::new();
+  law_map.insert(String::from("民法"), minpou);
+  let finds = parse_ref(&law_data, &law_map)
+    .into_iter()
+    .filter(|f| f.from.get_suppl_provision().is_some())
+    .collect::<Vec<_>>();
+  assert_eq!(
+    finds
+      .iter()
+      .map(|f| (f.is_self_reference(), f.to.citation_text()))
+      .collect::<Vec<_>>(),
+    vec![
+      // 「この法律は、」・「この法律の施行前に」
+      (true, String::from("テスト法")),
+      (true, String::from("テスト法第三条")),
+      (true, String::from("テスト法")),
+      (true, String::from("テスト法第一条第二項")),
+      (false, String::from("民法第九十三条")),
+    ]
+  );
+  assert_eq!(finds[1].position, Position { start: 25, end: 28 });
+  assert_eq!(finds[1].to.get_suppl_provision(), None);
+}
+
+#[test]
+fn check_appdx_table() {
+  assert_eq!(parse_appdx_table_number(""), Some(String::new()));
+  assert_eq!(parse_appdx_table_number("第一"), Some(String::from("1")));
+  assert_eq!(
+    parse_appdx_table_number("第一の二"),
+    Some(String::from("1_2"))
+  );
+  let ParsedLaw {
+    parts: law_data,
+    triples,
+    ..
+  } = egov_xml_parse(
+    TEST_XML.as_bytes(),
+    Date::new_ad(2025, 4, 1),
+    None,
+    String::from("507AC0000000001"),
+    None,
+  )
+  .unwrap();
+  let table = law_data.get("別表第一").unwrap();
+  assert_eq!(table.get_appdx_table(), Some(String::from("1")));
+  assert!(eli::Eli::eli_uri(table).ends_with("/appdx_table1"));
+  assert_eq!(
+    triples
+      .iter()
+      .filter(|t| t.predicate.as_str() == "http://data.europa.eu/eli/ontology#has_annex")
+      .count(),
+    1
+  );
+
+  let minpou = minpou(Date::new_ad(2025, 11, 26));
+  let mut law_map = HashMap::new();
+  law_map.insert(String::from("民法"), minpou);
+  let finds = parse_ref(&law_data, &law_map)
+    .into_iter()
+    .filter(|f| f.from.article_number == parse_article_number("第四条"))
+    .map(|f| (f.to.citation_text(), f.position))
+    .collect::<Vec<_>>();
+  assert_eq!(
+    finds,
+    vec![
+      (
+        String::from("民法別表第二"),
+        Position { start: 15, end: 21 }
+      ),
+      (
+        String::from("テスト法別表第一"),
+        Position { start: 0, end: 4 }
+      ),
+    ]
+  );
+}
+
+#[test]
+fn check_validate_number_hierarchy() {
+  let mut law = Law::new(
+    Date::new_ad(2025, 4, 1),
+    None,
+    String::from("507AC0000000001"),
+    String::new(),
+    LawType::Act,
+  );
+  assert!(law.validate_number_hierarchy().is_ok());
+  // 編の無い法令の章・節
+  law.chapter_number = parse_article_number("第一条");
+  law.section_number = parse_article_number("第二条");
+  assert!(law.validate_number_hierarchy().is_ok());
+  // 章の無い節
+  law.chapter_number = None;
+  assert_eq!(
+    law.validate_number_hierarchy(),
+    Err(EliError::NumberHierarchy {
+      law_id: String::from("507AC0000000001"),
+      child: String::from("節"),
+      parent: String::from("章"),
+    })
+  );
+  // 款を飛ばした目
+  law.chapter_number = parse_article_number("第一条");
+  law.division_number = parse_article_number("第一条");
+  assert!(law.validate_number_hierarchy().is_err());
+}
+
+#[test]
+fn check_set_numbers_from_toc() {
+  let date = Date::new_ad(2025, 11, 26);
+  let mut law_map = HashMap::new();
+  law_map.insert(String::from("民法"), minpou(date));
+  let law = Law::new(
+    date,
+    Some(String::from("テスト法")),
+    String::from("test"),
+    String::new(),
+    LawType::Act,
+  );
+  // 前の目次項目の節・目が残った章の目次項目
+  let mut toc = Toc::default();
+  toc.set_chapter(parse_article_number("第二条"));
+  toc.set_section(parse_article_number("第一条"));
+  toc.set_division(parse_article_number("第三条"));
+  toc.set_title(Some(String::from("第二章　民法第九十条の特例")));
+  let mut chapter = law.clone();
+  chapter.set_numbers_from_toc(&toc);
+  assert_eq!(chapter.chapter_number, parse_article_number("第二条"));
+  assert_eq!(chapter.section_number, None);
+  assert_eq!(chapter.division_number, None);
+  assert_eq!(chapter.article_number, None);
+  assert!(chapter.validate_number_hierarchy().is_ok());
+
+  // 目次項目は本文を持たないので，題名の中の法令名から参照は作られない
+  let mut target = HashMap::new();
+  target.insert(chapter.number_text(), chapter.clone());
+  assert!(parse_ref(&target, &law_map).is_empty());
+
+  // 同じ章の項は参照元になる
+  let mut paragraph = chapter.clone();
+  paragraph.article_number = parse_article_number("第五条");
+  paragraph.set_paragraph_number(parse_article_number("第一項").unwrap());
+  paragraph.set_paragraph_text(String::from("民法第九十条の規定は、適用しない。"));
+  target.insert(paragraph.number_text(), paragraph);
+  let finds = parse_ref(&target, &law_map);
+  assert_eq!(finds.len(), 1);
+  assert_eq!(
+    finds[0].from.article_number_str(),
+    Some(String::from("article5"))
+  );
+  assert_eq!(
+    finds[0].to.article_number_str(),
+    Some(String::from("article90"))
+  );
+}
+
+#[test]
+fn check_fixture_minpou() {
+  // `gen-fixture`で民法の第三条の二・第九十条・第七百九条・第七百十条だけに切り詰めたもの
+  let law_data = egov_xml_parse(
+    include_bytes!("../tests/fixtures/129AC0000000089_20230614.xml"),
+    Date::new_ad(2023, 6, 14),
+    Some(String::from("民法")),
+    String::from("129AC0000000089"),
+    Some(String::from("505AC0000000053")),
+  )
+  .unwrap()
+  .parts;
+  let numbers = law_structure(&law_data)
+    .iter()
+    .filter(|l| l.paragraph_number.is_some())
+    .map(|l| {
+      assert!(l.validate_number_hierarchy().is_ok());
+      (
+        l.number_text(),
+        [&l.part_number, &l.chapter_number, &l.section_number]
+          .map(|n| n.as_ref().map(|n| n.base_number)),
+      )
+    })
+    .collect::<Vec<_>>();
+  assert_eq!(
+    numbers,
+    vec![
+      (String::from("第三条の二１"), [Some(1), Some(2), Some(2)]),
+      (String::from("第九十条１"), [Some(1), Some(5), Some(1)]),
+      (String::from("第七百九条１"), [Some(3), Some(5), None]),
+      (String::from("第七百十条１"), [Some(3), Some(5), None]),
+    ]
+  );
+}
+
+#[test]
+fn check_metadata_triples() {
+  let parsed = egov_xml_parse(
+    include_bytes!("../tests/fixtures/129AC0000000089_20230614.xml"),
+    Date::new_ad(2023, 6, 14),
+    Some(String::from("民法")),
+    String::from("129AC0000000089"),
+    Some(String::from("505AC0000000053")),
+  )
+  .unwrap();
+  let triples = parsed
+    .metadata_triples()
+    .iter()
+    .map(|t| t.to_string())
+    .collect::<Vec<_>>();
+  let subject = eli::Eli::eli_uri(parsed.root());
+  assert_eq!(
+    triples,
+    vec![
+      format!("<{subject}> <http://data.europa.eu/eli/ontology#id_local> \"129AC0000000089\""),
+      format!(
+        "<{subject}> <http://data.europa.eu/eli/ontology#version_date> \"2023-06-14\"^^<http://www.w3.org/2001/XMLSchema#date>"
+      ),
+      format!("<{subject}> <http://data.europa.eu/eli/ontology#title> \"民法\""),
+      format!(
+        "<{subject}> <http://data.europa.eu/eli/ontology#number> \"明治二十九年法律第八十九号\""
+      ),
+    ]
+  );
+}
+
+#[test]
+fn check_manifestation_triples() {
+  let parsed = egov_xml_parse(
+    include_bytes!("../tests/fixtures/129AC0000000089_20230614.xml"),
+    Date::new_ad(2023, 6, 14),
+    Some(String::from("民法")),
+    String::from("129AC0000000089"),
+    Some(String::from("505AC0000000053")),
+  )
+  .unwrap();
+  let triples = parsed
+    .manifestation_triples()
+    .iter()
+    .map(|t| t.to_string())
+    .collect::<Vec<_>>();
+  let expression = eli::Eli::eli_uri(parsed.root());
+  let manifestation =
+    "https://laws.e-gov.go.jp/api/2/law_file/xml/129AC0000000089_20230614_505AC0000000053";
+  let xml = "<http://www.iana.org/assignments/media-types/application/xml>";
+  assert_eq!(
+    triples,
+    vec![
+      format!("<{manifestation}> <http://data.europa.eu/eli/ontology#embodies> <{expression}>"),
+      format!(
+        "<{expression}> <http://data.europa.eu/eli/ontology#is_embodied_by> <{manifestation}>"
+      ),
+      format!(
+        "<{manifestation}> <http://data.europa.eu/eli/ontology#legal_value> <http://data.europa.eu/eli/ontology#LegalValue-official>"
+      ),
+      format!("<{manifestation}> <http://data.europa.eu/eli/ontology#format> {xml}"),
+      format!("<{manifestation}> <http://data.europa.eu/eli/ontology#media_type> {xml}"),
+    ]
+  );
+}
+
+#[test]
+fn check_law_id_and_law_id_text() {
+  let law_data = egov_xml_parse(
+    include_bytes!("../tests/fixtures/129AC0000000089_20230614.xml"),
+    Date::new_ad(2023, 6, 14),
+    Some(String::from("民法")),
+    String::from("129AC0000000089"),
+    Some(String::from("505AC0000000053")),
+  )
+  .unwrap();
+  let root = law_data.root();
+  assert_eq!(root.get_law_id(), "129AC0000000089");
+  assert_eq!(root.get_law_id_text(), "明治二十九年法律第八十九号");
+  // 条項のすべてで法令IDと法令番号の組が法令全体のものと一致する
+  for law in law_data.parts.values() {
+    assert_eq!(
+      (law.get_law_id(), law.get_law_id_text()),
+      (root.get_law_id(), root.get_law_id_text())
+    );
+  }
+  // JSONLの各レコードの参照元・参照先の両方に法令IDと法令番号が出力される
+  let from = law_data
+    .parts
+    .values()
+    .find(|l| l.paragraph_number.is_some())
+    .unwrap()
+    .clone();
+  let find = Find {
+    to: root.clone(),
+    from,
+    position: Position { start: 0, end: 2 },
+    relation: Relation::Cites,
+    amendment_quote: false,
+    from_item: Vec::new(),
+    kind: MatchKind::FullName,
+    match_string: String::from("民法"),
+    repealed_date: None,
+  }
+  .without_text();
+  let json = serde_json::to_value(&find).unwrap();
+  for key in ["to", "from"] {
+    assert_eq!(json[key]["law_id"], "129AC0000000089");
+    assert_eq!(json[key]["law_id_text"], "明治二十九年法律第八十九号");
+  }
+}
+
+#[test]
+fn check_law_structure() {
+  let law_data = egov_xml_parse(
+    TEST_XML.as_bytes(),
+    Date::new_ad(2025, 4, 1),
+    None,
+    String::from("507AC0000000001"),
+    None,
+  )
+  .unwrap()
+  .parts;
+  for law in law_data.values() {
+    assert!(law.validate_number_hierarchy().is_ok());
+  }
+  let structure = law_structure(&law_data)
+    .iter()
+    .map(|l| l.number_text())
+    .collect::<Vec<_>>();
+  assert_eq!(
+    structure,
+    vec![
+      "",
+      "第一条",
+      "第一条１",
+      "第一条２",
+      "第二条",
+      "第二条１",
+      "第三条",
+      "第三条１",
+      "第四条",
+      "第四条１",
+      "附則",
+      "附則第一条",
+      "附則第一条１",
+      "附則第二条",
+      "附則第二条１",
+      "附則第二条２",
+      "別表第一",
+    ]
+  );
+}
+
+#[test]
+fn check_preamble() {
+  use eli::Eli;
+  let xml = concat!(
+    r#"<Law Era="Reiwa" Lang="ja" LawType="Act" Num="1" Year="7"><LawNum>令和七年法律第一号</LawNum>"#,
+    "<LawBody><LawTitle>テスト法</LawTitle>",
+    "<Preamble>",
+    r#"<Paragraph Num="1"><ParagraphNum/><ParagraphSentence><Sentence Num="1">試験は、大切である。</Sentence></ParagraphSentence></Paragraph>"#,
+    r#"<Paragraph Num="2"><ParagraphNum/><ParagraphSentence><Sentence Num="1">ここに、民法第一条の趣旨にのつとり、この法律を制定する。</Sentence></ParagraphSentence></Paragraph>"#,
+    "</Preamble>",
+    "<MainProvision>",
+    r#"<Article Num="1"><ArticleTitle>第一条</ArticleTitle>"#,
+    r#"<Paragraph Num="1"><ParagraphNum/><ParagraphSentence><Sentence Num="1">この法律は、試験のために定める。</Sentence></ParagraphSentence></Paragraph>"#,
+    "</Article>",
+    "</MainProvision></LawBody></Law>",
+  );
+  let law = egov_xml_parse(
+    xml.as_bytes(),
+    Date::new_ad(2025, 4, 1),
+    None,
+    String::from("507AC0000000001"),
+    None,
+  )
+  .unwrap();
+  // 前文の段落は本則の項と別のkeyで登録する
+  let preamble = law.parts.get("前文２").unwrap();
+  assert!(preamble.is_preamble());
+  assert!(law.parts.contains_key("第一条１"));
+  assert!(!law.parts["第一条１"].is_preamble());
+  assert_eq!(preamble.citation_text(), "テスト法前文第二項");
+  assert_eq!(preamble.parent().number_text(), "前文");
+  let uri = preamble.eli_uri();
+  assert!(
+    uri.ends_with("/507AC0000000001//preamble/paragraph2"),
+    "{uri}"
+  );
+  assert!(parse_eli_uri(&uri).unwrap().preamble);
+  // 前文の中の参照も探す
+  let minpou = minpou(Date::new_ad(2023, 6, 14));
+  let law_map = HashMap::from([(String::from("民法"), minpou)]);
+  let finds = parse_ref(&law.parts, &law_map);
+  let find = finds
+    .iter()
+    .find(|f| f.from.is_preamble())
+    .expect("reference in the preamble");
+  assert_eq!(find.to.citation_text(), "民法第一条");
+  // 前文でない法令のJSONには出力しない
+  let json = serde_json::to_string(&law.parts["第一条１"]).unwrap();
+  assert!(!json.contains("preamble"));
+  let json = serde_json::to_string(preamble).unwrap();
+  let restored: Law = serde_json::from_str(&json).unwrap();
+  assert_eq!(&restored, preamble);
+}
+
+#[test]
+fn check_egov_xml_parse_title() {
+  let with_title = egov_xml_parse(
+    TEST_XML.as_bytes(),
+    Date::new_ad(2025, 4, 1),
+    None,
+    String::from("507AC0000000001"),
+    None,
+  )
+  .unwrap();
+  let root = with_title.root();
+  assert_eq!(root.get_name(), Some(String::from("テスト法")));
+  // 空文字列のkeyでも法令全体を引ける
+  assert_eq!(with_title.parts.get(""), Some(root));
+  assert_eq!(root.get_law_id_text(), "令和七年法律第一号");
+  let with_name = egov_xml_parse(
+    TEST_XML.as_bytes(),
+    Date::new_ad(2025, 4, 1),
+    Some(String::from("令和七年法律第一号")),
+    String::from("507AC0000000001"),
+    None,
+  )
+  .unwrap();
+  assert_eq!(
+    with_name.root().get_name(),
+    Some(String::from("令和七年法律第一号"))
+  );
+}
+
+#[test]
+fn check_law_xml_schema_version() {
+  let old = include_bytes!("../tests/fixtures/old_schema_v2.xml");
+  assert_eq!(law_xml_schema_version(old), Some(2));
+  assert_eq!(law_xml_schema_version(TEST_XML.as_bytes()), None);
+  let err = egov_xml_parse(
+    old,
+    Date::new_ad(2008, 4, 1),
+    None,
+    String::from("420AC0000000001"),
+    None,
+  )
+  .map(|_| ())
+  .unwrap_err();
+  assert!(matches!(
+    err,
+    EliError::UnsupportedSchemaVersion {
+      found: 2,
+      supported: SUPPORTED_LAW_XML_SCHEMA_VERSION,
+      ..
+    }
+  ));
+  assert!(err.to_string().contains("version 2 is not supported"));
+  // 宣言が今の版なら，解析の失敗はそのまま返す
+  let current = String::from_utf8_lossy(old).replace("_v2.xsd", "_v3.xsd");
+  assert!(matches!(
+    egov_xml_parse(
+      current.as_bytes(),
+      Date::new_ad(2008, 4, 1),
+      None,
+      String::from("420AC0000000001"),
+      None,
+    ),
+    Err(EliError::Schema(_))
+  ));
+}
+
+#[test]
+fn check_egov_xml_parse_error() {
+  let parse = |xml: &str| {
+    egov_xml_parse(
+      xml.as_bytes(),
+      Date::new_ad(2025, 4, 1),
+      None,
+      String::from("507AC0000000001"),
+      None,
+    )
+    .map(|_| ())
+  };
+  assert_eq!(parse("<Law>"), Err(EliError::XmlSyntax));
+  assert!(matches!(
+    parse(r#"<Law Era="Reiwa" Lang="ja" LawType="Act" Num="1" Year="7"></Law>"#),
+    Err(EliError::MissingElement { .. })
+  ));
+  assert!(matches!(parse("<Foo/>"), Err(EliError::Schema(_))));
+  let header = LawIndexHeader::new("body");
+  assert_eq!(header.validate("body"), Ok(()));
+  assert_eq!(
+    header.validate("changed"),
+    Err(EliError::IndexHashMismatch {
+      found: law_index_content_hash("body"),
+      expected: law_index_content_hash("changed"),
+    })
+  );
+  let old = LawIndexHeader {
+    schema_version: 1,
+    ..header
+  };
+  assert_eq!(
+    old.validate("body"),
+    Err(EliError::IndexSchemaVersion {
+      found: 1,
+      expected: LAW_INDEX_SCHEMA_VERSION,
+    })
+  );
+}
+
+#[test]
+fn check_paragraph_items() {
+  let law_data = egov_xml_parse(
+    TEST_XML.as_bytes(),
+    Date::new_ad(2025, 4, 1),
+    None,
+    String::from("507AC0000000001"),
+    None,
+  )
+  .unwrap()
+  .parts;
+  let paragraph = law_data
+    .values()
+    .find(|l| l.article_number == parse_article_number("第三条") && l.paragraph_number.is_some())
+    .unwrap();
+  let items = paragraph.get_paragraph_items();
+  assert_eq!(
+    items
+      .iter()
+      .map(|i| (
+        i.item_numbers
+          .iter()
+          .map(|n| n.clone().map(|n| n.num_str()))
+          .collect::<Vec<_>>(),
+        i.text.as_str()
+      ))
+      .collect::<Vec<_>>(),
+    vec![
+      (vec![], "次に掲げる規定は、適用しない。"),
+      (vec![Some(String::from("1"))], "この法律の第一条"),
+      (vec![Some(String::from("2"))], "民法第九十二条"),
+    ]
+  );
+  // 号の中の参照は参照元がその号になる
+  let mut target = HashMap::new();
+  target.insert(String::new(), paragraph.clone());
+  let mut law_map = HashMap::new();
+  law_map.insert(String::from("民法"), law_data.get("").unwrap().clone());
+  let finds = parse_ref(&target, &law_map);
+  // 「この法律の第一条」と「民法第九十二条」
+  assert_eq!(finds.len(), 2);
+  assert_eq!(
+    finds[0].get_from_item(),
+    vec![parse_article_number("第一条")]
+  );
+  assert_eq!(
+    finds[1].get_from_item(),
+    vec![parse_article_number("第二条")]
+  );
+  // 号ごとのテキストをつなげると項のテキストになる
+  assert_eq!(
+    items
+      .iter()
+      .map(|i| i.text.clone())
+      .collect::<Vec<_>>()
+      .join("\n"),
+    paragraph.paragraph_text.clone().unwrap()
+  );
+}
+
+fn ord_article_number(a: &ArticleNumber, b: &ArticleNumber) -> Ordering {
+  if a.base_number == b.base_number {
+    for (a_e, b_e) in a.eda_numbers.iter().zip(&b.eda_numbers) {
+      if a_e != b_e {
+        return a_e.cmp(b_e);
+      }
+    }
+    a.eda_numbers.len().cmp(&b.eda_numbers.len())
+  } else {
+    a.base_number.cmp(&b.base_number)
+  }
+}
+
+fn ord_article(a: &Law, b: &Law) -> Ordering {
+  // 前文は本則の条・項の前に並べる（法令全体よりは後）
+  let has_number = |l: &Law| l.article_number.is_some() || l.paragraph_number.is_some();
+  match (a.preamble, b.preamble) {
+    (true, false) if has_number(b) => return Ordering::Less,
+    (false, true) if has_number(a) => return Ordering::Greater,
+    _ => (),
+  }
+  // 附則は本則の後に並べる
+  let suppl_ord = a
+    .suppl_provision
+    .is_some()
+    .cmp(&b.suppl_provision.is_some());
+  if suppl_ord != Ordering::Equal {
+    return suppl_ord;
+  }
+  let a_num = &a.article_number;
+  let b_num = &b.article_number;
+  match (a_num, b_num) {
+    (None, Some(_)) => {
+      // Noneは前文の可能性が高い
+      Ordering::Less
+    }
+    (Some(_), None) => Ordering::Greater,
+    (None, None) => {
+      let a_p = &a.paragraph_number;
+      let b_p = &b.paragraph_number;
+      match (a_p, b_p) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Less,
+        (Some(_), None) => Ordering::Greater,
+        (Some(a_pa), Some(b_pa)) => ord_article_number(a_pa, b_pa),
+      }
+    }
+    (Some(a_a), Some(b_a)) => {
+      let o = ord_article_number(a_a, b_a);
+      if o == Ordering::Equal {
+        let a_p = &a.paragraph_number;
+        let b_p = &b.paragraph_number;
+        match (a_p, b_p) {
+          (None, None) => Ordering::Equal,
+          (None, Some(_)) => Ordering::Less,
+          (Some(_), None) => Ordering::Greater,
+          (Some(a_pa), Some(b_pa)) => ord_article_number(a_pa, b_pa),
+        }
+      } else {
+        o
+      }
+    }
+  }
+}
+
+/// 番号が無いものを先にして比べる
+fn ord_option_number(a: &Option<ArticleNumber>, b: &Option<ArticleNumber>) -> Ordering {
+  match (a, b) {
+    (None, None) => Ordering::Equal,
+    (None, Some(_)) => Ordering::Less,
+    (Some(_), None) => Ordering::Greater,
+    (Some(a), Some(b)) => ord_article_number(a, b),
+  }
+}
+
+/// 本則・附則・別表の順に，その中では編・章・節・款・目・条・項の順に並べる
+/// 上の階層だけを持つもの（章そのものなど）はその中身よりも前に来る
+fn ord_document(a: &Law, b: &Law) -> Ordering {
+  let group = |l: &Law| {
+    if l.appdx_table.is_some() {
+      2
+    } else if l.suppl_provision.is_some() {
+      1
+    } else {
+      0
+    }
+  };
+  let appdx_number = |l: &Law| {
+    l.appdx_table
+      .as_ref()
+      .and_then(|num| ArticleNumber::from_num_str(num).ok())
+  };
+  group(a)
+    .cmp(&group(b))
+    .then_with(|| a.suppl_provision.cmp(&b.suppl_provision))
+    .then_with(|| ord_option_number(&appdx_number(a), &appdx_number(b)))
+    .then_with(|| ord_option_number(&a.part_number, &b.part_number))
+    .then_with(|| ord_option_number(&a.chapter_number, &b.chapter_number))
+    .then_with(|| ord_option_number(&a.section_number, &b.section_number))
+    .then_with(|| ord_option_number(&a.subsection_number, &b.subsection_number))
+    .then_with(|| ord_option_number(&a.division_number, &b.division_number))
+    .then_with(|| ord_article(a, b))
+}
+
+/// `egov_xml_parse`で得た法令の各部分を，法令全体・編・章・…・条・項の文書順に並べて返す
+/// 目次の表示などで，keyの文字列に頼らずに構造をたどるために使う
+pub fn law_structure(law_data: &HashMap<String, Law>) -> Vec<Law> {
+  let mut v = law_data.iter().collect::<Vec<_>>();
+  v.sort_by(|(a_key, a), (b_key, b)| ord_document(a, b).then_with(|| a_key.cmp(b_key)));
+  v.into_iter().map(|(_, l)| l.clone()).collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Position {
+  start: usize,
+  end: usize,
+}
+
+impl Position {
+  /// 二つの範囲が一文字以上重なっているか
+  pub fn overlaps(&self, other: &Position) -> bool {
+    self.start < other.end && other.start < self.end
+  }
+  /// `other`の範囲をすべて含んでいるか（同じ範囲も含む）
+  pub fn contains(&self, other: &Position) -> bool {
+    self.start <= other.start && other.end <= self.end
+  }
+}
+
+#[test]
+fn check_position() {
+  let p = Position { start: 2, end: 5 };
+  assert!(p.contains(&p));
+  assert!(p.contains(&Position { start: 3, end: 5 }));
+  assert!(!p.contains(&Position { start: 1, end: 4 }));
+  assert!(!Position { start: 3, end: 5 }.contains(&p));
+  assert!(p.overlaps(&Position { start: 4, end: 8 }));
+  assert!(Position { start: 0, end: 3 }.overlaps(&p));
+  // 隣接しているだけの範囲は重ならない
+  assert!(!p.overlaps(&Position { start: 5, end: 6 }));
+  assert!(!Position { start: 0, end: 2 }.overlaps(&p));
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct FindLawName {
+  position: Position,
+  find_law: Option<Law>,
+  match_string: String,
+  kind: MatchKind,
+}
+
+/// 法令名をどの方法で見つけたか
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum MatchKind {
+  /// 法令名
+  FullName,
+  /// 法令番号
+  LawNumber,
+  /// 略称
+  Abbreviation,
+  /// 「法」・「令」のような一文字の略称
+  SingleChar,
+  /// 「同法」・「同令」
+  Douhou,
+  /// 「○○法」の名前から補った施行令・施行規則
+  EnforcementOrder,
+  /// 附則から法令名無しで参照された本則の条や，法令名無しで参照された別表
+  SameLaw,
+  /// 「同条」・「同項」・「同号」
+  Doujou,
+  /// 「第二章を次のように改める」のように，改正文で番号だけで指された改正される法令の部分
+  AmendTarget,
+  /// 「この法律」・「この政令」・「本法」のような，その法令自身を指す語
+  SelfReference,
+  /// 条番号無しの「第二項」のように，番号だけで参照された同じ条の項
+  SameArticle,
+}
+
+/// 参照の見つけ方ごとの件数
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct MatchStats {
+  pub full_name: usize,
+  pub law_number: usize,
+  pub abbreviation: usize,
+  pub single_char: usize,
+  pub douhou: usize,
+  pub enforcement_order: usize,
+  pub same_law: usize,
+  pub doujou: usize,
+  pub amend_target: usize,
+  pub self_reference: usize,
+  pub same_article: usize,
+}
+
+impl MatchStats {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn add(&mut self, kind: MatchKind) {
+    match kind {
+      MatchKind::FullName => self.full_name += 1,
+      MatchKind::LawNumber => self.law_number += 1,
+      MatchKind::Abbreviation => self.abbreviation += 1,
+      MatchKind::SingleChar => self.single_char += 1,
+      MatchKind::Douhou => self.douhou += 1,
+      MatchKind::EnforcementOrder => self.enforcement_order += 1,
+      MatchKind::SameLaw => self.same_law += 1,
+      MatchKind::Doujou => self.doujou += 1,
+      MatchKind::AmendTarget => self.amend_target += 1,
+      MatchKind::SelfReference => self.self_reference += 1,
+      MatchKind::SameArticle => self.same_article += 1,
+    }
+  }
+
+  /// 別の集計結果を足し合わせる
+  pub fn merge(&mut self, other: &MatchStats) {
+    self.full_name += other.full_name;
+    self.law_number += other.law_number;
+    self.abbreviation += other.abbreviation;
+    self.single_char += other.single_char;
+    self.douhou += other.douhou;
+    self.enforcement_order += other.enforcement_order;
+    self.same_law += other.same_law;
+    self.doujou += other.doujou;
+    self.amend_target += other.amend_target;
+    self.self_reference += other.self_reference;
+    self.same_article += other.same_article;
+  }
+
+  pub fn total(&self) -> usize {
+    self.full_name
+      + self.law_number
+      + self.abbreviation
+      + self.single_char
+      + self.douhou
+      + self.enforcement_order
+      + self.same_law
+      + self.doujou
+      + self.amend_target
+      + self.self_reference
+      + self.same_article
+  }
+}
+
+/// 参照の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Relation {
+  /// 単純な引用
+  Cites,
+  /// 「第○条の規定により」や「○○法に基づき」のような，委任や根拠を示す参照
+  BasedOn,
+  /// 「○○法の一部を次のように改正する」や「第二章を次のように改める」のような改正
+  Changes,
+  /// 「第三節を削る」や「○○法は、廃止する」のような削除・廃止
+  Repeals,
+}
+
+impl Relation {
+  /// 対応するELIのオントロジー
+  pub fn ontology(&self) -> EliOntology {
+    match self {
+      Self::Cites => EliOntology::Cites,
+      Self::BasedOn => EliOntology::BasedOn,
+      Self::Changes => EliOntology::Changes,
+      Self::Repeals => EliOntology::Repeals,
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Find {
+  /// 参照先
+  to: Law,
+  /// 参照元の項
+  /// 号の中の参照でも項とし，号は`from_item`に持つので，逆引きでもどの項からの参照か区別できる
+  from: Law,
+  /// fromの中でのテキスト内の位置
+  position: Position,
+  /// 参照の種類
+  relation: Relation,
+  /// 改正文の「」の中にある参照かどうか
+  /// 改正される法令の文言を引用しているだけなので，改正する法令からの参照ではない
+  amendment_quote: bool,
+  /// 参照元の号・細分の番号を上の階層から並べたもの
+  /// 項の柱書きにある場合は空
+  from_item: Vec<Option<ArticleNumber>>,
+  /// 法令名をどの方法で見つけたか
+  kind: MatchKind,
+  /// 法令名として一致した文字列（「同法」や「同条」なども含む）
+  /// 附則から本則の条を番号だけで参照した場合などは空
+  match_string: String,
+  /// 参照先の法令が参照元の版の日付より前に廃止されていた場合の，その廃止日
+  /// `ParseConfig::repeal_dates`に廃止日が無い場合は`None`
+  repealed_date: Option<Date>,
+}
+
+impl Find {
+  pub fn get_relation(&self) -> Relation {
+    self.relation
+  }
+
+  /// 参照先
+  pub fn get_to(&self) -> Law {
+    self.to.clone()
+  }
+
+  /// 参照元
+  pub fn get_from(&self) -> Law {
+    self.from.clone()
+  }
+
+  pub fn get_from_item(&self) -> Vec<Option<ArticleNumber>> {
+    self.from_item.clone()
+  }
+
+  /// 改正文の「」の中にある参照かどうか
+  pub fn is_amendment_quote(&self) -> bool {
+    self.amendment_quote
+  }
+
+  /// 参照先の法令の種類（`Law::law_type_str`）
+  pub fn get_to_law_type_str(&self) -> String {
+    self.to.law_type_str()
+  }
+
+  /// 同じ法令の中を参照しているかどうか
+  pub fn is_self_reference(&self) -> bool {
+    self.to.law_id == self.from.law_id
+  }
+
+  /// 参照元から参照先へのトリプルを生成する
+  pub fn triple(&self) -> Triple {
+    self
+      .relation
+      .ontology()
+      .triple(self.from.without_text(), self.to.without_text())
+  }
+
+  /// `triple`を`rdf:Statement`の空白ノードとして表し，参照元の項の中での位置を付けたトリプル
+  /// 一つの参照のトリプルは同じ空白ノードを主語とするので，まとめて書き出す
+  pub fn reified_triples(&self) -> Vec<Triple> {
+    let triple = self.triple();
+    let statement = BlankNode::default();
+    let position = |local: &str, value: usize| {
+      Triple::new(
+        statement.clone(),
+        NamedNode::new_unchecked(format!("{REFERENCE_NAMESPACE}{local}")),
+        Literal::new_typed_literal(value.to_string(), oxrdf::vocab::xsd::INTEGER),
+      )
+    };
+    vec![
+      Triple::new(
+        statement.clone(),
+        oxrdf::vocab::rdf::TYPE,
+        oxrdf::vocab::rdf::STATEMENT,
+      ),
+      Triple::new(
+        statement.clone(),
+        oxrdf::vocab::rdf::SUBJECT,
+        triple.subject,
+      ),
+      Triple::new(
+        statement.clone(),
+        oxrdf::vocab::rdf::PREDICATE,
+        triple.predicate,
+      ),
+      Triple::new(statement.clone(), oxrdf::vocab::rdf::OBJECT, triple.object),
+      position("start", self.position.start),
+      position("end", self.position.end),
+    ]
+  }
+
+  /// 参照元と参照先の本文を持たない複製を返す（`Law::without_text`）
+  pub fn without_text(&self) -> Find {
+    Find {
+      to: self.to.without_text(),
+      from: self.from.without_text(),
+      position: self.position,
+      relation: self.relation,
+      amendment_quote: self.amendment_quote,
+      from_item: self.from_item.clone(),
+      kind: self.kind,
+      match_string: self.match_string.clone(),
+      repealed_date: self.repealed_date,
+    }
+  }
+
+  /// 参照先の法令が参照元の版の日付より前に廃止されていた場合の，その廃止日
+  pub fn get_repealed_date(&self) -> Option<Date> {
+    self.repealed_date
+  }
+
+  /// 法令名をどの方法で見つけたか
+  pub fn get_kind(&self) -> MatchKind {
+    self.kind
+  }
+
+  /// 誤検出の疑いがあり，目視で確かめた方がよい参照であればその理由を返す
+  pub fn lint(&self) -> Option<LintReason> {
+    if self.kind == MatchKind::SingleChar || self.match_string.chars().count() == 1 {
+      return Some(LintReason::SingleChar);
+    }
+    if self.kind == MatchKind::Douhou {
+      // 「同法」が法律以外を，「同令」が政令・勅令・府省令以外を指していれば，紐付け先を誤っている
+      let expected = match self.match_string.chars().last() {
+        Some('法') => matches!(self.to.law_type, LawType::Constitution | LawType::Act),
+        Some('令') => matches!(
+          self.to.law_type,
+          LawType::CabinetOrder | LawType::ImperialOrder | LawType::MinisterialOrdinance
+        ),
+        _ => true,
+      };
+      if !expected {
+        return Some(LintReason::DouhouMismatch);
+      }
+    }
+    None
+  }
+
+  /// 参照元の項のテキストのうち，参照の前後`width`文字までを，参照の部分を【】で囲んで返す
+  /// テキストを持たない場合（`without_text`の後など）は`None`
+  pub fn context(&self, width: usize) -> Option<String> {
+    self.context_in(ContextWindow::Chars(width))
+  }
+
+  /// 参照元の項のテキストのうち，`window`の範囲を，参照の部分を【】で囲んで返す
+  /// テキストを持たない場合（`without_text`の後など）は`None`
+  pub fn context_in(&self, window: ContextWindow) -> Option<String> {
+    let text = self.from.paragraph_text.as_ref()?;
+    let chars = text.chars().collect::<Vec<_>>();
+    let end = self.position.end.min(chars.len());
+    let start = self.position.start.min(end);
+    let (width, lower, upper) = match window {
+      ContextWindow::Chars(width) => (width, 0, chars.len()),
+      ContextWindow::Sentence(width) => {
+        // 句点の書き分けをそろえてから文の区切りを探す
+        let is_period = |c: &char| normalize_punctuation_char(*c) == '。';
+        let lower = chars[..start]
+          .iter()
+          .rposition(is_period)
+          .map_or(0, |i| i + 1);
+        let upper = chars[end..]
+          .iter()
+          .position(is_period)
+          .map_or(chars.len(), |i| end + i + 1);
+        (width, lower, upper)
+      }
+    };
+    let before = start.saturating_sub(width).max(lower);
+    let after = (end + width).min(upper);
+    Some(format!(
+      "{}【{}】{}",
+      chars[before..start].iter().collect::<String>(),
+      chars[start..end].iter().collect::<String>(),
+      chars[end..after].iter().collect::<String>()
+    ))
+  }
+}
+
+/// 目視で確かめた方がよい参照の理由
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum LintReason {
+  /// 「法」・「令」のような一文字で法令名を見つけた
+  SingleChar,
+  /// 「同法」・「同令」の紐付け先の法令の種類が合わない
+  DouhouMismatch,
+}
+
+/// `Find::context_in`で切り出す，参照の前後のテキストの範囲
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContextWindow {
+  /// 参照の前後それぞれこの文字数まで
+  Chars(usize),
+  /// 参照を含む文（前後の「。」まで）
+  /// 長い文は`Chars`と同じく参照の前後それぞれこの文字数までに切り詰め，「。」の無い項では`Chars`と同じになる
+  Sentence(usize),
+}
+
+/// 目視で確かめるための参照とその理由・前後のテキスト
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct LintEntry {
+  pub reason: LintReason,
+  /// `Find::context_in`で作った前後のテキスト
+  pub context: Option<String>,
+  pub find: Find,
+}
+
+/// 参照のうち目視で確かめた方がよいものを，`window`の範囲の前後のテキストと一緒に返す
+pub fn lint_finds(finds: &[Find], window: ContextWindow) -> Vec<LintEntry> {
+  finds
+    .iter()
+    .filter_map(|find| {
+      find.lint().map(|reason| LintEntry {
+        reason,
+        context: find.context_in(window),
+        find: find.clone(),
+      })
+    })
+    .collect()
+}
+
+#[test]
+fn check_lint_finds() {
+  let minpou = minpou(Date::new_ad(2025, 11, 26));
+  let mut law_map = HashMap::new();
+  law_map.insert(String::from("民法"), minpou);
+  let finds = refs(
+    "民法（以下「法」という。）第一条、同法第二条及び同令第三条",
+    &law_map,
+  );
+  let entries = lint_finds(&finds, ContextWindow::Chars(3));
+  let entries = entries
+    .iter()
+    .map(|e| (e.reason, e.context.clone().unwrap()))
+    .collect::<Vec<_>>();
+  assert_eq!(
+    entries,
+    vec![
+      // 略称を定義している箇所の「法」は，同じ法令を指す「民法」の参照と重なるので見つからない
+      (
+        LintReason::DouhouMismatch,
+        String::from("条及び【同令第三条】")
+      ),
+    ]
+  );
+  // テキストを持たない場合は前後のテキストを出さない
+  let without_text = finds.iter().map(|f| f.without_text()).collect::<Vec<_>>();
+  assert!(
+    lint_finds(&without_text, ContextWindow::Chars(3))
+      .iter()
+      .all(|e| e.context.is_none())
+  );
+}
+
+#[test]
+fn check_context_window() {
+  let minpou = minpou(Date::new_ad(2025, 11, 26));
+  let law_map = HashMap::from([(String::from("民法"), minpou)]);
+  let find = |text: &str| refs(text, &law_map).pop().unwrap();
+  let f = find("前の文である。民法第九十条の規定を準用する。次の文である。");
+  // 文字数では文の途中で切れる
+  assert_eq!(
+    f.context_in(ContextWindow::Chars(4)).unwrap(),
+    "である。【民法第九十条】の規定を"
+  );
+  // 文では前後の「。」まで
+  assert_eq!(
+    f.context_in(ContextWindow::Sentence(20)).unwrap(),
+    "【民法第九十条】の規定を準用する。"
+  );
+  // 長い文は文字数で切り詰める
+  assert_eq!(
+    f.context_in(ContextWindow::Sentence(3)).unwrap(),
+    "【民法第九十条】の規定"
+  );
+  // 全角のピリオドも文の区切りとする
+  let f = find("前の文である．民法第九十条の規定を準用する．");
+  assert_eq!(
+    f.context_in(ContextWindow::Sentence(20)).unwrap(),
+    "【民法第九十条】の規定を準用する．"
+  );
+  // 「。」の無い項は文字数と同じ
+  let f = find("前の文であり民法第九十条の規定を準用し");
+  assert_eq!(
+    f.context_in(ContextWindow::Sentence(3)),
+    f.context_in(ContextWindow::Chars(3))
+  );
+  assert_eq!(
+    f.context_in(ContextWindow::Sentence(3)).unwrap(),
+    "であり【民法第九十条】の規定"
+  );
+}
+
+/// 参照元を除いた参照
+/// `FindGroup`の中で使い，項目は`Find`と同じ
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct GroupedFind {
+  pub to: Law,
+  pub position: Position,
+  pub relation: Relation,
+  pub amendment_quote: bool,
+  pub from_item: Vec<Option<ArticleNumber>>,
+  pub kind: MatchKind,
+  pub match_string: String,
+  pub repealed_date: Option<Date>,
+}
+
+/// 参照元の項ごとにまとめた参照
+/// 大きな参照元の`Law`を参照ごとに繰り返さずに済む
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct FindGroup {
+  /// 参照元
+  pub from: Law,
+  /// 参照元から出ている参照（元の順のまま）
+  pub finds: Vec<GroupedFind>,
+}
+
+/// 参照を参照元の項ごとにまとめる
+/// まとまりは参照元が最初に出てきた順に並べるので，`parse_ref`の結果の順がそのまま保たれる
+pub fn group_by_from(finds: Vec<Find>) -> Vec<FindGroup> {
+  let mut groups: Vec<FindGroup> = Vec::new();
+  let mut index: HashMap<LawKey, usize> = HashMap::new();
+  for find in finds.into_iter() {
+    let key = LawKey::from(&find.from);
+    let grouped = GroupedFind {
+      to: find.to,
+      position: find.position,
+      relation: find.relation,
+      amendment_quote: find.amendment_quote,
+      from_item: find.from_item,
+      kind: find.kind,
+      match_string: find.match_string,
+      repealed_date: find.repealed_date,
+    };
+    match index.get(&key) {
+      Some(&i) => groups[i].finds.push(grouped),
+      None => {
+        index.insert(key, groups.len());
+        groups.push(FindGroup {
+          from: find.from,
+          finds: vec![grouped],
+        });
+      }
+    }
+  }
+  groups
+}
+
+#[test]
+fn check_group_by_from() {
+  let minpou = minpou(Date::new_ad(2025, 11, 26));
+  let mut law_map = HashMap::new();
+  law_map.insert(String::from("民法"), minpou);
+  let mut target = HashMap::new();
+  let texts = [
+    "民法第一条及び民法第二条",
+    "民法第三条",
+    "民法第四条、民法第五条及び民法第六条",
+  ];
+  for (i, text) in texts.iter().enumerate() {
+    let mut paragraph = paragraph(text);
+    paragraph.article_number = parse_article_number("第一条");
+    paragraph.set_paragraph_number(parse_article_number(&format!("{}", i + 1)).unwrap());
+    target.insert(format!("{i}"), paragraph);
+  }
+  let finds = parse_ref(&target, &law_map);
+  let groups = group_by_from(finds.clone());
+  let shape = groups
+    .iter()
+    .map(|g| {
+      (
+        g.from.paragraph_number_str().unwrap(),
+        g.finds
+          .iter()
+          .map(|f| f.to.article_number.as_ref().unwrap().base_number)
+          .collect::<Vec<_>>(),
+      )
+    })
+    .collect::<Vec<_>>();
+  assert_eq!(
+    shape,
+    vec![
+      (String::from("paragraph1"), vec![1, 2]),
+      (String::from("paragraph2"), vec![3]),
+      (String::from("paragraph3"), vec![4, 5, 6]),
+    ]
+  );
+  assert_eq!(
+    groups.iter().map(|g| g.finds.len()).sum::<usize>(),
+    finds.len()
+  );
+}
+
+/// `validate_find_jsonl`で読めなかった行
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct InvalidRecord {
+  /// 行番号（1から）
+  pub line: usize,
+  /// 読めなかった理由
+  pub message: String,
+}
+
+/// `validate_find_jsonl`の結果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ValidationReport {
+  pub path: String,
+  /// 参照として読めた行の数
+  pub valid: usize,
+  /// 読めなかった行
+  pub invalid: Vec<InvalidRecord>,
+}
+
+/// 結果のファイルを一行ずつ読み，書き出しと同じ`Find`か`FindGroup`（`group_by_from`でまとめた形）として読めるかを確かめる
+/// ファイル全体を読み込まないので，大きなファイルでもメモリを使わない
+/// 空行は数えない
+#[cfg(feature = "jsonl")]
+pub fn validate_find_jsonl(path: &std::path::Path) -> Result<ValidationReport> {
+  use std::io::BufRead;
+  let file = std::fs::File::open(path).map_err(|err| EliError::Io {
+    path: path.display().to_string(),
+    message: err.to_string(),
+  })?;
+  let mut report = ValidationReport {
+    path: path.display().to_string(),
+    valid: 0,
+    invalid: Vec::new(),
+  };
+  for (i, line) in std::io::BufReader::new(file).lines().enumerate() {
+    let result = line.map_err(|err| err.to_string()).and_then(|line| {
+      if line.is_empty() {
+        return Ok(false);
+      }
+      match serde_json::from_str::<Find>(&line) {
+        Ok(_) => Ok(true),
+        // `finds`を持つ行は`FindGroup`として読み，その理由を返す
+        Err(_) if line.contains("\"finds\"") => serde_json::from_str::<FindGroup>(&line)
+          .map(|_| true)
+          .map_err(|err| err.to_string()),
+        Err(err) => Err(err.to_string()),
+      }
+    });
+    match result {
+      Ok(true) => report.valid += 1,
+      Ok(false) => (),
+      Err(message) => report.invalid.push(InvalidRecord {
+        line: i + 1,
+        message,
+      }),
+    }
+  }
+  Ok(report)
+}
+
+#[cfg(feature = "jsonl")]
+#[test]
+fn check_validate_find_jsonl() {
+  let minpou = minpou(Date::new_ad(2023, 6, 14));
+  let law_map = HashMap::from([(String::from("民法"), minpou)]);
+  let finds = refs("民法第九十条及び民法第九十一条", &law_map);
+  let path = std::env::temp_dir().join(format!(
+    "gen_eli_validate_jsonl_test_{}.jsonl",
+    std::process::id()
+  ));
+  let validate = |lines: &[String]| {
+    std::fs::write(&path, lines.join("\n")).unwrap();
+    let report = validate_find_jsonl(&path);
+    std::fs::remove_file(&path).unwrap();
+    let report = report.unwrap();
+    (
+      report.valid,
+      report.invalid.iter().map(|r| r.line).collect::<Vec<_>>(),
+    )
+  };
+  // 参照を一行ずつ書いた形
+  let mut lines = finds
+    .iter()
+    .map(|f| serde_json::to_string(f).unwrap())
+    .collect::<Vec<_>>();
+  // 書き込みの途中で切れた行
+  let broken = lines[1][..lines[1].len() / 2].to_string();
+  lines.insert(1, broken);
+  lines.push(String::new());
+  assert_eq!(validate(&lines), (2, vec![2]));
+  // 参照元の項ごとにまとめた形
+  let group = serde_json::to_string(&group_by_from(finds.clone())[0]).unwrap();
+  let broken = group.replacen("\"relation\"", "\"relations\"", 1);
+  assert_eq!(validate(&[group, broken]), (1, vec![2]));
+  assert_eq!(
+    validate_find_jsonl(&path),
+    Err(EliError::Io {
+      path: path.display().to_string(),
+      message: std::fs::File::open(&path).unwrap_err().to_string(),
+    })
+  );
+}
+
+/// 同じ法令の二つの版の参照の差分
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RefDiff {
+  /// 新しい版にだけある参照
+  pub added: Vec<Find>,
+  /// 古い版にだけある参照
+  pub removed: Vec<Find>,
+  /// 両方の版にある参照（新しい版のもの）
+  pub unchanged: Vec<Find>,
+}
+
+/// 同じ法令の古い版の参照`old`と新しい版の参照`new`を比べる
+/// 参照元・参照先は日付と改正法令IDを除いた条項の位置（`Law::same_target`）で比べ，
+/// 参照の種類と参照元の号も同じものを同じ参照とする
+/// 改正で文言が変わっても同じ参照とみなせるよう，テキスト内の位置は比べない
+/// 同じ参照が複数ある場合は，その数の差を追加・削除とする
+pub fn diff_finds(old: &[Find], new: &[Find]) -> RefDiff {
+  let same = |a: &Find, b: &Find| {
+    a.relation == b.relation
+      && a.amendment_quote == b.amendment_quote
+      && a.from_item == b.from_item
+      && a.from.same_target(&b.from)
+      && a.to.same_target(&b.to)
+  };
+  let mut remaining = old.iter().map(Some).collect::<Vec<_>>();
+  let mut diff = RefDiff::default();
+  for find in new.iter() {
+    let matched = remaining
+      .iter()
+      .position(|o| o.is_some_and(|o| same(o, find)));
+    match matched {
+      Some(i) => {
+        remaining[i] = None;
+        diff.unchanged.push(find.clone());
+      }
+      None => diff.added.push(find.clone()),
+    }
+  }
+  diff.removed = remaining.into_iter().flatten().cloned().collect();
+  diff
+}
+
+#[test]
+fn check_diff_finds() {
+  let minpou = minpou(Date::new_ad(2020, 4, 1));
+  let mut law_map = HashMap::new();
+  law_map.insert(String::from("民法"), minpou);
+  let finds = |date, patch_id: &str, text: &str| {
+    let mut paragraph = paragraph(text);
+    paragraph.date = date;
+    paragraph.set_patch_id(String::from(patch_id));
+    paragraph.article_number = parse_article_number("第一条");
+    parse_ref(&HashMap::from([(String::new(), paragraph)]), &law_map)
+  };
+  let old = finds(
+    Date::new_ad(2019, 4, 1),
+    "431AC0000000001",
+    "民法第一条及び民法第二条",
+  );
+  // 第二条はテキスト内の位置が変わるが，同じ参照とみなす
+  let new = finds(
+    Date::new_ad(2020, 4, 1),
+    "502AC0000000001",
+    "民法第二条及び民法第三条",
+  );
+  let articles = |finds: &[Find]| {
+    finds
+      .iter()
+      .map(|f| f.to.article_number.as_ref().unwrap().base_number)
+      .collect::<Vec<_>>()
+  };
+  let diff = diff_finds(&old, &new);
+  assert_eq!(articles(&diff.added), vec![3]);
+  assert_eq!(articles(&diff.removed), vec![1]);
+  assert_eq!(articles(&diff.unchanged), vec![2]);
+  assert_eq!(diff.unchanged[0].from.get_date(), Date::new_ad(2020, 4, 1));
+  // 同じ参照が増えた分だけ追加になる
+  let twice = finds(
+    Date::new_ad(2020, 4, 1),
+    "502AC0000000001",
+    "民法第一条及び民法第二条並びに民法第一条",
+  );
+  let diff = diff_finds(&old, &twice);
+  assert_eq!(articles(&diff.added), vec![1]);
+  assert!(diff.removed.is_empty());
+  assert_eq!(articles(&diff.unchanged), vec![1, 2]);
+}
+
+/// 参照先の条項ごとの被参照数
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct TargetCount {
+  /// 参照先の法令ID
+  pub law_id: String,
+  /// 参照先の引用表記
+  pub citation: String,
+  /// 参照先の条番号（`article3_2`のような形式）
+  pub article: Option<String>,
+  /// 参照先の項番号（`paragraph2`のような形式）
+  pub paragraph: Option<String>,
+  /// 被参照数
+  pub count: usize,
+}
+
+/// 参照先の条項ごとに被参照数を集計する
+/// `TargetCount`の列で区別できる単位（法令IDと項までの条項）で数えるので，
+/// 参照先の版の日付や号が違う参照も同じ行にまとめる
+#[derive(Debug, Clone, Default)]
+pub struct TargetCounter {
+  /// 法令IDと項までの条項番号のテキスト（`Law::locator_text`）をkeyとする
+  counts: HashMap<(String, String), TargetCount>,
+}
+
+impl TargetCounter {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// 参照の一覧を集計に加える
+  pub fn add(&mut self, finds: &[Find]) {
+    for find in finds.iter() {
+      let mut to = find.to.without_text();
+      to.item_number = None;
+      self
+        .counts
+        .entry((to.get_law_id(), to.locator_text()))
+        .or_insert_with(|| TargetCount {
+          law_id: to.get_law_id(),
+          citation: to.citation_text(),
+          article: to.article_number_str(),
+          paragraph: to.paragraph_number_str(),
+          count: 0,
+        })
+        .count += 1;
+    }
+  }
+
+  /// 別に集計したものを加える
+  pub fn merge(&mut self, other: TargetCounter) {
+    for (key, count) in other.counts.into_iter() {
+      self
+        .counts
+        .entry(key)
+        .and_modify(|c| c.count += count.count)
+        .or_insert(count);
+    }
+  }
+
+  /// 被参照数の多い順に並べた一覧を返す
+  /// 同数の場合は法令ID・条・項・引用表記の順で並べる
+  /// topが与えられたときは上位top件だけを返す
+  pub fn ranking(&self, top: Option<usize>) -> Vec<TargetCount> {
+    let mut v = self.counts.values().cloned().collect::<Vec<_>>();
+    v.sort_by(|a, b| {
+      b.count
+        .cmp(&a.count)
+        .then_with(|| a.law_id.cmp(&b.law_id))
+        .then_with(|| a.article.cmp(&b.article))
+        .then_with(|| a.paragraph.cmp(&b.paragraph))
+        .then_with(|| a.citation.cmp(&b.citation))
+    });
+    if let Some(top) = top {
+      v.truncate(top);
+    }
+    v
+  }
+}
+
+/// 被参照数の一覧をヘッダ付きのCSVにする
+pub fn target_counts_to_csv(counts: &[TargetCount]) -> String {
+  let escape = |s: &str| {
+    if s.contains([',', '"', '\n']) {
+      format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+      s.to_string()
+    }
+  };
+  let mut s = String::from("law_id,citation,article,paragraph,count\n");
+  for c in counts.iter() {
+    s.push_str(&format!(
+      "{},{},{},{},{}\n",
+      escape(&c.law_id),
+      escape(&c.citation),
+      c.article.clone().unwrap_or_default(),
+      c.paragraph.clone().unwrap_or_default(),
+      c.count
+    ));
+  }
+  s
+}
+
+/// 参照情報の抽出に関する設定
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParseConfig {
+  /// 「○○法施行令」・「○○法施行規則」がそのままの名前で見つからなかったときに，
+  /// 「○○法」の法令名から対応する施行令・施行規則を探す
+  pub expand_enforcement_orders: bool,
+  /// 参照を探すときの手がかりにする語句
+  pub keywords: Keywords,
+  /// 条項番号のように見えるが読み取れなかった「第…」を診断情報として記録する
+  pub strict: bool,
+  /// 参照先の法令にどの時点の版を使うか
+  pub version_select: VersionSelect,
+  /// 法令IDごとの廃止日
+  /// 参照元の版の日付より前に廃止された法令への参照に`Find::get_repealed_date`で廃止日を付ける
+  pub repeal_dates: RepealDates,
+  /// 「最判平成○年○月○日」のような判例の引用も探し，`ParseReport::case_law_refs`に記録する
+  /// 法令名とは別の規則で探すので，誤検出が多くなりうる
+  pub case_law: bool,
+  /// 紐付け先が見つからなかった「同法」・「同条」などを`ParseReport::unresolved`に記録する
+  pub unresolved: bool,
+  /// 条項番号を読み取る前の法令名の候補を`ParseReport::candidates`に記録する
+  /// 参照が残った理由・残らなかった理由を調べるためのもので，通常は使わない
+  pub debug_candidates: bool,
+  /// 「以下「○○」という。」による略称の定義を`ParseReport::abbreviations`に記録する
+  pub abbreviations: bool,
+}
+
+/// 参照先の法令の版の選び方
+///
+/// 全ての参照の`to`がどの版の法令を指すかが変わるので，時点を比べる分析をするときは注意すること
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum VersionSelect {
+  /// `law_map`に登録された版（通常は最新の版）をそのまま使う
+  #[default]
+  Latest,
+  /// 参照元の法令の日付の時点で施行されている版を使う
+  /// その時点より前の版が一つも無い場合は，最も古い版を使う
+  AtDate(LawVersions),
+}
+
+/// 法令IDごとに，日付の異なる複数の版を持つ
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LawVersions {
+  versions: HashMap<String, Vec<Law>>,
+  /// `insert_articles`で登録した，版ごとの本則の条番号の一覧
+  articles: HashMap<LawKey, HashSet<ArticleNumber>>,
+}
+
+impl LawVersions {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// 版を追加する
+  /// 同じ法令IDの版は日付順に，同じ日付の版は改正法令ID順に並べておく
+  pub fn insert(&mut self, law: Law) {
+    let versions = self.versions.entry(law.law_id.clone()).or_default();
+    versions.push(law);
+    versions.sort_by(|a, b| (a.date, &a.patch_id).cmp(&(b.date, &b.patch_id)));
+  }
+
+  /// `law`の版と，その一つ前の版・抽象的な法令との関係のトリプル
+  /// 版は`eli:is_member_of`・`eli:has_member`で`Law::work_uri`に属し，
+  /// 前の版があれば`eli:consolidates`・`eli:consolidated_by`でそれと結ぶ
+  /// 同じ日付の版は`eli_uri`が同じになるので，前の版には日付の異なるものを使う
+  pub fn version_triples(&self, law: &Law) -> Vec<Triple> {
+    let Some(versions) = self.versions.get(&law.law_id) else {
+      return Vec::new();
+    };
+    let Some(index) = versions
+      .iter()
+      .position(|l| l.date == law.date && l.patch_id == law.patch_id)
+    else {
+      return Vec::new();
+    };
+    let version = NamedNode::new(eli::Eli::eli_uri(law)).unwrap();
+    let work = NamedNode::new(law.work_uri()).unwrap();
+    let mut triples = vec![
+      Triple::new(
+        version.clone(),
+        EliOntology::IsMemberOf.named_node(),
+        work.clone(),
+      ),
+      Triple::new(work, EliOntology::HasMember.named_node(), version.clone()),
+    ];
+    if let Some(previous) = versions[..index].iter().rev().find(|l| l.date < law.date) {
+      let previous = NamedNode::new(eli::Eli::eli_uri(previous)).unwrap();
+      triples.push(Triple::new(
+        version.clone(),
+        EliOntology::Consolidates.named_node(),
+        previous.clone(),
+      ));
+      triples.push(Triple::new(
+        previous,
+        EliOntology::ConsolidatedBy.named_node(),
+        version,
+      ));
+    }
+    triples
+  }
+
+  /// 全ての版を法令ID・日付の順に返す
+  pub fn iter(&self) -> impl Iterator<Item = &Law> {
+    let mut law_ids = self.versions.keys().collect::<Vec<_>>();
+    law_ids.sort();
+    law_ids
+      .into_iter()
+      .flat_map(|law_id| self.versions[law_id].iter())
+  }
+
+  /// `date`の時点で施行されている版を返す
+  pub fn at_date(&self, law_id: &str, date: &Date) -> Option<&Law> {
+    let versions = self.versions.get(law_id)?;
+    versions
+      .iter()
+      .rev()
+      .find(|l| l.date <= *date)
+      .or_else(|| versions.first())
+  }
+
+  /// `law`の版の本則にある条番号を登録する
+  /// 登録した版は，`at_date_with_article`で条があるかどうかを確かめるのに使う
+  pub fn insert_articles(&mut self, law: &Law, articles: impl IntoIterator<Item = ArticleNumber>) {
+    self
+      .articles
+      .entry(LawKey::from(law))
+      .or_default()
+      .extend(articles);
+  }
+
+  /// `insert_articles`で登録した`law`の版の条番号
+  /// 索引ファイルの内容が変わらないよう，条番号の順に並べる
+  pub fn articles(&self, law: &Law) -> Option<Vec<ArticleNumber>> {
+    let mut articles = self
+      .articles
+      .get(&LawKey::from(law))?
+      .iter()
+      .cloned()
+      .collect::<Vec<_>>();
+    articles.sort_by(|a, b| {
+      (a.base_number, &a.eda_numbers, &a.range_end_numbers).cmp(&(
+        b.base_number,
+        &b.eda_numbers,
+        &b.range_end_numbers,
+      ))
+    });
+    Some(articles)
+  }
+
+  /// `date`の時点で施行されている版のうち，`article`の条を持つものを返す
+  /// 後の改正で追加された「第三条の五」のような条を参照している場合は，その条を持つ近くの版（後の版を優先する）を返す
+  /// 条番号を登録していない版や範囲を持つ条番号の場合は，`at_date`と同じ版を返す
+  pub fn at_date_with_article(
+    &self,
+    law_id: &str,
+    date: &Date,
+    article: &ArticleNumber,
+  ) -> Option<&Law> {
+    let law = self.at_date(law_id, date)?;
+    let has_article = |l: &Law| {
+      self
+        .articles
+        .get(&LawKey::from(l))
+        .is_none_or(|articles| articles.contains(article))
+    };
+    if !article.range_end_numbers.is_empty() || has_article(law) {
+      return Some(law);
+    }
+    let versions = self.versions.get(law_id)?;
+    let i = versions.iter().position(|l| std::ptr::eq(l, law))?;
+    versions[i + 1..]
+      .iter()
+      .find(|l| has_article(l))
+      .or_else(|| versions[..i].iter().rev().find(|l| has_article(l)))
+      .or(Some(law))
+  }
+}
+
+/// 法令IDごとの廃止日
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RepealDates {
+  dates: HashMap<String, Date>,
+}
+
+impl RepealDates {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// 廃止日を追加する
+  /// 同じ法令の廃止が複数見つかった場合は早い方を使う
+  pub fn insert(&mut self, law_id: String, date: Date) {
+    self
+      .dates
+      .entry(law_id)
+      .and_modify(|d| *d = (*d).min(date))
+      .or_insert(date);
+  }
+
+  /// 「○○法は、廃止する」のような法令全体の廃止の参照から廃止日を集める
+  /// 廃止日は廃止する側の法令の版の日付とする
+  pub fn add_finds(&mut self, finds: &[Find]) {
+    for find in finds.iter() {
+      if find.relation == Relation::Repeals && find.to.number_text().is_empty() {
+        self.insert(find.to.law_id.clone(), find.from.date);
+      }
+    }
+  }
+
+  pub fn get(&self, law_id: &str) -> Option<Date> {
+    self.dates.get(law_id).copied()
+  }
+
+  /// `date`より前に廃止されていればその廃止日を返す
+  pub fn repealed_before(&self, law_id: &str, date: &Date) -> Option<Date> {
+    self.get(law_id).filter(|d| d < date)
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.dates.is_empty()
+  }
+}
+
+/// 法令名の索引ファイルの形式の版
+/// 形式を変えたら上げて，古い索引を読み込まないようにする
+pub const LAW_INDEX_SCHEMA_VERSION: u32 = 3;
+
+/// 法令名の索引ファイルの先頭行
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LawIndexHeader {
+  pub schema_version: u32,
+  /// 索引を作ったときのこのクレートの版
+  /// 版が違うと法令名の登録の仕方が変わっている可能性があるので，一致しなければ読み込まない
+  pub crate_version: String,
+  /// 二行目以降の内容のハッシュ値（`law_index_content_hash`）
+  pub content_hash: String,
+}
+
+impl LawIndexHeader {
+  /// 二行目以降の内容`body`に対する先頭行を作る
+  pub fn new(body: &str) -> Self {
+    Self {
+      schema_version: LAW_INDEX_SCHEMA_VERSION,
+      crate_version: env!("CARGO_PKG_VERSION").to_string(),
+      content_hash: law_index_content_hash(body),
+    }
+  }
+
+  /// 今の形式・版で作られ，内容`body`が書き換わっていない索引かを確かめる
+  pub fn validate(&self, body: &str) -> Result<()> {
+    if self.schema_version != LAW_INDEX_SCHEMA_VERSION {
+      return Err(EliError::IndexSchemaVersion {
+        found: self.schema_version,
+        expected: LAW_INDEX_SCHEMA_VERSION,
+      });
+    }
+    if self.crate_version != env!("CARGO_PKG_VERSION") {
+      return Err(EliError::IndexCrateVersion {
+        found: self.crate_version.clone(),
+        expected: env!("CARGO_PKG_VERSION").to_string(),
+      });
+    }
+    let hash = law_index_content_hash(body);
+    if self.content_hash != hash {
+      return Err(EliError::IndexHashMismatch {
+        found: self.content_hash.clone(),
+        expected: hash,
+      });
+    }
+    Ok(())
+  }
+}
+
+/// 索引ファイルの内容のハッシュ値
+/// 環境によって値が変わらないよう，標準ライブラリの`Hasher`ではなくFNV-1a（64bit）を使う
+pub fn law_index_content_hash(body: &str) -> String {
+  let hash = body.bytes().fold(0xcbf29ce484222325_u64, |hash, b| {
+    (hash ^ b as u64).wrapping_mul(0x100000001b3)
+  });
+  format!("{hash:016x}")
+}
+
+/// 法令名の索引ファイルの二行目以降の一行
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum LawIndexEntry {
+  /// 法令名と，その名前で参照される法令
+  Name { name: String, law: Law },
+  /// `--version-select at-date`で使う，法令の版と，`LawVersions::insert_articles`で登録した本則の条番号
+  Version {
+    law: Law,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    articles: Option<Vec<ArticleNumber>>,
+  },
+}
+
+/// 法令名の索引
+/// 法令の解析の前半（法令名の登録）の結果で，これがあれば後半の参照の解析を行える
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LawIndex {
+  /// 法令名から法令へ
+  pub law_map: HashMap<String, Law>,
+  /// 法令IDごとの全ての版
+  pub versions: LawVersions,
+}
+
+impl LawIndex {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// 索引ファイルの二行目以降の各行にする
+  /// 同じ内容から同じハッシュ値が得られるよう，法令名・法令IDの順に並べる
+  pub fn entries(&self) -> Vec<LawIndexEntry> {
+    let mut names = self.law_map.keys().collect::<Vec<_>>();
+    names.sort();
+    let mut entries = names
+      .into_iter()
+      .map(|name| LawIndexEntry::Name {
+        name: name.clone(),
+        law: self.law_map[name].clone(),
+      })
+      .collect::<Vec<_>>();
+    entries.extend(self.versions.iter().map(|law| LawIndexEntry::Version {
+      law: law.clone(),
+      articles: self.versions.articles(law),
+    }));
+    entries
+  }
+
+  /// 索引ファイルの一行を加える
+  pub fn insert_entry(&mut self, entry: LawIndexEntry) {
+    match entry {
+      LawIndexEntry::Name { name, law } => {
+        self.law_map.insert(name, law);
+      }
+      LawIndexEntry::Version { law, articles } => {
+        if let Some(articles) = articles {
+          self.versions.insert_articles(&law, articles);
+        }
+        self.versions.insert(law);
+      }
+    }
+  }
+}
+
+/// 法令全体を廃止する規定を探し，廃止日を`dates`に加える
+/// 「廃止」を含む項だけを解析するので，全体を解析する前に廃止日を集めるのに使う
+pub fn collect_repeal_dates(
+  target: &HashMap<String, Law>,
+  law_map: &HashMap<String, Law>,
+  config: &ParseConfig,
+  dates: &mut RepealDates,
+) {
+  let target = target
+    .iter()
+    .filter(|(key, law)| {
+      key.is_empty()
+        || law
+          .paragraph_text
+          .as_ref()
+          .is_some_and(|text| text.contains("廃止"))
+    })
+    .map(|(key, law)| (key.clone(), law.clone()))
+    .collect::<HashMap<_, _>>();
+  let (finds, _) = parse_ref_with_report(&target, law_map, config);
+  dates.add_finds(&finds);
+}
+
+#[test]
+fn check_repeal_dates() {
+  let old_law = Law::new(
+    Date::new_ad(1990, 4, 1),
+    Some(String::from("旧テスト法")),
+    String::from("402AC0000000001"),
+    String::from("平成二年法律第一号"),
+    LawType::Act,
+  );
+  let mut law_map = HashMap::new();
+  law_map.insert(String::from("旧テスト法"), old_law);
+  let target = |date: Date, text: &str| {
+    let mut paragraph = paragraph(text);
+    paragraph.date = date;
+    HashMap::from([(String::from("1"), paragraph)])
+  };
+  let mut dates = RepealDates::new();
+  collect_repeal_dates(
+    &target(Date::new_ad(2000, 4, 1), "旧テスト法は、廃止する。"),
+    &law_map,
+    &ParseConfig::default(),
+    &mut dates,
+  );
+  // 条だけの削除は法令の廃止ではない
+  collect_repeal_dates(
+    &target(Date::new_ad(1995, 4, 1), "旧テスト法第三条を廃止する。"),
+    &law_map,
+    &ParseConfig::default(),
+    &mut dates,
+  );
+  assert_eq!(dates.get("402AC0000000001"), Some(Date::new_ad(2000, 4, 1)));
+  let config = ParseConfig {
+    repeal_dates: dates,
+    ..Default::default()
+  };
+  let text = "旧テスト法第二条の規定による処分";
+  let (finds, _) =
+    parse_ref_with_report(&target(Date::new_ad(2010, 4, 1), text), &law_map, &config);
+  assert_eq!(finds[0].get_repealed_date(), Some(Date::new_ad(2000, 4, 1)));
+  // 廃止より前の版や廃止する規定そのものには付けない
+  let (finds, _) =
+    parse_ref_with_report(&target(Date::new_ad(1999, 4, 1), text), &law_map, &config);
+  assert_eq!(finds[0].get_repealed_date(), None);
+  let (finds, _) = parse_ref_with_report(
+    &target(Date::new_ad(2000, 4, 1), "旧テスト法は、廃止する。"),
+    &law_map,
+    &config,
+  );
+  assert_eq!(finds[0].get_repealed_date(), None);
+}
+
+/// 解析中に見つかった問題の記録
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Diagnostic {
+  /// 問題が見つかった法令の法令ID
+  pub law_id: String,
+  /// 問題が見つかった条（`article3_2`のような形式）
+  pub article: Option<String>,
+  /// 問題が見つかった項（`paragraph2`のような形式）
+  pub paragraph: Option<String>,
+  /// 項のテキスト中の位置
+  pub position: Position,
+  /// 問題となった文字列
+  pub token: String,
+  pub message: String,
+}
+
+/// 「以下「○○」という。」による略称の定義
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct AbbreviationDef {
+  /// 定義された略称
+  pub term: String,
+  /// 略称が指す法令（直前の法令名と紐付けられなかった場合は`None`）
+  pub law: Option<Law>,
+  /// 略称を定義している項
+  pub defined_in: Law,
+  /// `defined_in`の中での略称（鉤括弧の中身）の位置
+  pub position: Position,
+}
+
+/// 「最高裁判所平成二十年六月四日大法廷判決」・「最判令和元年五月一日」のような判例の引用
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct CaseLawRef {
+  /// 裁判所（「最高裁判所」・「最判」・「東京高判」など，書かれたまま）
+  pub court: String,
+  /// 判決・決定の日付
+  pub date: Option<Date>,
+  /// 引用のテキスト全体
+  pub text: String,
+  /// 判例を引用している項
+  pub cited_in: Law,
+  /// `cited_in`の中での引用の位置
+  pub position: Position,
+}
+
+impl CaseLawRef {
+  /// 引用している項に，引用のテキストを`eli:cited_by_case_law_reference`で付けたトリプル
+  pub fn triple(&self) -> Triple {
+    Triple {
+      subject: NamedNode::new(eli::Eli::eli_uri(&self.cited_in))
+        .unwrap()
+        .into(),
+      predicate: EliOntology::CitedByCaseLawReference.named_node(),
+      object: Literal::new_simple_literal(&self.text).into(),
+    }
+  }
+}
+
+/// 紐付け先が見つからずに捨てた「同法」・「同令」・「同条」などの出現
+/// 直前の法令名や条項を見つけられなかったことを示すので，法令名の検索漏れを探す手がかりになる
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct UnresolvedRef {
+  /// 出現した語（「同法」・「同条」など）
+  pub token: String,
+  /// 語が出現した項
+  pub from: Law,
+  /// `from`の中での語の位置
+  pub position: Position,
+}
+
+/// 重複の解消や略称・「同法」の紐付けを終え，条項番号を読み取る前の法令名の候補
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct NameCandidate {
+  /// 候補が出現した項
+  pub from: Law,
+  /// `from`の中での法令名の位置
+  pub position: Position,
+  /// 一致した文字列
+  pub match_string: String,
+  /// 法令名の見つけ方
+  pub kind: MatchKind,
+  /// 紐付けた法令の法令ID（紐付け先が無い場合は`None`）
+  pub law_id: Option<String>,
+}
+
+/// 参照情報の抽出結果に付随する情報
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ParseReport {
+  /// 法令名の見つけ方ごとの件数
+  pub stats: MatchStats,
+  /// `ParseConfig::strict`のときに記録される診断情報
+  pub diagnostics: Vec<Diagnostic>,
+  /// `ParseConfig::abbreviations`のときに記録される略称の定義
+  pub abbreviations: Vec<AbbreviationDef>,
+  /// `ParseConfig::case_law`のときに記録される判例の引用
+  pub case_law_refs: Vec<CaseLawRef>,
+  /// `ParseConfig::unresolved`のときに記録される，紐付け先が見つからなかった「同法」・「同条」など
+  pub unresolved: Vec<UnresolvedRef>,
+  /// `ParseConfig::debug_candidates`のときに記録される，条項番号を読み取る前の法令名の候補
+  pub candidates: Vec<NameCandidate>,
+}
+
+/// 参照を探すときの手がかりにする語句の一覧
+/// 古い表記（「並に」など）や実験的な語句を正規表現を書き換えずに追加できるようにする
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keywords {
+  /// 直前に出た法令を指す語（「同法」・「同令」）
+  pub douhou: Vec<String>,
+  /// その法令自身を指す語（「この法律」・「この政令」・「本法」など）
+  pub self_reference: Vec<String>,
+  /// 略称の定義で鉤括弧の前に置かれる語（「以下「法」という。」の「以下」）
+  pub abb_def_prefix: Vec<String>,
+  /// 略称とみなす語の末尾（「法」・「令」・「規則」）
+  pub abb_suffix: Vec<String>,
+  /// 略称の末尾に一致しても法令名ではないものの末尾（「方法」・「命令」）
+  pub abb_exclude_suffix: Vec<String>,
+  /// 参照の直後に来ると委任や根拠を示す語（「により」・「に基づき」など）
+  /// 直前の「の規定」は省略できる
+  pub based_on: Vec<String>,
+  /// 改正文であることを示す語（「改める」・「加える」・「削る」）
+  /// 「改め、」のような連用形にも一致するよう語幹で書く
+  pub amendment: Vec<String>,
+  /// 法令名と条項番号の間で読み飛ばす括弧の組（開き括弧と閉じ括弧）
+  /// 「民法（明治二十九年法律第八十九号）第九十条」の法令番号の注記などを囲む
+  pub parens: Vec<(char, char)>,
+}
+
+impl Default for Keywords {
+  fn default() -> Self {
+    let to_vec = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+    Keywords {
+      douhou: to_vec(&["同法", "同令"]),
+      self_reference: to_vec(&[
+        "この法律",
+        "この政令",
+        "この勅令",
+        "この省令",
+        "この府令",
+        "この規則",
+        "この命令",
+        "本法",
+      ]),
+      abb_def_prefix: to_vec(&["以下"]),
+      abb_suffix: to_vec(&["法", "令", "規則"]),
+      abb_exclude_suffix: to_vec(&["方法", "命令"]),
+      based_on: to_vec(&[
+        "により",
+        "によって",
+        "に基づき",
+        "に基づいて",
+        "に基づく",
+        "に基き",
+        "に基いて",
+        "に基く",
+      ]),
+      amendment: to_vec(&["改め", "加え", "削る", "削り"]),
+      parens: vec![('（', '）')],
+    }
+  }
+}
+
+/// `Keywords`から組み立てた正規表現と，法令番号などの固定の正規表現
+/// 項ごとに作り直さないよう，解析の始めに一度だけ作る
+/// 空の一覧から作るとどこにでも一致してしまうので，その場合は`None`とし，その手がかりを使わない
+struct KeywordRegexes {
+  douhou: Option<Regex>,
+  self_reference: Option<Regex>,
+  abb_def: Option<Regex>,
+  abb_exclude_suffix: Vec<String>,
+  based_on: Option<Regex>,
+  amendment: Option<Regex>,
+  /// 「平成十年政令第五号」のような法令番号
+  law_number: Regex,
+  parens: Vec<(char, char)>,
+}
+
+impl KeywordRegexes {
+  fn new(keywords: &Keywords) -> Self {
+    // 空の語は除き，一つも無ければ`None`とする
+    let alt = |v: &[String]| {
+      let v = v
+        .iter()
+        .filter(|s| !s.is_empty())
+        .map(|s| regex::escape(s))
+        .collect::<Vec<_>>();
+      (!v.is_empty()).then(|| v.join("|"))
+    };
+    let regex = |s: String| Regex::new(&s).unwrap();
+    KeywordRegexes {
+      douhou: alt(&keywords.douhou).map(regex),
+      self_reference: alt(&keywords.self_reference).map(regex),
+      abb_def: alt(&keywords.abb_def_prefix)
+        .zip(alt(&keywords.abb_suffix))
+        .map(|(prefix, suffix)| regex(format!("(?:{prefix})「(?<abb>[^」]*(?:{suffix}))」"))),
+      abb_exclude_suffix: keywords.abb_exclude_suffix.clone(),
+      parens: keywords.parens.clone(),
+      based_on: alt(&keywords.based_on).map(|s| regex(format!("^(の規定)?({s})"))),
+      amendment: alt(&keywords.amendment).map(regex),
+      law_number: Regex::new(&format!(
+        "(?:明治|大正|昭和|平成|令和)(?:元|{number})年(?:法律|政令|勅令|太政官布告|[^、。，「」（）\\s]{{1,20}}?(?:省令|府令|庁令|規則))第{number}号",
+        number = "[〇一二三四五六七八九十百千]+"
+      ))
+      .unwrap(),
+    }
+  }
+}
+
+/// 解析対象の法令の各部分と，番号だけで参照されたときに参照先を確かめるための一覧
+struct TargetParts<'a> {
+  parts: &'a HashMap<String, Law>,
+  /// 附則から番号だけで参照されたときに使う，本則の条番号の一覧
+  main_articles: HashSet<ArticleNumber>,
+  /// 法令名無しで参照されたときに使う，別表の番号の一覧
+  appdx_tables: HashSet<String>,
+}
+
+impl<'a> TargetParts<'a> {
+  fn new(parts: &'a HashMap<String, Law>) -> Self {
+    let main_articles = parts
+      .values()
+      .filter(|l| l.suppl_provision.is_none())
+      .filter_map(|l| l.article_number.clone())
+      .collect::<HashSet<_>>();
+    let appdx_tables = parts
+      .values()
+      .filter_map(|l| l.appdx_table.clone())
+      .collect::<HashSet<_>>();
+    Self {
+      parts,
+      main_articles,
+      appdx_tables,
+    }
+  }
+}
+
+/// 項ごとに参照を探すときに，項をまたいで引き継ぐ情報
+///
+/// 「以下「法」という。」で定義された略称と，「○○法の一部を次のように改正する」で示された改正される法令は，
+/// 後の項の参照の解決に使う．同じ文書の項は出現順に，同じ`ReferenceContext`を渡して処理すること．
+/// 「同条」・「同項」の紐付け先は項の中でだけ使い，次の項には引き継がない．
+/// 見つけ方ごとの件数や診断情報は項をまたいで足し合わせ，`into_report`で取り出せる
+pub struct ReferenceContext<'a> {
+  config: &'a ParseConfig,
+  regexes: KeywordRegexes,
+  /// 法令番号だけで参照されたときに使う，法令番号から法令への対応
+  law_number_index: HashMap<&'a str, &'a Law>,
+  /// `law_map`の法令名を`find_law_name`で探す形にしたもの
+  law_names: Vec<LawNamePattern<'a>>,
+  /// `find_refs_in_paragraph`で参照元にする法令
+  source: Law,
+  /// これまでの項で定義された略称
+  law_name_list: Vec<FindLawName>,
+  /// 「○○法の一部を次のように改正する」で示された，改正される法令
+  amended_law: Option<Law>,
+  report: ParseReport,
+}
+
+impl<'a> ReferenceContext<'a> {
+  /// `law_map`は`find_refs_in_paragraph`に渡すものと同じものにする
+  /// `source`は`find_refs_in_paragraph`で見つけた参照の参照元（`Find::get_from`）にする法令
+  pub fn new(law_map: &'a HashMap<String, Law>, config: &'a ParseConfig, source: Law) -> Self {
+    // 法令番号そのものをkeyとして登録されているものを優先する
+    let mut law_number_index: HashMap<&str, &Law> = HashMap::new();
+    for (name, law) in law_map.iter() {
+      if law.law_id_text.is_empty() {
+        continue;
+      }
+      if *name == law.law_id_text || !law_number_index.contains_key(law.law_id_text.as_str()) {
+        law_number_index.insert(&law.law_id_text, law);
+      }
+    }
+    Self {
+      config,
+      regexes: KeywordRegexes::new(&config.keywords),
+      law_number_index,
+      law_names: law_name_patterns(law_map),
+      source,
+      law_name_list: Vec::new(),
+      amended_law: None,
+      report: ParseReport::default(),
+    }
+  }
+
+  /// これまでの項での見つけ方ごとの件数・診断情報・略称の定義など
+  pub fn report(&self) -> &ParseReport {
+    &self.report
+  }
+
+  pub fn into_report(self) -> ParseReport {
+    self.report
+  }
+}
+
+/// XMLから読み込んだ法令ではない，任意のテキスト（契約書など）の一つの段落から参照を探す
+///
+/// 参照元は`ReferenceContext::new`で指定した法令とし，その項のテキストを`text`にする．
+/// 「この法律」や番号だけの「第三条」のような，参照元の法令自身への参照は探さない．
+/// 複数の段落を処理するときは，前の段落の略称などを使えるよう，同じ`carry`を順に渡す
+pub fn find_refs_in_paragraph(
+  text: &str,
+  law_map: &HashMap<String, Law>,
+  carry: &mut ReferenceContext,
+) -> Vec<Find> {
+  let mut paragraph = carry.source.clone();
+  paragraph.set_paragraph_text(text.to_string());
+  let parts = HashMap::new();
+  let target = TargetParts::new(&parts);
+  find_refs(&paragraph, text, law_map, &target, carry)
+}
+
+/// 参照情報を抽出する
+/// - target: 解析対象の法令の情報
+/// - law_map: 法令名や法令IDのテキストをkeyとし，法令全体を表すLawをvalueとするhashmap
+pub fn parse_ref(target: &HashMap<String, Law>, law_map: &HashMap<String, Law>) -> Vec<Find> {
+  parse_ref_with_config(target, law_map, &ParseConfig::default())
+}
+
+/// 設定を指定して参照情報を抽出する
+pub fn parse_ref_with_config(
+  target: &HashMap<String, Law>,
+  law_map: &HashMap<String, Law>,
+  config: &ParseConfig,
+) -> Vec<Find> {
+  parse_ref_with_report(target, law_map, config).0
+}
+
+/// 参照情報を抽出し，法令名の見つけ方ごとの件数や診断情報も返す
+/// 一文字の略称のような精度の低い見つけ方がどれだけ使われたかを調べるのに使う
+pub fn parse_ref_with_report(
+  target: &HashMap<String, Law>,
+  law_map: &HashMap<String, Law>,
+  config: &ParseConfig,
+) -> (Vec<Find>, ParseReport) {
+  let mut result = Vec::new();
+  let report = parse_ref_with(target, law_map, config, |find| {
+    result.push(find);
+    ControlFlow::Continue(())
+  });
+  (result, report)
+}
+
+/// 参照情報を見つけるたびに`f`を呼び出す
+/// 結果を溜め込まないので，参照の多い法令でもメモリ使用量が増えない
+/// `f`が`ControlFlow::Break`を返すとそこで解析を打ち切る
+pub fn parse_ref_with<F>(
+  target: &HashMap<String, Law>,
+  law_map: &HashMap<String, Law>,
+  config: &ParseConfig,
+  mut f: F,
+) -> ParseReport
+where
+  F: FnMut(Find) -> ControlFlow<()>,
+{
+  // 段落を順番で並べ替える
+  // 改正法令ごとの附則のように条項番号が同じ段落もあるので，附則の改正法令番号とkeyで順序を決める
+  let mut paragraph_list = target
+    .iter()
+    .filter(|(_, l)| l.paragraph_text.is_some())
+    .collect::<Vec<_>>();
+  paragraph_list.sort_by(|(a_key, a), (b_key, b)| {
+    ord_article(a, b)
+      .then_with(|| a.suppl_provision.cmp(&b.suppl_provision))
+      .then_with(|| a_key.cmp(b_key))
+  });
+  let paragraph_list = paragraph_list
+    .into_iter()
+    .map(|(_, l)| l.clone())
+    .collect::<Vec<_>>();
+
+  // 参照元は項ごとに`find_refs`に渡すので，`ReferenceContext`の参照元は使わない
+  let Some(first) = paragraph_list.first() else {
+    return ParseReport::default();
+  };
+  let mut carry = ReferenceContext::new(law_map, config, first.without_text());
+  let target = TargetParts::new(target);
+
+  for paragraph in paragraph_list.iter() {
+    trace!(
+      "[START] parse paragraph {:?} - {:?}",
+      paragraph.article_number_str(),
+      paragraph.paragraph_number_str()
+    );
+    if let Some(original_text) = &paragraph.paragraph_text {
+      let result = find_refs(paragraph, original_text, law_map, &target, &mut carry);
+      for find in result.into_iter() {
+        if f(find).is_break() {
+          return carry.into_report();
+        }
+      }
+    }
+    trace!(
+      "[END] parse paragraph {:?} - {:?}",
+      paragraph.article_number_str(),
+      paragraph.paragraph_number_str()
+    );
+  }
+  carry.into_report()
+}
+
+/// 一つの項のテキスト`original_text`から参照を探す
+/// `paragraph`は参照元の項で，`target`はその項を含む法令
+fn find_refs(
+  paragraph: &Law,
+  original_text: &str,
+  law_map: &HashMap<String, Law>,
+  target: &TargetParts,
+  carry: &mut ReferenceContext,
+) -> Vec<Find> {
+  // この項で見つかった参照
+  let mut result = Vec::new();
+  // ゼロ幅文字などを取り除いたテキストで検索し，位置は元のテキストに戻す
+  let (cleaned_text, char_map) = clean_text(original_text);
+  let text = &cleaned_text;
+  // 改正文で引用されている文言の範囲
+  let amendment_quotes = find_amendment_quotes(text, &carry.regexes);
+  let is_amendment_quote = |start: usize| {
+    amendment_quotes
+      .iter()
+      .any(|p| p.start <= start && start < p.end)
+  };
+  // 正式名称の一覧を持ってテキスト内検索を行う
+  let mut find_law_name_result = find_law_name(text, &carry.law_names, &carry.law_name_list);
+  // 法令名の一覧に無い法令番号も，法令番号の一覧から探す
+  let mut found_law_number = false;
+  for find in find_law_number(text, &carry.law_number_index, &carry.regexes).into_iter() {
+    if find.find_law.is_some() {
+      find_law_name_result = resolve_duplicates(&find_law_name_result, &find);
+      found_law_number = true;
+    } else if carry.config.strict {
+      carry.report.diagnostics.push(Diagnostic {
+        law_id: paragraph.get_law_id(),
+        article: paragraph.article_number_str(),
+        paragraph: paragraph.paragraph_number_str(),
+        position: restore_position(&char_map, &find.position),
+        message: format!("unknown law number: {}", find.match_string),
+        token: find.match_string,
+      });
+    }
+  }
+  if found_law_number {
+    sort_by_position(&mut find_law_name_result);
+    find_law_name_result = resolve_name_and_number(&find_law_name_result, text);
+  }
+
+  // 「この法律」などはその法令自身を指す
+  // 改正法令の附則の中では，その附則を置いた改正法令を指す
+  let self_law = match &paragraph.suppl_provision {
+    Some(amend_law_num) if !amend_law_num.is_empty() => law_map.get(amend_law_num),
+    _ => target.parts.get(""),
+  };
+  if let Some(self_law) = self_law {
+    for mut find in find_self_reference(text, &carry.regexes).into_iter() {
+      find.find_law = Some(self_law.clone());
+      find_law_name_result = resolve_duplicates(&find_law_name_result, &find);
+    }
+    sort_by_position(&mut find_law_name_result);
+  }
+
+  // 略称の定義箇所を検索する
+  let find_abb_def_result = find_abb_def(text, &carry.regexes);
+  // 今までの項で見つかった法令名と，この項で見つかった略称を紐付けていく
+  let mut linked_abb_def_result = find_abb_def_result
+    .iter()
+    .filter_map(|l| linking_abb_and_full_name(l, &find_law_name_result))
+    .collect::<Vec<_>>();
+  if carry.config.abbreviations {
+    for abb in find_abb_def_result.iter() {
+      let law = linked_abb_def_result
+        .iter()
+        .find(|l| l.position == abb.position)
+        .and_then(|l| l.find_law.as_ref())
+        .map(|l| l.without_text());
+      carry.report.abbreviations.push(AbbreviationDef {
+        term: abb.match_string.clone(),
+        law,
+        defined_in: paragraph.without_text(),
+        position: restore_position(&char_map, &abb.position),
+      });
+    }
+  }
+
+  // 同法に紐付けさせるためのリスト
+  let mut linked_abb_def_result2 = linked_abb_def_result.clone();
+  find_law_name_result.append(&mut linked_abb_def_result2);
+
+  // 同法・同令の出現位置を検索する
+  let find_douhou_result = find_douhou(text, &carry.regexes);
+  // 今までの項で見つかった法令名と略称の情報と，この項で見つかった「同法」・「同令」を紐付けていく
+  let mut linked_douhou_result = Vec::new();
+  for douhou in find_douhou_result.iter() {
+    match linking_abb_and_full_name(douhou, &find_law_name_result) {
+      Some(linked) => linked_douhou_result.push(linked),
+      None if carry.config.unresolved => carry.report.unresolved.push(UnresolvedRef {
+        token: douhou.match_string.clone(),
+        from: paragraph.without_text(),
+        position: restore_position(&char_map, &douhou.position),
+      }),
+      None => (),
+    }
+  }
+
+  find_law_name_result.append(&mut linked_douhou_result);
+  // 後から追加した略称・同法の分も含めて出現順にする
+  sort_by_position(&mut find_law_name_result);
+
+  if carry.config.expand_enforcement_orders {
+    find_law_name_result = expand_enforcement_orders(text, &find_law_name_result, law_map);
+  }
+
+  if carry.config.debug_candidates {
+    for candidate in find_law_name_result.iter() {
+      carry.report.candidates.push(NameCandidate {
+        from: paragraph.without_text(),
+        position: restore_position(&char_map, &candidate.position),
+        match_string: candidate.match_string.clone(),
+        kind: candidate.kind,
+        law_id: candidate.find_law.as_ref().map(|l| l.get_law_id()),
+      });
+    }
+  }
+
+  // この項で見つかった参照の範囲
+  let mut found_positions = Vec::new();
+  // 条項番号として読めなかった文字列と，それを含む参照の範囲
+  let mut unparsed_list = Vec::new();
+  // この項で条まで解決できた参照
+  // 「同条」・「同項」・「同号」は，これらのうち直前のものを指すとして紐付ける
+  let mut locators = Vec::new();
+  // 条項の検索をする
+  let mut name_refs = Vec::new();
+  for find_law_name in find_law_name_result.iter() {
+    if let Some(l) = &find_law_name.find_law {
+      let mut unparsed = Vec::new();
+      let (end, joukou) = find_joukou(
+        text,
+        &find_law_name.position,
+        &carry.regexes.parens,
+        &mut unparsed,
+      );
+      name_refs.push((find_law_name, l, end, joukou, unparsed));
+    }
+  }
+  // 一つの法令を複数の法令名で登録しているので，
+  // 「明治二十九年法律第八十九号（民法）第九十条」のように同じ参照の中で別の法令名にも一致することがある
+  let keep = keep_outer_names(
+    text,
+    &name_refs
+      .iter()
+      .map(|(find_law_name, l, _, _, _)| (find_law_name.position, l.law_id.as_str()))
+      .collect::<Vec<_>>(),
+    &carry.regexes.parens,
+  );
+  let name_refs = name_refs
+    .into_iter()
+    .zip(keep)
+    .filter_map(|(name_ref, keep)| keep.then_some(name_ref));
+  for (find_law_name, l, end, joukou, unparsed) in name_refs {
+    let mut to_law = match &carry.config.version_select {
+      VersionSelect::Latest => l.clone(),
+      VersionSelect::AtDate(versions) => match &joukou.article_number {
+        Some(article) if !joukou.suppl_provision => {
+          versions.at_date_with_article(&l.law_id, &paragraph.date, article)
+        }
+        _ => versions.at_date(&l.law_id, &paragraph.date),
+      }
+      .unwrap_or(l)
+      .clone(),
+    };
+    to_law = to_law.with_locator(joukou);
+    to_law.fill_constitution_chapter();
+    let mut items = vec![(find_law_name.position.start, end, to_law, unparsed)];
+    // 「民法第九十条第一項、第二項」・「民法第九十条及び第九十一条」のように並べて書かれた条項も同じ法令の参照とし，
+    // 書かれていない上の階層は直前の条項から引き継ぐ
+    while let Some((_, end, to_law, _)) = items.last()
+      && let Some(start) = find_listed_locator(text, *end)
+    {
+      let mut unparsed = Vec::new();
+      let (end, joukou) = find_joukou(
+        text,
+        &Position { start, end: start },
+        &carry.regexes.parens,
+        &mut unparsed,
+      );
+      if joukou == JoukouRef::default() {
+        break;
+      }
+      let to_law = to_law.with_locator(joukou);
+      items.push((start, end, to_law, unparsed));
+    }
+    for (i, (start, end, to_law, unparsed)) in items.into_iter().enumerate() {
+      unparsed_list.push((start, end, unparsed));
+      found_positions.push(Position { start, end });
+      // 参照の直後の表現から参照の種類を決める
+      let relation = find_relation(text, end, &carry.regexes);
+      carry.report.stats.add(find_law_name.kind);
+      push_locator(&mut locators, start, end, &to_law);
+      if i == 0 && relation == Relation::Changes && to_law.article_number.is_none() {
+        carry.amended_law = Some(to_law.clone());
+      }
+      // 結果を返す
+      result.push(Find {
+        to: to_law,
+        from: paragraph.clone(),
+        position: restore_position(&char_map, &Position { start, end }),
+        relation,
+        amendment_quote: is_amendment_quote(start),
+        from_item: Vec::new(),
+        kind: find_law_name.kind,
+        match_string: find_law_name.match_string.clone(),
+        repealed_date: None,
+      });
+    }
+  }
+
+  // 附則の中で，本則の条を番号だけで参照している箇所を探す
+  if paragraph.suppl_provision.is_some()
+    && let Some(root) = target.parts.get("")
+  {
+    for position in find_same_law_article(text, &found_positions) {
+      let mut to_law = root.clone();
+      let mut unparsed = Vec::new();
+      let (end, joukou) = find_joukou(text, &position, &carry.regexes.parens, &mut unparsed);
+      to_law = to_law.with_locator(joukou);
+      // 本則に存在する条だけを参照とみなす
+      if !to_law
+        .article_number
+        .as_ref()
+        .is_some_and(|num| target.main_articles.contains(num))
+      {
+        continue;
+      }
+      unparsed_list.push((position.start, end, unparsed));
+      found_positions.push(Position {
+        start: position.start,
+        end,
+      });
+      let relation = find_relation(text, end, &carry.regexes);
+      carry.report.stats.add(MatchKind::SameLaw);
+      push_locator(&mut locators, position.start, end, &to_law);
+      result.push(Find {
+        to: to_law,
+        from: paragraph.clone(),
+        position: restore_position(
+          &char_map,
+          &Position {
+            start: position.start,
+            end,
+          },
+        ),
+        relation,
+        amendment_quote: is_amendment_quote(position.start),
+        from_item: Vec::new(),
+        kind: MatchKind::SameLaw,
+        match_string: String::new(),
+        repealed_date: None,
+      });
+    }
+  }
+
+  // 法令名を伴わない「別表第○」を，この法令の別表への参照として扱う
+  if let Some(root) = target.parts.get("") {
+    for position in find_same_law_appdx_table(text, &found_positions) {
+      let mut to_law = root.clone();
+      let mut unparsed = Vec::new();
+      let (end, joukou) = find_joukou(text, &position, &carry.regexes.parens, &mut unparsed);
+      to_law = to_law.with_locator(joukou);
+      if !to_law
+        .appdx_table
+        .as_ref()
+        .is_some_and(|num| target.appdx_tables.contains(num))
+      {
+        continue;
+      }
+      unparsed_list.push((position.start, end, unparsed));
+      found_positions.push(Position {
+        start: position.start,
+        end,
+      });
+      let relation = find_relation(text, end, &carry.regexes);
+      carry.report.stats.add(MatchKind::SameLaw);
+      push_locator(&mut locators, position.start, end, &to_law);
+      result.push(Find {
+        to: to_law,
+        from: paragraph.clone(),
+        position: restore_position(
+          &char_map,
+          &Position {
+            start: position.start,
+            end,
+          },
+        ),
+        relation,
+        amendment_quote: is_amendment_quote(position.start),
+        from_item: Vec::new(),
+        kind: MatchKind::SameLaw,
+        match_string: String::new(),
+        repealed_date: None,
+      });
+    }
+  }
+
+  // 改正される法令の編・章・節・款・目・条を番号だけで指している改正文を探す
+  // 「同条第二項第三号を削る」のように，これを基準にした「同条」なども改正の対象になるので，「同条」などより先に処理する
+  if let Some(amended) = &carry.amended_law {
+    let mut last_end = 0;
+    for position in find_amend_targets(text, &found_positions) {
+      if position.start < last_end || is_amendment_quote(position.start) {
+        continue;
+      }
+      let mut to_law = amended.clone();
+      let mut unparsed = Vec::new();
+      let (end, joukou) = find_joukou(text, &position, &carry.regexes.parens, &mut unparsed);
+      to_law = to_law.with_locator(joukou);
+      last_end = end;
+      let Some(relation) = find_amend_relation(text, end) else {
+        continue;
+      };
+      unparsed_list.push((position.start, end, unparsed));
+      found_positions.push(Position {
+        start: position.start,
+        end,
+      });
+      carry.report.stats.add(MatchKind::AmendTarget);
+      push_locator(&mut locators, position.start, end, &to_law);
+      result.push(Find {
+        to: to_law,
+        from: paragraph.clone(),
+        position: restore_position(
+          &char_map,
+          &Position {
+            start: position.start,
+            end,
+          },
+        ),
+        relation,
+        amendment_quote: false,
+        from_item: Vec::new(),
+        kind: MatchKind::AmendTarget,
+        match_string: String::new(),
+        repealed_date: None,
+      });
+    }
+  }
+
+  // 「同条第二項」のように，直前に解決した条項を基準にした参照を探す
+  // 直前の参照より後ろに出てくるので，同法などの後で処理する
+  sort_by_position(&mut locators);
+  for doujou in find_doujou(text, &found_positions).iter() {
+    let Some(mut to_law) =
+      linking_abb_and_full_name(doujou, &locators).and_then(|linked| linked.find_law)
+    else {
+      if carry.config.unresolved {
+        carry.report.unresolved.push(UnresolvedRef {
+          token: doujou.match_string.clone(),
+          from: paragraph.without_text(),
+          position: restore_position(&char_map, &doujou.position),
+        });
+      }
+      continue;
+    };
+    match doujou.match_string.as_str() {
+      // 条だけを引き継ぎ，項と号は後ろに続く番号で決める
+      "同条" => {
+        to_law.paragraph_number = None;
+        to_law.item_number = None;
+      }
+      "同項" if to_law.paragraph_number.is_none() => continue,
+      "同項" => to_law.item_number = None,
+      // 直前の参照が号まで解決できていない場合は，同じ条項を指すものとする
+      _ => (),
+    }
+    let mut unparsed = Vec::new();
+    let (end, joukou) = find_joukou(text, &doujou.position, &carry.regexes.parens, &mut unparsed);
+    to_law = to_law.with_locator(joukou);
+    unparsed_list.push((doujou.position.start, end, unparsed));
+    found_positions.push(Position {
+      start: doujou.position.start,
+      end,
+    });
+    // 改正文の中で改正される法令の条項を指す場合は，「同条第二項第三号を削る」のように改正の対象になる
+    let amend_relation = carry
+      .amended_law
+      .as_ref()
+      .filter(|amended| {
+        amended.law_id == to_law.law_id && !is_amendment_quote(doujou.position.start)
+      })
+      .and_then(|_| find_amend_relation(text, end));
+    let relation = amend_relation.unwrap_or_else(|| find_relation(text, end, &carry.regexes));
+    carry.report.stats.add(MatchKind::Doujou);
+    // 「同条第二項」の後の「同項」は第二項を指すので，これも紐付け先にする
+    push_locator(&mut locators, doujou.position.start, end, &to_law);
+    result.push(Find {
+      to: to_law,
+      from: paragraph.clone(),
+      position: restore_position(
+        &char_map,
+        &Position {
+          start: doujou.position.start,
+          end,
+        },
+      ),
+      relation,
+      amendment_quote: is_amendment_quote(doujou.position.start),
+      from_item: Vec::new(),
+      kind: MatchKind::Doujou,
+      match_string: doujou.match_string.clone(),
+      repealed_date: None,
+    });
+  }
+
+  // 条番号無しの「第二項」は，この項と同じ条の項を指す
+  // 「同条」などで見つかった参照の中のものは除くので，それらの後で処理する
+  if let Some(root) = target.parts.get("") {
+    for position in find_same_article_paragraph(text, &found_positions) {
+      let mut to_law = root.clone();
+      to_law.suppl_provision = paragraph.suppl_provision.clone();
+      to_law.article_number = paragraph.article_number.clone();
+      let mut unparsed = Vec::new();
+      let (end, joukou) = find_joukou(text, &position, &carry.regexes.parens, &mut unparsed);
+      to_law = to_law.with_locator(joukou);
+      // この法令に存在する項だけを参照とみなす
+      let mut key_law = to_law.clone();
+      key_law.item_number = None;
+      if to_law.paragraph_number.is_none() || !target.parts.contains_key(&key_law.number_text()) {
+        continue;
+      }
+      unparsed_list.push((position.start, end, unparsed));
+      found_positions.push(Position {
+        start: position.start,
+        end,
+      });
+      let relation = find_relation(text, end, &carry.regexes);
+      carry.report.stats.add(MatchKind::SameArticle);
+      push_locator(&mut locators, position.start, end, &to_law);
+      result.push(Find {
+        to: to_law,
+        from: paragraph.clone(),
+        position: restore_position(
+          &char_map,
+          &Position {
+            start: position.start,
+            end,
+          },
+        ),
+        relation,
+        amendment_quote: is_amendment_quote(position.start),
+        from_item: Vec::new(),
+        kind: MatchKind::SameArticle,
+        match_string: String::new(),
+        repealed_date: None,
+      });
+    }
+  }
+
+  if carry.config.strict {
+    for (start, end, unparsed) in unparsed_list.into_iter() {
+      for token in unparsed.into_iter() {
+        carry.report.diagnostics.push(Diagnostic {
+          law_id: paragraph.get_law_id(),
+          article: paragraph.article_number_str(),
+          paragraph: paragraph.paragraph_number_str(),
+          position: restore_position(&char_map, &Position { start, end }),
+          message: format!("cannot parse article number: {token}"),
+          token,
+        });
+      }
+    }
+  }
+
+  if carry.config.case_law {
+    for (position, court, date_text) in find_case_law(text).into_iter() {
+      carry.report.case_law_refs.push(CaseLawRef {
+        court,
+        date: date_from_wareki_text(&date_text),
+        text: text
+          .chars()
+          .skip(position.start)
+          .take(position.end - position.start)
+          .collect(),
+        cited_in: paragraph.without_text(),
+        position: restore_position(&char_map, &position),
+      });
+    }
+  }
+
+  // 号の中にある参照は，参照元をその号にする
+  for find in result.iter_mut() {
+    find.from_item = item_numbers_at(&paragraph.paragraph_items, find.position.start);
+    find.repealed_date = carry
+      .config
+      .repeal_dates
+      .repealed_before(&find.to.law_id, &paragraph.date);
+  }
+
+  // 略称は他の項でも見るので追加
+  carry.law_name_list.append(&mut linked_abb_def_result);
+  result
+}
+
+/// 「以下「○○」という。」による略称の定義だけを一覧にする
+/// 法令用語の辞書を作るときのように，参照の解決結果は要らない場合に使う
+pub fn extract_abbreviations(
+  target: &HashMap<String, Law>,
+  law_map: &HashMap<String, Law>,
+  config: &ParseConfig,
+) -> Vec<AbbreviationDef> {
+  let config = ParseConfig {
+    abbreviations: true,
+    ..config.clone()
+  };
+  parse_ref_with(target, law_map, &config, |_| ControlFlow::Continue(())).abbreviations
+}
+
+/// 検索の妨げになる文字かどうか
+/// ゼロ幅スペース・ゼロ幅接合子などの書式文字，異体字セレクタ，改行以外の制御文字が該当する
+fn is_ignorable_char(c: char) -> bool {
+  matches!(
+    c,
+    '\u{00AD}'
+      | '\u{200B}'..='\u{200F}'
+      | '\u{2060}'..='\u{2064}'
+      | '\u{FEFF}'
+      | '\u{FE00}'..='\u{FE0F}'
+      | '\u{E0100}'..='\u{E01EF}'
+  ) || (c.is_control() && c != '\n')
+}
+
+/// 踊り字（「々」・「〻」）を直前の文字に置き換える
+/// 「段々」と「段段」のような書き分けがあっても法令名が一致するように，法令名とテキストの両方に使う
+/// 一文字を一文字に置き換えるので，文字位置は変わらない
+fn expand_odoriji(text: &str) -> String {
+  let mut expanded = String::new();
+  let mut prev = None;
+  for c in text.chars() {
+    let c = match (c, prev) {
+      ('々' | '〻', Some(p)) => p,
+      _ => c,
+    };
+    expanded.push(c);
+    prev = Some(c);
+  }
+  expanded
+}
+
+/// 読点・句点の書き分け（`,`・`，`・`､`と`.`・`．`・`｡`）を「、」・「。」にそろえる
+/// 「以下「○○」という。」や，並べて書かれた条項の区切りの判定がデータの書き方によらず働くようにする
+/// 一文字を一文字に置き換えるので，文字位置は変わらない
+fn normalize_punctuation(text: &str) -> String {
+  text.chars().map(normalize_punctuation_char).collect()
+}
+
+/// `normalize_punctuation`の一文字分
+fn normalize_punctuation_char(c: char) -> char {
+  match c {
+    ',' | '，' | '､' => '、',
+    '.' | '．' | '｡' => '。',
+    _ => c,
+  }
+}
+
+/// 法令名の末尾に付いた「（略称：○○）」のような注記の括弧の中身の書き出し
+const NAME_NOTE_PREFIXES: [&str; 4] = ["略称", "通称", "旧称", "旧題名"];
+
+/// 法令名の末尾の「（略称：○○）」のような注記を取り除く
+/// 引用するテキストには注記が書かれないので，検索には注記を除いた法令名を使う
+/// 題名の一部である括弧を取り除かないよう，中身が`NAME_NOTE_PREFIXES`で始まる括弧だけを対象にする
+fn strip_name_note(name: &str) -> &str {
+  let Some(inner) = name.strip_suffix('）') else {
+    return name;
+  };
+  let Some(open) = inner.rfind('（') else {
+    return name;
+  };
+  let note = &inner[open + '（'.len_utf8()..];
+  if open == 0 || note.contains('（') || !NAME_NOTE_PREFIXES.iter().any(|p| note.starts_with(p)) {
+    return name;
+  }
+  &name[..open]
+}
+
+/// 検索の妨げになる文字を取り除き，踊り字を直前の文字に置き換え，読点・句点をそろえたテキストと，
+/// 取り除いた後の文字位置から元のテキストの文字位置への対応表を返す
+/// 対応表の最後には元のテキストの文字数が入る
+fn clean_text(text: &str) -> (String, Vec<usize>) {
+  let mut cleaned = String::new();
+  let mut char_map = Vec::new();
+  let mut len = 0;
+  for (i, c) in text.chars().enumerate() {
+    if !is_ignorable_char(c) {
+      cleaned.push(c);
+      char_map.push(i);
+    }
+    len = i + 1;
+  }
+  char_map.push(len);
+  (normalize_punctuation(&expand_odoriji(&cleaned)), char_map)
+}
+
+/// 取り除いた後のテキストでの位置を元のテキストでの位置に戻す
+/// 末尾の直後に取り除いた文字があっても範囲に含めない
+fn restore_position(char_map: &[usize], position: &Position) -> Position {
+  let start = char_map[position.start];
+  let end = if position.end > position.start {
+    char_map[position.end - 1] + 1
+  } else {
+    start
+  };
+  Position { start, end }
+}
+
+/// XMLのバイト列を解析し，そのまま参照情報を抽出する
+/// ファイルシステムや非同期ランタイムに依存しないため，WebAssemblyなどからも呼び出せる
+/// - law_map: 参照先として探す法令の一覧（`parse_ref`と同じ）
+pub fn analyze(
+  buf: &[u8],
+  date: Date,
+  law_name: Option<String>,
+  law_id: String,
+  patch_id: Option<String>,
+  law_map: &HashMap<String, Law>,
+  config: &ParseConfig,
+) -> Result<Vec<Find>> {
+  let parsed = egov_xml_parse(buf, date, law_name, law_id, patch_id)?;
+  Ok(parse_ref_with_config(&parsed.parts, law_map, config))
+}
+
+/// 項のテキストから探す法令名と，その法令・見つけ方
+type LawNamePattern<'a> = (String, &'a Law, MatchKind);
+
+/// `law_map`の法令名を，注記を除いて踊り字と句読点を揃えた検索用の形にして並べる
+/// 項ごとに作り直さないよう，`ReferenceContext`を作るときに一度だけ作る
+fn law_name_patterns(law_map: &HashMap<String, Law>) -> Vec<LawNamePattern<'_>> {
+  let mut patterns = law_map
+    .iter()
+    .map(|(k, v)| {
+      let kind = if *k == v.law_id_text {
+        MatchKind::LawNumber
+      } else {
+        MatchKind::FullName
+      };
+      (
+        normalize_punctuation(&expand_odoriji(strip_name_note(k))),
+        v,
+        kind,
+      )
+    })
+    .collect::<Vec<_>>();
+  // law_mapの走査順は不定なので，同じ範囲に一致する法令名の優先順位が実行ごとに変わらないよう並べておく
+  patterns.sort_by(|(a, law_a, _), (b, law_b, _)| {
+    (a, &law_a.law_id, &law_a.patch_id).cmp(&(b, &law_b.law_id, &law_b.patch_id))
+  });
+  patterns
+}
+
+/// 法令名の検索
+/// `law_names`は`law_name_patterns`で作ったもので，その後に`find_lst`の略称も探す
+fn find_law_name(
+  text: &str,
+  law_names: &[LawNamePattern],
+  find_lst: &[FindLawName],
+) -> Vec<FindLawName> {
+  let text_chars = text.chars().collect::<Vec<_>>();
+  let byte_to_char_map = byte_to_char_index_map(text);
+  let mut lst = Vec::new();
+  let abbreviations = find_lst
+    .iter()
+    .map(|v| {
+      (
+        v.match_string.clone(),
+        v.find_law.as_ref().unwrap(),
+        MatchKind::Abbreviation,
+      )
+    })
+    .collect::<Vec<_>>();
+  let rules = universal_name_rules();
+  for (find_law_name, law, kind) in law_names.iter().chain(abbreviations.iter()) {
+    for (start, s) in text.match_indices(find_law_name.as_str()) {
+      let end = start + s.len();
+      let start = byte_to_char_map[start];
+      let end = byte_to_char_map[end];
+      let match_text = s;
+      // 「法」・「令」のような短い法令名が，「方法」・「政令」のような普遍的な語の一部である場合や，
+      // 「〇〇年法律第〇〇号」のような法令番号の一部である場合は除く
+      let name_match = NameMatch {
+        name: find_law_name,
+        chars: &text_chars,
+        start,
+        end,
+      };
+      if let Some(rule) = rules.iter().find(|rule| (rule.reject)(&name_match)) {
+        trace!("skip universal name {find_law_name}: {}", rule.name);
+        continue;
+      }
+      let kind = if find_law_name.chars().count() == 1 {
+        MatchKind::SingleChar
+      } else {
+        *kind
+      };
+      let find = FindLawName {
+        position: Position { start, end },
+        match_string: match_text.to_string(),
+        find_law: Some((*law).clone()),
+        kind,
+      };
+      lst = resolve_duplicates(&lst, &find);
+    }
+  }
+  // 「内閣は、消防施設強化促進法（昭和二十八年法律第八十七号）第三条の規定に基き、この政令を制定する。」
+  // のような文における，法令番号の抽出を抑制したい．
+  // 具体的には，次のパターンに該当するかどうかをチェックする．
+  // - 同一の法令を指し示す法令名のendと法令番号のstartの差が1
+  // - 当該endと当該startの間の文字が'（'
+  // 該当したときに法令番号側を削除する
+  // law_mapの走査順は不定なので，隣接関係を見る前に出現位置で並べ替えておく
+  sort_by_position(&mut lst);
+  lst = resolve_name_and_number(&lst, text);
+
+  // 最終的な法令名探索結果
+  lst
+}
+
+/// テキスト中で見つかった法令名と，その前後の文字
+struct NameMatch<'a> {
+  name: &'a str,
+  chars: &'a [char],
+  start: usize,
+  end: usize,
+}
+
+impl NameMatch<'_> {
+  /// 法令名の`n`文字前の文字
+  fn before(&self, n: usize) -> Option<char> {
+    self.start.checked_sub(n).map(|i| self.chars[i])
+  }
+
+  /// 法令名の直後から`offset`文字目の文字
+  /// テキストの範囲外の場合は`None`を返す
+  fn after(&self, offset: usize) -> Option<char> {
+    self.chars.get(self.end + offset).copied()
+  }
+}
+
+/// 見つかった法令名を，普遍的な語の一部なので法令名ではないとして除く規則
+struct UniversalNameRule {
+  /// 規則の説明
+  name: &'static str,
+  /// 除く場合に`true`を返す
+  reject: fn(&NameMatch) -> bool,
+}
+
+/// `find_law_name`で使う，法令名ではないものを除く規則の一覧
+/// いずれかの規則が`true`を返したものを除く
+fn universal_name_rules() -> Vec<UniversalNameRule> {
+  vec![
+    UniversalNameRule {
+      name: "「同法」・「方法」・「旧法」・「本法」の「法」",
+      reject: |m| m.name == "法" && matches!(m.before(1), Some('方' | '同' | '旧' | '本')),
+    },
+    UniversalNameRule {
+      name: "「大日本帝国憲法」の中の「憲法」",
+      reject: |m| m.name == "憲法" && m.before(1) == Some('国'),
+    },
+    UniversalNameRule {
+      name: "「憲法審査会」・「憲法改正」のように後に語が続く「憲法」",
+      reject: |m| {
+        m.name == "憲法"
+          && m
+            .after(0)
+            .is_some_and(|c| ('\u{4e00}'..='\u{9fff}').contains(&c) && !matches!(c, '第' | '前'))
+      },
+    },
+    UniversalNameRule {
+      name: "「法人」",
+      reject: |m| m.name.ends_with('法') && m.after(0) == Some('人'),
+    },
+    UniversalNameRule {
+      name: "「法令」・「法律」の「法」",
+      reject: |m| m.name == "法" && matches!(m.after(0), Some('令' | '律')),
+    },
+    UniversalNameRule {
+      name: "「〇〇年法律第〇〇号」",
+      reject: |m| m.name.ends_with('法') && m.after(0) == Some('律') && m.after(1) == Some('第'),
+    },
+    UniversalNameRule {
+      name: "「命令」・「政令」・「同令」・「法令」・「省令」などの「令」",
+      reject: |m| {
+        m.name == "令"
+          && matches!(
+            m.before(1),
+            Some('命' | '政' | '同' | '法' | '省' | '府' | '勅' | '旧')
+          )
+      },
+    },
+    UniversalNameRule {
+      name: "「〇〇年〇〇省令第〇〇号」などの「令」",
+      reject: |m| {
+        m.name == "令"
+          && m.after(0) == Some('第')
+          && matches!(m.before(1), Some('省' | '政' | '勅' | '府'))
+      },
+    },
+    UniversalNameRule {
+      name: "「〇〇年〇〇委員会規則第〇〇号」・「〇〇年〇〇院規則第〇〇号」",
+      reject: |m| {
+        m.name.ends_with('則')
+          && m.after(0) == Some('第')
+          && m.before(1) == Some('規')
+          && matches!(m.before(2), Some('院' | '会'))
+      },
+    },
+    UniversalNameRule {
+      name: "鉤括弧で終わる語の一部",
+      reject: |m| m.after(0) == Some('」'),
+    },
+  ]
+}
+
+#[test]
+fn check_universal_name_rules() {
+  // 規則の説明，テキスト，法令名，除かれるか
+  let cases = [
+    (
+      "「同法」・「方法」・「旧法」・「本法」の「法」",
+      "同法第一条",
+      "法",
+      true,
+    ),
+    (
+      "「同法」・「方法」・「旧法」・「本法」の「法」",
+      "本法の施行",
+      "法",
+      true,
+    ),
+    (
+      "「同法」・「方法」・「旧法」・「本法」の「法」",
+      "、法第一条",
+      "法",
+      false,
+    ),
+    (
+      "「大日本帝国憲法」の中の「憲法」",
+      "大日本帝国憲法の",
+      "憲法",
+      true,
+    ),
+    (
+      "「大日本帝国憲法」の中の「憲法」",
+      "日本国の憲法の",
+      "憲法",
+      false,
+    ),
+    (
+      "「憲法審査会」・「憲法改正」のように後に語が続く「憲法」",
+      "憲法審査会は",
+      "憲法",
+      true,
+    ),
+    (
+      "「憲法審査会」・「憲法改正」のように後に語が続く「憲法」",
+      "憲法改正の",
+      "憲法",
+      true,
+    ),
+    (
+      "「憲法審査会」・「憲法改正」のように後に語が続く「憲法」",
+      "憲法第九条",
+      "憲法",
+      false,
+    ),
+    (
+      "「憲法審査会」・「憲法改正」のように後に語が続く「憲法」",
+      "憲法前文",
+      "憲法",
+      false,
+    ),
+    ("「法人」", "民法人の", "民法", true),
+    ("「法人」", "民法の", "民法", false),
+    ("「法令」・「法律」の「法」", "法令の", "法", true),
+    ("「法令」・「法律」の「法」", "法の", "法", false),
+    (
+      "「〇〇年法律第〇〇号」",
+      "昭和二十二年法律第六十七号",
+      "法",
+      true,
+    ),
+    ("「〇〇年法律第〇〇号」", "この法律の施行", "法", false),
+    (
+      "「命令」・「政令」・「同令」・「法令」・「省令」などの「令」",
+      "政令の",
+      "令",
+      true,
+    ),
+    (
+      "「命令」・「政令」・「同令」・「法令」・「省令」などの「令」",
+      "、令の",
+      "令",
+      false,
+    ),
+    (
+      "「〇〇年〇〇省令第〇〇号」などの「令」",
+      "省令第一号",
+      "令",
+      true,
+    ),
+    (
+      "「〇〇年〇〇省令第〇〇号」などの「令」",
+      "、令第一号",
+      "令",
+      false,
+    ),
+    (
+      "「〇〇年〇〇委員会規則第〇〇号」・「〇〇年〇〇院規則第〇〇号」",
+      "委員会規則第一号",
+      "則",
+      true,
+    ),
+    (
+      "「〇〇年〇〇委員会規則第〇〇号」・「〇〇年〇〇院規則第〇〇号」",
+      "委員会規則の",
+      "則",
+      false,
+    ),
+    ("鉤括弧で終わる語の一部", "「民法」という", "民法", true),
+    // テキストの最後の文字も見る（別表の欄のように鉤括弧で終わる項）
+    ("鉤括弧で終わる語の一部", "「旧民法」", "民法", true),
+    ("鉤括弧で終わる語の一部", "「民法の", "民法", false),
+  ];
+  let rules = universal_name_rules();
+  for rule in rules.iter() {
+    assert!(
+      cases
+        .iter()
+        .any(|(name, _, _, reject)| *name == rule.name && *reject),
+      "no test for rule: {}",
+      rule.name
+    );
+  }
+  for (rule_name, text, name, expected) in cases {
+    let rule = rules.iter().find(|r| r.name == rule_name).unwrap();
+    let chars = text.chars().collect::<Vec<_>>();
+    let start = text[..text.find(name).unwrap()].chars().count();
+    let name_match = NameMatch {
+      name,
+      chars: &chars,
+      start,
+      end: start + name.chars().count(),
+    };
+    assert_eq!((rule.reject)(&name_match), expected, "{rule_name}: {text}");
+  }
+}
+
+/// 「平成十年政令第五号」のような法令番号の出現位置を探し，法令番号の一覧から法令を引く
+/// 一覧に無い法令番号は`find_law`を`None`にして返す
+fn find_law_number(
+  text: &str,
+  law_number_index: &HashMap<&str, &Law>,
+  regexes: &KeywordRegexes,
+) -> Vec<FindLawName> {
+  let byte_to_char_map = byte_to_char_index_map(text);
+  let mut lst = Vec::new();
+  for m in regexes.law_number.find_iter(text) {
+    lst.push(FindLawName {
+      position: Position {
+        start: byte_to_char_map[m.start()],
+        end: byte_to_char_map[m.end()],
+      },
+      find_law: law_number_index.get(m.as_str()).map(|l| (*l).clone()),
+      match_string: m.as_str().to_string(),
+      kind: MatchKind::LawNumber,
+    });
+  }
+  lst
+}
+
+/// 法令名を伴わない「第○条」の出現位置を探す
+/// 返す位置は「第」の直前を指す幅0の範囲で，`find_joukou`にそのまま渡せる
+/// 既に見つかった参照の範囲の中にあるものや，「附則第○条」のように附則を指すものは除く
+fn find_same_law_article(text: &str, found_positions: &[Position]) -> Vec<Position> {
+  static ARTICLE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"第[一二三四五六七八九十百千]+条").unwrap());
+  let byte_to_char_map = byte_to_char_index_map(text);
+  let mut lst = Vec::new();
+  for m in ARTICLE_RE.find_iter(text) {
+    let start = byte_to_char_map[m.start()];
+    if text[..m.start()].ends_with("附則")
+      || found_positions
+        .iter()
+        .any(|p| p.start <= start && start < p.end)
+    {
+      continue;
+    }
+    lst.push(Position { start, end: start });
+  }
+  lst
+}
+
+/// 条番号を伴わない「第○項」の出現位置を探す
+/// 返す位置は`find_same_law_article`と同じく「第」の直前を指す幅0の範囲
+/// 「第三条第二項」・「前条第二項」・「同条第二項」のように直前に条や号などがあるものや，
+/// 「附則第二項」，既に見つかった参照の範囲の中にあるものは除く
+/// 「第三条第一項及び第二項」の「第二項」のように，条番号を伴う項に並べて書かれたものも除く
+fn find_same_article_paragraph(text: &str, found_positions: &[Position]) -> Vec<Position> {
+  static PARAGRAPH_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"第[一二三四五六七八九十百千]+項").unwrap());
+  // 並べて書かれた項の間に入る語
+  static CONNECTOR_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+      r"^(及び|並びに|又は|若しくは|から|、|，|前段|後段|本文|ただし書|各号|第[一二三四五六七八九十百千]+号)*$",
+    )
+    .unwrap()
+  });
+  let byte_to_char_map = byte_to_char_index_map(text);
+  let mut lst = Vec::new();
+  // 直前の「第○項」の終わりと，それが他の条の項だったか
+  let mut last: Option<(usize, bool)> = None;
+  for m in PARAGRAPH_RE.find_iter(text) {
+    let start = byte_to_char_map[m.start()];
+    let before = &text[..m.start()];
+    let other_article = if before
+      .ends_with(|c: char| "編章節款目条項号のノ前次同一二三四五六七八九十百千".contains(c))
+      || before.ends_with("附則")
+    {
+      true
+    } else {
+      match last {
+        Some((last_end, other)) if CONNECTOR_RE.is_match(&text[last_end..m.start()]) => other,
+        _ => false,
+      }
+    };
+    last = Some((m.end(), other_article));
+    if other_article
+      || found_positions
+        .iter()
+        .any(|p| p.start <= start && start < p.end)
+    {
+      continue;
+    }
+    lst.push(Position { start, end: start });
+  }
+  lst
+}
+
+/// 法令名を伴わない「別表」の出現位置を探す
+/// 返す位置は`find_same_law_article`と同じく「別」の直前を指す幅0の範囲
+/// 既に見つかった参照の範囲の中にあるものや，「附則別表」は除く
+fn find_same_law_appdx_table(text: &str, found_positions: &[Position]) -> Vec<Position> {
+  let byte_to_char_map = byte_to_char_index_map(text);
+  let mut lst = Vec::new();
+  for (byte_start, _) in text.match_indices("別表") {
+    let start = byte_to_char_map[byte_start];
+    if text[..byte_start].ends_with("附則")
+      || found_positions
+        .iter()
+        .any(|p| p.start <= start && start < p.end)
+    {
+      continue;
+    }
+    lst.push(Position { start, end: start });
+  }
+  lst
+}
+
+/// 改正文の中で，改正される法令の文言を引用している「」の範囲を探す
+/// 「改める」などの語が無い項は改正文ではないとみなし，何も返さない
+/// 「」は入れ子になることがあるので，一番外側の範囲だけを返す
+fn find_amendment_quotes(text: &str, regexes: &KeywordRegexes) -> Vec<Position> {
+  let mut lst = Vec::new();
+  if !regexes.amendment.as_ref().is_some_and(|r| r.is_match(text)) {
+    return lst;
+  }
+  let mut depth = 0_usize;
+  let mut start = 0;
+  for (i, c) in text.chars().enumerate() {
+    if c == '「' {
+      if depth == 0 {
+        start = i;
+      }
+      depth += 1;
+    } else if c == '」' && depth > 0 {
+      depth -= 1;
+      if depth == 0 {
+        lst.push(Position { start, end: i + 1 });
+      }
+    }
+  }
+  lst
+}
+
+/// 「最高裁判所平成二十年六月四日大法廷判決」・「東京高判令和元年五月一日」のような判例の引用を探す
+/// 裁判所と和暦の日付が続けて書かれているものだけを対象にし，位置と裁判所と日付のテキストを返す
+fn find_case_law(text: &str) -> Vec<(Position, String, String)> {
+  static CASE_LAW_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(concat!(
+      r"(?<court>最高裁判所(?:大法廷|第[一二三]小法廷)?|大審院|最[大一二三]?[判決]|大[判決]",
+      r"|\p{Han}{1,4}?(?:高等裁判所|地方裁判所|家庭裁判所|簡易裁判所|[高地家簡][判決]))",
+      r"\s?(?<date>(?:明治|大正|昭和|平成|令和)(?:元|[一二三四五六七八九十百]+)年",
+      r"[一二三四五六七八九十]+月[一二三四五六七八九十]+日)",
+      r"(?:(?:大法廷|第[一二三]小法廷)?(?:判決|決定))?",
+    ))
+    .unwrap()
+  });
+  let byte_to_char_map = byte_to_char_index_map(text);
+  CASE_LAW_RE
+    .captures_iter(text)
+    .map(|caps| {
+      let m = caps.get(0).unwrap();
+      let position = Position {
+        start: byte_to_char_map[m.start()],
+        end: byte_to_char_map[m.end()],
+      };
+      (
+        position,
+        caps["court"].to_string(),
+        caps["date"].to_string(),
+      )
+    })
+    .collect()
+}
+
+/// 「同条」・「同項」・「同号」の出現位置を探す
+/// 既に見つかった参照の範囲の中にあるものは除く
+fn find_doujou(text: &str, found_positions: &[Position]) -> Vec<FindLawName> {
+  static DOUJOU_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new("同[条項号]").unwrap());
+  let byte_to_char_map = byte_to_char_index_map(text);
+  let mut lst = Vec::new();
+  for m in DOUJOU_RE.find_iter(text) {
+    let start = byte_to_char_map[m.start()];
+    let end = byte_to_char_map[m.end()];
+    if found_positions
+      .iter()
+      .any(|p| p.start <= start && start < p.end)
+    {
+      continue;
+    }
+    lst.push(FindLawName {
+      position: Position { start, end },
+      find_law: None,
+      match_string: m.as_str().to_string(),
+      kind: MatchKind::Doujou,
+    });
+  }
+  lst
+}
+
+/// 条まで解決できた参照を，「同条」などの紐付け先として追加する
+fn push_locator(locators: &mut Vec<FindLawName>, start: usize, end: usize, law: &Law) {
+  if law.article_number.is_some() {
+    locators.push(FindLawName {
+      position: Position { start, end },
+      find_law: Some(law.clone()),
+      match_string: String::new(),
+      kind: MatchKind::Doujou,
+    });
+  }
+}
+
+/// 検索結果を出現位置（start，endの順）で並べ替える
+fn sort_by_position(lst: &mut [FindLawName]) {
+  lst.sort_by_key(|f| (f.position.start, f.position.end));
+}
+
+/// 「内閣は、消防施設強化促進法（昭和二十八年法律第八十七号）第三条の規定に基き、この政令を制定する。」
+/// のような文における，法令名と法令番号の重複を解消するために，法令番号を削除する．
+/// 入力は出現位置で並べ替えられていることを期待する
+fn resolve_name_and_number(lst: &[FindLawName], text: &str) -> Vec<FindLawName> {
+  let chars = text.chars().collect::<Vec<char>>();
+  // 削除対象
+  let mut remove_index: Vec<usize> = Vec::new();
+  let mut iter = lst.iter().enumerate().peekable();
+  while let Some((i, law_name)) = iter.next() {
+    if let Some(&(j, next_law_name)) = iter.peek() {
+      // 順序の保証がないため，どっちであっても対応できるようにする
+      let (i2, law1, law2) = if law_name.position.end < next_law_name.position.start {
+        (j, law_name, next_law_name)
+      } else {
+        (i, next_law_name, law_name)
+      };
+      if law1.position.end.abs_diff(law2.position.start) == 1 // 差が1
+        && chars.get(law1.position.end) == Some(&'（') // 間にある文字が全角かっこ
+        && law1.find_law.clone().map(|l| l.get_law_id())
+        == law2.find_law.clone().map(|l| l.get_law_id())
+        && !law1.match_string.ends_with("号") // 前側が号で終わらず
+        && law2.match_string.ends_with("号")
+      // 後ろ側が号で終わる
+      {
+        // 削除対象に登録
+        remove_index.push(i2)
+      }
+    }
+  }
+  lst
+    .iter()
+    .enumerate()
+    .filter(|(i, _)| !remove_index.contains(i))
+    .map(|(_, v)| v.clone())
+    .collect::<Vec<_>>()
+}
+
+#[test]
+fn check_resolve_name_and_number() {
+  let s = "陸上交通事業調整法（以下「法」という。）第二条第一項の政令で定める審議会等は、交通政策審議会とする。ただし、法第二条第一項の規定に基づき、国土交通大臣が都市計画法（昭和四十三年法律第百号）第四条第二項に規定する都市計画区域内において調整の区域を決定しようとするときは、当該調整の区域について交通政策審議会及び社会資本整備審議会とする。";
+  let v = vec![
+    FindLawName {
+      position: Position { start: 0, end: 25 },
+      match_string: String::from("陸上交通事業調整法"),
+      kind: MatchKind::FullName,
+      find_law: Some(Law::new(
+        Date::new_ad(2000, 1, 1),
+        Some(String::from("陸上交通事業調整法")),
+        String::from("313AC0000000071"),
+        String::from("昭和十三年法律第七十一号"),
+        LawType::Act,
+      )),
+    },
+    FindLawName {
+      position: Position { start: 82, end: 93 },
+      match_string: String::from("昭和四十三年法律第百号"),
+      kind: MatchKind::FullName,
+      find_law: Some(Law::new(
+        Date::new_ad(2000, 1, 1),
+        Some(String::from("都市計画法")),
+        String::from("343AC0000000100"),
+        String::from("昭和四十三年法律第百号"),
+        LawType::Act,
+      )),
+    },
+    FindLawName {
+      position: Position { start: 76, end: 81 },
+      match_string: String::from("都市計画法"),
+      kind: MatchKind::FullName,
+      find_law: Some(Law::new(
+        Date::new_ad(2000, 1, 1),
+        Some(String::from("都市計画法")),
+        String::from("343AC0000000100"),
+        String::from("昭和四十三年法律第百号"),
+        LawType::Act,
+      )),
+    },
+    FindLawName {
+      position: Position { start: 13, end: 14 },
+      match_string: String::from("法"),
+      kind: MatchKind::FullName,
+      find_law: Some(Law::new(
+        Date::new_ad(2000, 1, 1),
+        Some(String::from("陸上交通事業調整法")),
+        String::from("313AC0000000071"),
+        String::from("昭和十三年法律第七十一号"),
+        LawType::Act,
+      )),
+    },
+  ];
+
+  let v2 = resolve_name_and_number(&v, s);
+
+  let v3 = vec![
+    FindLawName {
+      position: Position { start: 0, end: 25 },
+      match_string: String::from("陸上交通事業調整法"),
+      kind: MatchKind::FullName,
+      find_law: Some(Law::new(
+        Date::new_ad(2000, 1, 1),
+        Some(String::from("陸上交通事業調整法")),
+        String::from("313AC0000000071"),
+        String::from("昭和十三年法律第七十一号"),
+        LawType::Act,
+      )),
+    },
+    FindLawName {
+      position: Position { start: 76, end: 81 },
+      match_string: String::from("都市計画法"),
+      kind: MatchKind::FullName,
+      find_law: Some(Law::new(
+        Date::new_ad(2000, 1, 1),
+        Some(String::from("都市計画法")),
+        String::from("343AC0000000100"),
+        String::from("昭和四十三年法律第百号"),
+        LawType::Act,
+      )),
+    },
+    FindLawName {
+      position: Position { start: 13, end: 14 },
+      match_string: String::from("法"),
+      kind: MatchKind::FullName,
+      find_law: Some(Law::new(
+        Date::new_ad(2000, 1, 1),
+        Some(String::from("陸上交通事業調整法")),
+        String::from("313AC0000000071"),
+        String::from("昭和十三年法律第七十一号"),
+        LawType::Act,
+      )),
+    },
+  ];
+  assert_eq!(v2, v3)
+}
+
+#[test]
+fn check_find_law_name_sorted() {
+  let s = "内閣は、消防施設強化促進法（昭和二十八年法律第八十七号）第三条の規定に基き、この政令を制定する。";
+  let law = Law::new(
+    Date::new_ad(2000, 1, 1),
+    Some(String::from("消防施設強化促進法")),
+    String::from("328AC0000000087"),
+    String::from("昭和二十八年法律第八十七号"),
+    LawType::Act,
+  );
+  let mut law_map = HashMap::new();
+  law_map.insert(String::from("昭和二十八年法律第八十七号"), law.clone());
+  law_map.insert(String::from("消防施設強化促進法"), law.clone());
+  law_map.insert(String::from("政令"), law.clone());
+  law_map.insert(String::from("内閣"), law.clone());
+  let v = find_law_name(s, &law_name_patterns(&law_map), &[]);
+  let positions = v.iter().map(|f| f.position.start).collect::<Vec<_>>();
+  let mut sorted = positions.clone();
+  sorted.sort();
+  assert_eq!(positions, sorted);
+  assert!(v.iter().all(|f| !f.match_string.ends_with('号')));
+  assert_eq!(v.len(), 3);
+}
+
+// 略称の定義を検索
+fn find_abb_def(text: &str, regexes: &KeywordRegexes) -> Vec<FindLawName> {
+  let byte_to_char_map = byte_to_char_index_map(text);
+  let mut lst = Vec::new();
+  for caps in regexes.abb_def.iter().flat_map(|r| r.captures_iter(text)) {
+    // 鉤括弧の中身だけを取り出す
+    let m = caps.name("abb").unwrap();
+    // charでのインデックスにマップする
+    let start = byte_to_char_map[m.start()];
+    let end = byte_to_char_map[m.end()];
+    let abb = m.as_str().to_string();
+    // 方法や命令は今回の対象ではないので除く
+    if !regexes
+      .abb_exclude_suffix
+      .iter()
+      .any(|suffix| abb.ends_with(suffix.as_str()))
+    {
+      let kind = if abb.chars().count() == 1 {
+        MatchKind::SingleChar
+      } else {
+        MatchKind::Abbreviation
+      };
+      let find = FindLawName {
+        position: Position { start, end },
+        match_string: abb,
+        find_law: None,
+        kind,
+      };
+      lst = resolve_duplicates(&lst, &find);
+    }
+  }
+  lst
+}
+
+// 「同法」と「同令」で再度検索する
+fn find_douhou(text: &str, regexes: &KeywordRegexes) -> Vec<FindLawName> {
+  let text_chars = text.chars().collect::<Vec<char>>();
+  let byte_to_char_map = byte_to_char_index_map(text);
+  let mut lst = Vec::new();
+  for m in regexes.douhou.iter().flat_map(|r| r.find_iter(text)) {
+    let start = byte_to_char_map[m.start()];
+    let end = byte_to_char_map[m.end()];
+    let match_text = m.as_str();
+    let after = |offset: usize| text_chars.get(end + offset).copied();
+    if !(after(0) == Some('人') || (after(0) == Some('律') && after(1) == Some('第'))) {
+      let find = FindLawName {
+        position: Position { start, end },
+        match_string: match_text.to_string(),
+        find_law: None,
+        kind: MatchKind::Douhou,
+      };
+      lst.push(find);
+    }
+  }
+  lst
+}
+
+/// 「この法律」・「この政令」・「本法」のような，その法令自身を指す語の出現位置を探す
+/// 紐付け先の法令は呼び出し側で決める
+fn find_self_reference(text: &str, regexes: &KeywordRegexes) -> Vec<FindLawName> {
+  let byte_to_char_map = byte_to_char_index_map(text);
+  regexes
+    .self_reference
+    .iter()
+    .flat_map(|r| r.find_iter(text))
+    .map(|m| FindLawName {
+      position: Position {
+        start: byte_to_char_map[m.start()],
+        end: byte_to_char_map[m.end()],
+      },
+      match_string: m.as_str().to_string(),
+      find_law: None,
+      kind: MatchKind::SelfReference,
+    })
+    .collect()
+}
+
+#[test]
+fn check_self_reference() {
+  let root = Law::new(
+    Date::new_ad(2025, 11, 26),
+    Some(String::from("テスト法")),
+    String::from("507AC0000000001"),
+    String::from("令和七年法律第一号"),
+    LawType::Act,
+  );
+  let amend_law = Law::new(
+    Date::new_ad(2025, 11, 26),
+    Some(String::from("テスト法の一部を改正する法律")),
+    String::from("507AC0000000002"),
+    String::from("令和七年法律第二号"),
+    LawType::Act,
+  );
+  let mut law_map = HashMap::new();
+  law_map.insert(String::from("令和七年法律第二号"), amend_law);
+  let paragraph = |suppl_provision: Option<&str>, text: &str| {
+    let mut paragraph = root.clone();
+    if let Some(amend_law_num) = suppl_provision {
+      paragraph.set_suppl_provision(amend_law_num.to_string());
+    }
+    paragraph.article_number = parse_article_number("第一条");
+    paragraph.set_paragraph_number(parse_article_number("第一項").unwrap());
+    paragraph.set_paragraph_text(text.to_string());
+    paragraph
+  };
+  let parse = |paragraph: Law| {
+    let target = HashMap::from([
+      (String::new(), root.clone()),
+      (String::from("1"), paragraph),
+    ]);
+    parse_ref(&target, &law_map)
+  };
+  let finds = parse(paragraph(None, "この法律第三条及び本法第四条の規定により"));
+  assert_eq!(
+    finds
+      .iter()
+      .map(|f| (f.is_self_reference(), f.get_kind(), f.to.citation_text()))
+      .collect::<Vec<_>>(),
+    vec![
+      (
+        true,
+        MatchKind::SelfReference,
+        String::from("テスト法第三条")
+      ),
+      (
+        true,
+        MatchKind::SelfReference,
+        String::from("テスト法第四条")
+      ),
+    ]
+  );
+  // 改正法令の附則の「この法律」は改正法令を指す
+  let finds = parse(paragraph(
+    Some("令和七年法律第二号"),
+    "この法律は、公布の日から施行する。",
+  ));
+  assert_eq!(finds.len(), 1);
+  assert_eq!(finds[0].to.get_law_id(), "507AC0000000002");
+  assert!(!finds[0].is_self_reference());
+}
+
+/// 「第一の二」のような別表の番号を`1_2`の形式にする
+/// 番号が無い場合は空文字列を返す
+fn parse_appdx_table_number(s: &str) -> Option<String> {
+  if s.is_empty() {
+    return Some(String::new());
+  }
+  // 「第一条の二」の形にして条番号として読む
+  let (first, rest) = s.split_at(s.find('の').unwrap_or(s.len()));
+  parse_article_number(&format!("{first}条{rest}")).map(|num| num.num_str())
+}
+
+/// 「第九十条第一項、第二項」・「第九十条及び第九十一条」のように，`end`で終わる条項番号に並べて書かれた条項番号の始まりを探す
+/// 読点や「及び」などの直後に「第」が続く場合だけ，その「第」の位置を返す
+fn find_listed_locator(text: &str, end: usize) -> Option<usize> {
+  let rest = text.chars().skip(end).collect::<String>();
+  ["、", "及び", "並びに", "又は", "若しくは"]
+    .iter()
+    .find(|connector| rest.starts_with(&format!("{connector}第")))
+    .map(|connector| end + connector.chars().count())
+}
+
+/// 条項番号を検索する
+/// 法令名の後の括弧がきを飛ばし，その後に「第一条」のような文字列が出るのを期待する
+/// "第"が出なかったら法令名だけなので処理を打ち切り
+/// 読み飛ばす括弧は`parens`の組で，入れ子になっていてもよい
+/// 返り値は最終的な範囲のend（その位置の文字は含まない）と読み取った条項番号
+/// 条項番号は`Law::with_locator`で参照先の法令に当てはめる
+/// 「第」で始まるのに番号として読めなかった文字列はunparsedに追加する
+fn find_joukou(
+  text: &str,
+  position: &Position,
+  parens: &[(char, char)],
+  unparsed: &mut Vec<String>,
+) -> (usize, JoukouRef) {
+  let mut law = JoukouRef::default();
+  let mut s = String::new();
+  // 開いている括弧に対応する閉じ括弧
+  let mut closers: Vec<char> = Vec::new();
+  let target_c = [
+    '第', '編', '章', '節', '款', '目', '条', '項', '号', 'の', 'ノ', '一', '二', '三', '四', '五',
+    '六', '七', '八', '九', '十', '百', '千',
+  ];
+  let separators = [' ', '　', '、', '，'];
+  let text_chars = text.chars().collect::<Vec<_>>();
+  let mut end = position.end;
+  for (i, &c) in text_chars.iter().enumerate() {
+    if i < position.end || i < end {
+      continue;
+    }
+    // 「附則第二条」・「附則」のように附則を指す場合
+    if closers.is_empty()
+      && s.is_empty()
+      && !law.suppl_provision
+      && c == '附'
+      && text_chars.get(i + 1) == Some(&'則')
+    {
+      law.suppl_provision = true;
+      end = i + 2;
+      continue;
+    }
+    // 「別表第一」のように別表を指す場合
+    if closers.is_empty() && s.is_empty() && c == '別' && text_chars.get(i + 1) == Some(&'表') {
+      s.push_str("別表");
+      end = i + 2;
+      continue;
+    }
+    // 「民法 第三条」・「民法、第三条」のように法令名と条項番号の間に空白や読点がある場合は，
+    // その後に「第」が続くときだけ読み飛ばす
+    if closers.is_empty() && s.is_empty() && separators.contains(&c) {
+      if text_chars[i..].iter().find(|c| !separators.contains(c)) == Some(&'第') {
+        end = i + 1;
+        continue;
+      }
+      break;
+    }
+    if let Some(&(_, close)) = parens.iter().find(|(open, _)| *open == c) {
+      closers.push(close);
+      continue;
+    }
+    if parens.iter().any(|(_, close)| *close == c) {
+      // 法令名を囲む括弧の閉じ括弧の場合は，そこで参照が終わる
+      if closers.is_empty() {
+        break;
+      }
+      // 対応の取れない閉じ括弧は，それに対応する開き括弧まで閉じる
+      // 開いていない種類の閉じ括弧は無視する
+      if let Some(depth) = closers.iter().rposition(|close| *close == c) {
+        closers.truncate(depth);
+      }
+      continue;
+    }
+    if !closers.is_empty() {
+      continue;
+    }
+    if target_c.contains(&c) {
+      s.push(c);
+      end = i + 1;
+      continue;
+    }
+    break;
+  }
+  // 末尾が'の', 'ノ'ならば，枝番号の途中で括弧や文の終わりが来たものなのでそれを取り除く
+  // 「第三条の二」のように後ろに数字が続く'の'は枝番号の一部なので残す
+  if let Some(trimmed) = s.strip_suffix(['の', 'ノ']) {
+    s = trimmed.to_string();
+    end -= 1;
+  }
+  // 「○○法の一部を」の「の一」のように，「第」を含まない場合は条項番号ではない
+  if !s.is_empty() && !s.starts_with("別表") && !s.contains('第') {
+    return (position.end, JoukouRef::default());
+  }
+  trace!("find joukou number string: {s}");
+  if let Some(num) = s.strip_prefix("別表") {
+    match parse_appdx_table_number(num) {
+      Some(num) => law.appdx_table = Some(num),
+      None => unparsed.push(s.clone()),
+    }
+    return (end, law);
+  }
+  for (i, a) in s.split("第").enumerate() {
+    if !a.is_empty() {
+      let s2 = format!("第{a}");
+      trace!("find joukou number string(split): {s2}");
+      let num = parse_article_number(&s2);
+      trace!("parsed article number: {num:?}");
+      // 最初の「第」より前の部分は条項番号ではないので記録しない
+      if num.is_none() && i > 0 {
+        unparsed.push(s2.clone());
+      }
+      // 「第三条の二」のように枝番号が付く場合は末尾ではなく最後の単位の文字で判断する
+      let unit = a.chars().rev().find(|c| "編章節款目条項号".contains(*c));
+      if let Some(num) = num {
+        match unit {
+          Some('条') => law.article_number = Some(num),
+          Some('項') => law.paragraph_number = Some(num),
+          Some('号') => law.item_number = Some(num),
+          Some('編') => law.part_number = Some(num),
+          Some('章') => law.chapter_number = Some(num),
+          Some('節') => law.section_number = Some(num),
+          Some('款') => law.subsection_number = Some(num),
+          Some('目') => law.division_number = Some(num),
+          _ => (),
+        }
+      }
+    }
+  }
+  (end, law)
+}
+
+#[test]
+fn check_find_joukou() {
+  let position = Position { start: 0, end: 2 };
+  let parens = Keywords::default().parens;
+  // 括弧の前で終わる'の'は取り除く
+  let mut unparsed = Vec::new();
+  let (end, joukou) = find_joukou("民法第三条の（括弧）", &position, &parens, &mut unparsed);
+  assert_eq!(end, 5);
+  assert_eq!(joukou.article_number, parse_article_number("第三条"));
+  assert!(unparsed.is_empty());
+  // 文の終わりで終わる'の'も取り除く
+  let (end, joukou) = find_joukou("民法第三条の", &position, &parens, &mut unparsed);
+  assert_eq!(end, 5);
+  assert_eq!(joukou.article_number, parse_article_number("第三条"));
+  // 枝番号の'の'は残す
+  let (end, joukou) = find_joukou("民法第三条の二の規定", &position, &parens, &mut unparsed);
+  assert_eq!(end, 7);
+  assert_eq!(joukou.article_number, parse_article_number("第三条の二"));
+  assert!(unparsed.is_empty());
+  // 法令名と条項番号の間の空白・読点・'の'は読み飛ばし，続けて書いた場合と同じにする
+  for (text, len) in [
+    ("民法第三条第二項の規定", 8),
+    ("民法 第三条第二項の規定", 9),
+    ("民法　第三条第二項の規定", 9),
+    ("民法、第三条第二項の規定", 9),
+    ("民法の第三条第二項の規定", 9),
+  ] {
+    let (end, joukou) = find_joukou(text, &position, &parens, &mut unparsed);
+    assert_eq!(end, len, "{text}");
+    assert_eq!(joukou.article_number, parse_article_number("第三条"));
+    assert_eq!(joukou.paragraph_number, parse_article_number("第二項"));
+  }
+  assert!(unparsed.is_empty());
+  // 「第」が続かない場合は読み飛ばさない
+  let (end, joukou) = find_joukou("民法、刑法第三条", &position, &parens, &mut unparsed);
+  assert_eq!(end, 2);
+  assert_eq!(joukou.article_number, None);
+  // 枝番号が続く条と，枝番号の条の後の項
+  let (end, joukou) = find_joukou(
+    "民法第三条の二の三の規定",
+    &position,
+    &parens,
+    &mut unparsed,
+  );
+  assert_eq!(end, 9);
+  let num = joukou.article_number.clone().unwrap();
+  assert_eq!((num.base_number, num.eda_numbers), (3, vec![2, 3]));
+  assert_eq!(joukou.paragraph_number, None);
+  let (end, joukou) = find_joukou(
+    "民法第三条の二第一項第二号の規定",
+    &position,
+    &parens,
+    &mut unparsed,
+  );
+  assert_eq!(end, 13);
+  assert_eq!(joukou.article_number, parse_article_number("第三条の二"));
+  assert_eq!(joukou.paragraph_number, parse_article_number("第一項"));
+  assert_eq!(joukou.item_number, parse_article_number("第二号"));
+  let (_, joukou) = find_joukou(
+    "民法第三条の二の三第一項",
+    &position,
+    &parens,
+    &mut unparsed,
+  );
+  assert_eq!(
+    joukou.article_number,
+    parse_article_number("第三条の二の三")
+  );
+  assert_eq!(joukou.paragraph_number, parse_article_number("第一項"));
+  assert!(unparsed.is_empty());
+  // 半角の括弧や【】で囲んだ法令番号の注記は，設定した場合だけ読み飛ばす
+  let text = "民法(明治二十九年法律第八十九号)第九十条";
+  let (end, joukou) = find_joukou(text, &position, &parens, &mut unparsed);
+  assert_eq!((end, joukou.article_number), (2, None));
+  let mixed = [('（', '）'), ('(', ')'), ('【', '】')];
+  let (end, joukou) = find_joukou(text, &position, &mixed, &mut unparsed);
+  assert_eq!(end, text.chars().count());
+  assert_eq!(joukou.article_number, parse_article_number("第九十条"));
+  let text = "民法【明治二十九年法律第八十九号】第九十条";
+  let (_, joukou) = find_joukou(text, &position, &mixed, &mut unparsed);
+  assert_eq!(joukou.article_number, parse_article_number("第九十条"));
+  // 対応の取れない括弧は，外側の閉じ括弧でまとめて閉じ，開いていない種類の閉じ括弧は無視する
+  let text = "民法（明治二十九年(注】法律第八十九号）第九十条";
+  let (_, joukou) = find_joukou(text, &position, &mixed, &mut unparsed);
+  assert_eq!(joukou.article_number, parse_article_number("第九十条"));
+  // 開いていない閉じ括弧は参照の終わり
+  let (end, _) = find_joukou("民法)第九十条", &position, &mixed, &mut unparsed);
+  assert_eq!(end, 2);
+  // 附則を指す場合
+  let (end, joukou) = find_joukou("民法附則第二条の規定", &position, &parens, &mut unparsed);
+  assert_eq!(end, 7);
+  assert!(joukou.suppl_provision);
+  assert_eq!(joukou.article_number, parse_article_number("第二条"));
+  let (end, joukou) = find_joukou("民法附則の規定", &position, &parens, &mut unparsed);
+  assert_eq!(end, 4);
+  assert_eq!(
+    joukou,
+    JoukouRef {
+      suppl_provision: true,
+      ..Default::default()
+    }
+  );
+  // 「附則の一部」の「の一」は条項番号ではないので，附則も含めない
+  let (end, joukou) = find_joukou("民法附則の一部を", &position, &parens, &mut unparsed);
+  assert_eq!((end, joukou), (2, JoukouRef::default()));
+  assert!(unparsed.is_empty());
+}
+
+#[test]
+fn check_suppl_provision_ref() {
+  let minpou = minpou(Date::new_ad(2025, 11, 26));
+  let mut law_map = HashMap::new();
+  law_map.insert(String::from("民法"), minpou);
+  let finds = refs(
+    "民法附則第二条の規定は、同法附則に定めるもののほか、民法第三条の場合に適用する。",
+    &law_map,
+  );
+  let to = finds
+    .iter()
+    .map(|f| (f.to.law_id.as_str(), f.to.number_text(), f.position))
+    .collect::<Vec<_>>();
+  assert_eq!(
+    to,
+    vec![
+      (
+        "129AC0000000089",
+        String::from("附則第二条"),
+        Position { start: 0, end: 7 }
+      ),
+      (
+        "129AC0000000089",
+        String::from("附則"),
+        Position { start: 12, end: 16 }
+      ),
+      (
+        "129AC0000000089",
+        String::from("第三条"),
+        Position { start: 26, end: 31 }
+      ),
+    ]
+  );
+  assert!(eli::Eli::eli_uri(&finds[0].to).ends_with("/suppl_provision/article2"));
+}
+
+#[test]
+fn check_with_locator() {
+  let mut law = Law::new(
+    Date::new_ad(2025, 11, 26),
+    Some(String::from("民法")),
+    String::from("129AC0000000089"),
+    String::new(),
+    LawType::Act,
+  );
+  // 何も書かれていなければそのまま
+  assert_eq!(law.with_locator(JoukouRef::default()), law);
+  law.chapter_number = parse_article_number("第一章");
+  law.article_number = parse_article_number("第三条");
+  law.paragraph_number = parse_article_number("第二項");
+  law.item_number = parse_article_number("第一号");
+  // 項から下を置き換え，書かれていない号は消す
+  let paragraph = law.with_locator(JoukouRef {
+    paragraph_number: parse_article_number("第三項"),
+    ..Default::default()
+  });
+  assert_eq!(paragraph.chapter_number, parse_article_number("第一章"));
+  assert_eq!(paragraph.article_number, parse_article_number("第三条"));
+  assert_eq!(paragraph.paragraph_number, parse_article_number("第三項"));
+  assert_eq!(paragraph.item_number, None);
+  // 条を指定すれば元の項と号は残らない
+  let (_, joukou) = find_joukou(
+    "民法第五条",
+    &Position { start: 0, end: 2 },
+    &Keywords::default().parens,
+    &mut Vec::new(),
+  );
+  let article = law.with_locator(joukou);
+  assert_eq!(article.chapter_number, parse_article_number("第一章"));
+  assert_eq!(article.article_number, parse_article_number("第五条"));
+  assert_eq!(article.paragraph_number, None);
+  assert_eq!(article.item_number, None);
+  // 別表は本則の階層をすべて消す
+  let (_, joukou) = find_joukou(
+    "民法別表第二",
+    &Position { start: 0, end: 2 },
+    &Keywords::default().parens,
+    &mut Vec::new(),
+  );
+  let table = law.with_locator(joukou);
+  assert_eq!(table.get_appdx_table(), Some(String::from("2")));
+  assert_eq!(table.chapter_number, None);
+  assert_eq!(table.article_number, None);
+  // 条を指定すれば別表ではなくなる
+  let article = table.with_locator(JoukouRef {
+    article_number: parse_article_number("第一条"),
+    ..Default::default()
+  });
+  assert_eq!(article.get_appdx_table(), None);
+  assert_eq!(article.article_number, parse_article_number("第一条"));
+}
+
+/// 「○○法施行令」・「○○法施行規則」のように，見つかった法令名の直後に「施行令」・「施行規則」が続く場合に，
+/// 「○○法」の法令名から対応する施行令（政令）・施行規則（府省令・規則）を探して置き換える
+/// 略称の「法」に続く「法施行令」のような場合もこれで解決できる
+fn expand_enforcement_orders(
+  text: &str,
+  lst: &[FindLawName],
+  law_map: &HashMap<String, Law>,
+) -> Vec<FindLawName> {
+  let text_chars = text.chars().collect::<Vec<_>>();
+  let suffix_list = [
+    ("施行令", vec![LawType::CabinetOrder]),
+    (
+      "施行規則",
+      vec![LawType::MinisterialOrdinance, LawType::Rule],
+    ),
+  ];
+  let mut expanded = Vec::new();
+  for find in lst.iter() {
+    let mut find = find.clone();
+    if let Some(law) = &find.find_law
+      && let Some(name) = law.get_name()
+    {
+      let rest = text_chars[find.position.end..].iter().collect::<String>();
+      for (suffix, law_types) in suffix_list.iter() {
+        if !rest.starts_with(suffix) || name.ends_with(suffix) {
+          continue;
+        }
+        let order_name = format!("{name}{suffix}");
+        let order = law_map
+          .get(&order_name)
+          .or_else(|| {
+            law_map
+              .values()
+              .find(|l| l.get_name().as_ref() == Some(&order_name))
+          })
+          .filter(|l| law_types.contains(&l.law_type));
+        if let Some(order) = order {
+          trace!("expand enforcement order: {order_name}");
+          find = FindLawName {
+            position: Position {
+              start: find.position.start,
+              end: find.position.end + suffix.chars().count(),
+            },
+            match_string: format!("{}{suffix}", find.match_string),
+            find_law: Some(order.clone()),
+            kind: MatchKind::EnforcementOrder,
+          };
+          break;
+        }
+      }
+    }
+    expanded = resolve_duplicates(&expanded, &find);
+  }
+  sort_by_position(&mut expanded);
+  expanded
+}
+
+#[test]
+fn check_expand_enforcement_orders() {
+  let act = Law::new(
+    Date::new_ad(2025, 11, 26),
+    Some(String::from("地方自治法")),
+    String::from("322AC0000000067"),
+    String::from("昭和二十二年法律第六十七号"),
+    LawType::Act,
+  );
+  let order = Law::new(
+    Date::new_ad(2025, 11, 26),
+    Some(String::from("地方自治法施行令")),
+    String::from("322CO0000000016"),
+    String::from("昭和二十二年政令第十六号"),
+    LawType::CabinetOrder,
+  );
+  let mut law_map = HashMap::new();
+  law_map.insert(String::from("地方自治法"), act);
+  law_map.insert(String::from("昭和二十二年政令第十六号"), order);
+  let mut target = HashMap::new();
+  let texts = [
+    "この規則は、地方自治法（以下「法」という。）の規定に基づき定める。",
+    "法施行令第三条の規定は、この規則について準用する。",
+  ];
+  for (i, text) in texts.iter().enumerate() {
+    let mut paragraph = Law::new(
+      Date::new_ad(2025, 11, 26),
+      Some(String::from("テスト規則")),
+      String::from("test"),
+      String::new(),
+      LawType::Rule,
+    );
+    paragraph.set_paragraph_number(parse_article_number(&format!("{}", i + 1)).unwrap());
+    paragraph.set_paragraph_text(text.to_string());
+    target.insert(format!("{i}"), paragraph);
+  }
+  let find_law_ids = |config: &ParseConfig| {
+    parse_ref_with_config(&target, &law_map, config)
+      .iter()
+      .filter(|f| f.from.paragraph_text.as_deref() == Some(texts[1]))
+      .map(|f| (f.to.get_law_id(), f.to.article_number_str(), f.position))
+      .collect::<Vec<_>>()
+  };
+  assert_eq!(
+    find_law_ids(&ParseConfig::default()),
+    vec![(
+      String::from("322AC0000000067"),
+      None,
+      Position { start: 0, end: 1 }
+    )]
+  );
+  let config = ParseConfig {
+    expand_enforcement_orders: true,
+    ..Default::default()
+  };
+  assert_eq!(
+    find_law_ids(&config),
+    vec![(
+      String::from("322CO0000000016"),
+      Some(String::from("article3")),
+      Position { start: 0, end: 7 }
+    )]
+  );
+}
+
+/// 参照の直後に続く表現から参照の種類を判定する
+/// 「第○条の規定により」・「○○法に基づき」のように委任や根拠を示す表現が続く場合は`BasedOn`とする
+/// 法令番号などの括弧書きは飛ばして判定する
+fn find_relation(text: &str, end: usize, regexes: &KeywordRegexes) -> Relation {
+  let mut rest = String::new();
+  let mut paren_depth = 0_usize;
+  for c in text.chars().skip(end) {
+    if c == '（' {
+      paren_depth += 1;
+    } else if c == '）' {
+      paren_depth = paren_depth.saturating_sub(1);
+    } else if paren_depth == 0 {
+      rest.push(c);
+    }
+  }
+  if regexes.based_on.as_ref().is_some_and(|r| r.is_match(&rest)) {
+    Relation::BasedOn
+  } else if rest.starts_with("の一部を次のように改正する") {
+    Relation::Changes
+  } else if ["は、廃止する", "は，廃止する", "を廃止する"]
+    .iter()
+    .any(|s| rest.starts_with(s))
+  {
+    Relation::Repeals
+  } else {
+    Relation::Cites
+  }
+}
+
+/// 改正文の中で番号だけで書かれた編・章・節・款・目・条の出現位置を探す
+/// 返す位置は`find_same_law_article`と同じく「第」の直前を指す幅0の範囲
+fn find_amend_targets(text: &str, found_positions: &[Position]) -> Vec<Position> {
+  static TARGET_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"第[一二三四五六七八九十百千]+[編章節款目条]").unwrap());
+  let byte_to_char_map = byte_to_char_index_map(text);
+  let mut lst = Vec::new();
+  for m in TARGET_RE.find_iter(text) {
+    let start = byte_to_char_map[m.start()];
+    if text[..m.start()].ends_with("附則")
+      || found_positions
+        .iter()
+        .any(|p| p.start <= start && start < p.end)
+    {
+      continue;
+    }
+    lst.push(Position { start, end: start });
+  }
+  lst
+}
+
+/// 改正文で番号の直後に続く表現から，改正か削除かを決める
+/// 「第三章を第二章とする」の「第二章」のように，改正の対象ではないものは`None`
+fn find_amend_relation(text: &str, end: usize) -> Option<Relation> {
+  let rest = text.chars().skip(end).collect::<String>();
+  if rest.starts_with("を削") {
+    Some(Relation::Repeals)
+  } else if ["中", "を次のように改め", "の次に", "に次の", "を第"]
+    .iter()
+    .any(|s| rest.starts_with(s))
+  {
+    Some(Relation::Changes)
+  } else {
+    None
+  }
+}
+
+#[test]
+fn check_egov_xml_id() {
+  let num = |s: &str| Some(ArticleNumber::from_num_str(s).unwrap());
+  let base = Law::new(
+    Date::new_ad(2025, 11, 26),
+    None,
+    String::from("test"),
+    String::new(),
+    LawType::Act,
+  );
+  // (編, 章, 節, 款, 目, 条, 項, e-govのURLのフラグメント)
+  let table = [
+    // 民法第九十条: https://laws.e-gov.go.jp/law/129AC0000000089#Mp-Pa_1-Ch_5-Se_1-At_90
+    (
+      num("1"),
+      num("5"),
+      num("1"),
+      None,
+      None,
+      num("90"),
+      None,
+      "Mp-Pa_1-Ch_5-Se_1-At_90",
+    ),
+    // 民法第三条の二
+    (
+      num("1"),
+      num("2"),
+      num("2"),
+      None,
+      None,
+      num("3_2"),
+      None,
+      "Mp-Pa_1-Ch_2-Se_2-At_3_2",
+    ),
+    // 民法第四百二十四条の五第一項
+    (
+      num("3"),
+      num("1"),
+      num("2"),
+      num("3"),
+      num("2"),
+      num("424_5"),
+      num("1"),
+      "Mp-Pa_3-Ch_1-Se_2-Ss_3-Di_2-At_424_5-Pr_1",
+    ),
+    // 労働基準法第三十二条: https://laws.e-gov.go.jp/law/322AC0000000049#Mp-Ch_4-At_32
+    (
+      None,
+      num("4"),
+      None,
+      None,
+      None,
+      num("32"),
+      None,
+      "Mp-Ch_4-At_32",
+    ),
+    // 日本国憲法第二十五条第二項
+    (
+      None,
+      num("3"),
+      None,
+      None,
+      None,
+      num("25"),
+      num("2"),
+      "Mp-Ch_3-At_25-Pr_2",
+    ),
+  ];
+  for (part, chapter, section, subsection, division, article, paragraph, id) in table {
+    let mut law = base.clone();
+    law.part_number = part;
+    law.chapter_number = chapter;
+    law.section_number = section;
+    law.subsection_number = subsection;
+    law.division_number = division;
+    law.article_number = article;
+    law.paragraph_number = paragraph;
+    assert_eq!(law.egov_xml_id().as_deref(), Some(id));
+  }
+  // 法令全体にはIDが無い
+  assert_eq!(base.egov_xml_id(), None);
+}
+
+#[test]
+fn check_short_text() {
+  // 一文字の法令名だけのテキストで，前後の文字を調べる処理が範囲外を読まないこと
+  let law = Law::new(
+    Date::new_ad(2025, 1, 1),
+    Some(String::from("法")),
+    String::from("test"),
+    String::new(),
+    LawType::Act,
+  );
+  let mut law_map = HashMap::new();
+  law_map.insert(String::from("法"), law);
+  for text in ["法", "同法", "法）", "別表"] {
+    let mut paragraph = Law::new(
+      Date::new_ad(2025, 1, 1),
+      None,
+      String::from("from"),
+      String::new(),
+      LawType::Act,
+    );
+    paragraph.set_paragraph_number(parse_article_number("第一項").unwrap());
+    paragraph.set_paragraph_text(text.to_string());
+    let mut target = HashMap::new();
+    target.insert(String::from("1"), paragraph);
+    parse_ref(&target, &law_map);
+  }
+  // テキストの最後の文字も「同法人」・「同法律第」の判定に使う
+  let regexes = KeywordRegexes::new(&Keywords::default());
+  assert!(find_douhou("同法人", &regexes).is_empty());
+  assert!(find_douhou("同法律第", &regexes).is_empty());
+  assert_eq!(find_douhou("同法", &regexes).len(), 1);
+}
+
+#[test]
+fn check_find_relation() {
+  let minpou = minpou(Date::new_ad(2025, 11, 26));
+  let mut law_map = HashMap::new();
+  law_map.insert(String::from("民法"), minpou);
+  let parse = |text: &str| refs(text, &law_map);
+  let based_on = parse("民法第九十条の規定により、無効とする。");
+  assert_eq!(based_on.len(), 1);
+  assert_eq!(based_on[0].get_relation(), Relation::BasedOn);
+  assert_eq!(based_on[0].position, Position { start: 0, end: 6 });
+  let based_on = parse("民法（明治二十九年法律第八十九号）に基づき、定める。");
+  assert_eq!(based_on[0].get_relation(), Relation::BasedOn);
+  let repeals = parse("民法（明治二十九年法律第八十九号）は、廃止する。");
+  assert_eq!(repeals[0].get_relation(), Relation::Repeals);
+  let cites = parse("民法第九十条に規定する法律行為をいう。");
+  assert_eq!(cites.len(), 1);
+  assert_eq!(cites[0].get_relation(), Relation::Cites);
+  assert_eq!(
+    cites[0].triple().predicate.as_str(),
+    "http://data.europa.eu/eli/ontology#cites"
+  );
+}
+
+#[test]
+fn check_reified_triples() {
+  let minpou = minpou(Date::new_ad(2025, 11, 26));
+  let law_map = HashMap::from([(String::from("民法"), minpou)]);
+  let finds = refs("民法第九十条の規定を準用する。", &law_map);
+  let find = &finds[0];
+  let triple = find.triple();
+  let reified = find.reified_triples();
+  // 全て同じ空白ノードを主語とする
+  let statement = reified[0].subject.clone();
+  assert!(matches!(statement, oxrdf::NamedOrBlankNode::BlankNode(_)));
+  assert!(reified.iter().all(|t| t.subject == statement));
+  let predicate_object = reified
+    .iter()
+    .map(|t| (t.predicate.as_str().to_string(), t.object.clone()))
+    .collect::<Vec<_>>();
+  let position = |value: &str| Literal::new_typed_literal(value, oxrdf::vocab::xsd::INTEGER).into();
+  assert_eq!(
+    predicate_object,
+    vec![
+      (
+        oxrdf::vocab::rdf::TYPE.as_str().to_string(),
+        oxrdf::vocab::rdf::STATEMENT.into()
+      ),
+      (
+        oxrdf::vocab::rdf::SUBJECT.as_str().to_string(),
+        oxrdf::Term::from(triple.subject.clone())
+      ),
+      (
+        oxrdf::vocab::rdf::PREDICATE.as_str().to_string(),
+        triple.predicate.clone().into()
+      ),
+      (
+        oxrdf::vocab::rdf::OBJECT.as_str().to_string(),
+        triple.object.clone()
+      ),
+      (format!("{REFERENCE_NAMESPACE}start"), position("0")),
+      (format!("{REFERENCE_NAMESPACE}end"), position("6")),
+    ]
+  );
+  // 参照ごとに別の空白ノードになる
+  assert_ne!(find.reified_triples()[0].subject, statement);
+}
+
+#[test]
+fn check_without_text() {
+  let law_data = egov_xml_parse(
+    TEST_XML.as_bytes(),
+    Date::new_ad(2025, 4, 1),
+    None,
+    String::from("507AC0000000001"),
+    None,
+  )
+  .unwrap()
+  .parts;
+  let paragraph = law_data
+    .values()
+    .find(|l| !l.get_paragraph_items().is_empty())
+    .unwrap();
+  let without_text = paragraph.without_text();
+  assert_eq!(without_text.get_paragraph_text(), None);
+  assert!(without_text.get_paragraph_items().is_empty());
+  // 本文以外は同じ
+  assert_eq!(LawKey::from(&without_text), LawKey::from(paragraph));
+  let mut with_empty_text = paragraph.clone();
+  with_empty_text.paragraph_text = None;
+  with_empty_text.paragraph_items = Vec::new();
+  assert_eq!(without_text, with_empty_text);
+}
+
+#[test]
+fn check_extract_abbreviations() {
+  let minpou = minpou(Date::new_ad(2025, 11, 26));
+  let mut law_map = HashMap::new();
+  law_map.insert(String::from("民法"), minpou);
+  let target = HashMap::from([(
+    String::new(),
+    paragraph("この法律（以下「本法」という。）及び民法（以下「旧法」という。）"),
+  )]);
+  let abbreviations = extract_abbreviations(&target, &law_map, &ParseConfig::default());
+  let result = abbreviations
+    .iter()
+    .map(|a| {
+      (
+        a.term.as_str(),
+        a.law.as_ref().map(|l| l.get_law_id()),
+        a.position,
+      )
+    })
+    .collect::<Vec<_>>();
+  assert_eq!(
+    result,
+    vec![
+      // 「この法律」の略称はその法令自身に紐付く
+      (
+        "本法",
+        Some(String::from("test")),
+        Position { start: 8, end: 10 }
+      ),
+      (
+        "旧法",
+        Some(String::from("129AC0000000089")),
+        Position { start: 24, end: 26 }
+      ),
+    ]
+  );
+  assert_eq!(abbreviations[0].defined_in.get_law_id(), "test");
+  assert_eq!(abbreviations[0].defined_in.get_paragraph_text(), None);
+  // 参照を探すだけのときは略称の定義を記録しない
+  let (_, report) = parse_ref_with_report(&target, &law_map, &ParseConfig::default());
+  assert!(report.abbreviations.is_empty());
+}
+
+#[test]
+fn check_strip_name_note() {
+  assert_eq!(
+    strip_name_note("消費者契約法（略称：消契法）"),
+    "消費者契約法"
+  );
+  assert_eq!(strip_name_note("テスト法（通称：テスト）"), "テスト法");
+  // 注記ではない括弧は取り除かない
+  assert_eq!(
+    strip_name_note("テスト法（平成元年）"),
+    "テスト法（平成元年）"
+  );
+  assert_eq!(strip_name_note("（略称：テスト）"), "（略称：テスト）");
+  assert_eq!(strip_name_note("民法"), "民法");
+  let law_map = ["消費者契約法（略称：消契法）", "テスト（試行）法"]
+    .into_iter()
+    .enumerate()
+    .map(|(i, name)| {
+      let law = Law::new(
+        Date::new_ad(2025, 11, 26),
+        Some(name.to_string()),
+        format!("test{i}"),
+        String::new(),
+        LawType::Act,
+      );
+      (name.to_string(), law)
+    })
+    .collect::<HashMap<_, _>>();
+  let to = refs(
+    "消費者契約法第四条及びテスト（試行）法第二条の規定を準用する。",
+    &law_map,
+  )
+  .iter()
+  .map(|f| (f.to.get_name(), f.position))
+  .collect::<Vec<_>>();
+  // 出力には注記を含む法令名を使う
+  assert_eq!(
+    to,
+    vec![
+      (
+        Some(String::from("消費者契約法（略称：消契法）")),
+        Position { start: 0, end: 9 }
+      ),
+      (
+        Some(String::from("テスト（試行）法")),
+        Position { start: 11, end: 22 }
+      ),
+    ]
+  );
+}
+
+#[test]
+fn check_odoriji() {
+  assert_eq!(expand_odoriji("段々畑と〻"), "段段畑とと");
+  // 先頭の踊り字はそのまま
+  assert_eq!(expand_odoriji("々"), "々");
+  let law_map = [("段々畑保全法", "test1"), ("種種調整法", "test2")]
+    .into_iter()
+    .map(|(name, id)| {
+      let law = Law::new(
+        Date::new_ad(2025, 11, 26),
+        Some(name.to_string()),
+        id.to_string(),
+        String::new(),
+        LawType::Act,
+      );
+      (name.to_string(), law)
+    })
+    .collect::<HashMap<_, _>>();
+  let to = refs("段段畑保全法第一条及び種々調整法第二条", &law_map)
+    .iter()
+    .map(|f| (f.to.get_law_id(), f.position))
+    .collect::<Vec<_>>();
+  assert_eq!(
+    to,
+    vec![
+      (String::from("test1"), Position { start: 0, end: 9 }),
+      (String::from("test2"), Position { start: 11, end: 19 }),
+    ]
+  );
+}
+
+#[test]
+fn check_normalize_punctuation() {
+  assert_eq!(
+    normalize_punctuation("第一項,第二項，第三項､第四項.．｡"),
+    "第一項、第二項、第三項、第四項。。。"
+  );
+  let minpou = minpou(Date::new_ad(2025, 11, 26));
+  let law_map = HashMap::from([(String::from("民法"), minpou)]);
+  let root = Law::new(
+    Date::new_ad(2025, 11, 26),
+    Some(String::from("テスト法")),
+    String::from("test"),
+    String::new(),
+    LawType::Act,
+  );
+  let parse = |text: &str| {
+    let mut target = HashMap::from([(String::new(), root.clone())]);
+    for num in ["第一項", "第二項"] {
+      let mut paragraph = root.clone();
+      paragraph.article_number = parse_article_number("第一条");
+      paragraph.set_paragraph_number(parse_article_number(num).unwrap());
+      target.insert(paragraph.number_text(), paragraph);
+    }
+    target
+      .get_mut("第一条１")
+      .unwrap()
+      .set_paragraph_text(text.to_string());
+    parse_ref(&target, &law_map)
+      .iter()
+      .map(|f| (f.to.law_id.clone(), f.to.number_text(), f.position))
+      .collect::<Vec<_>>()
+  };
+  // 半角の読点で並べた「第二項」も，全角の場合と同じく民法第九十条の項とみなし，この条の項としない
+  for text in [
+    "民法第九十条第一項、第二項の規定",
+    "民法第九十条第一項,第二項の規定",
+  ] {
+    assert_eq!(
+      parse(text),
+      vec![
+        (
+          String::from("129AC0000000089"),
+          String::from("第九十条１"),
+          Position { start: 0, end: 9 }
+        ),
+        (
+          String::from("129AC0000000089"),
+          String::from("第九十条２"),
+          Position { start: 10, end: 13 }
+        ),
+      ],
+      "{text}"
+    );
+  }
+  // 並べて書かれた条も同じ法令の条とする
+  assert_eq!(
+    parse("民法第九十条及び第九十一条の規定"),
+    vec![
+      (
+        String::from("129AC0000000089"),
+        String::from("第九十条"),
+        Position { start: 0, end: 6 }
+      ),
+      (
+        String::from("129AC0000000089"),
+        String::from("第九十一条"),
+        Position { start: 8, end: 13 }
+      ),
+    ]
+  );
+  // 全角のピリオドで終わる略称の定義も見つける
+  let mut paragraph = root.clone();
+  paragraph.set_paragraph_text(String::from("民法（以下「旧法」という．）第一条"));
+  let abbreviations = extract_abbreviations(
+    &HashMap::from([(String::new(), paragraph)]),
+    &law_map,
+    &ParseConfig::default(),
+  );
+  assert_eq!(abbreviations.len(), 1);
+  assert_eq!(abbreviations[0].term, "旧法");
+  assert_eq!(
+    abbreviations[0].law.as_ref().map(|l| l.get_law_id()),
+    Some(String::from("129AC0000000089"))
+  );
+}
+
+#[test]
+fn check_clean_text() {
+  let xml = format!(
+    r#"<?xml version="1.0" encoding="UTF-8"?>
+<Law Era="Reiwa" Lang="ja" LawType="Act" Num="1" Year="7"><LawNum>令和七年法律第一号</LawNum><LawBody><LawTitle>テスト法</LawTitle>
+<MainProvision>
+<Article Num="1"><ArticleTitle>第一条</ArticleTitle>
+<Paragraph Num="1"><ParagraphNum/><ParagraphSentence><Sentence Num="1"><Ruby>民<Rt>みん</Rt></Ruby>法第九十条の規定により、無効とする。</Sentence></ParagraphSentence></Paragraph>
+<Paragraph Num="2"><ParagraphNum>２</ParagraphNum><ParagraphSentence><Sentence Num="1">この場合において、民{zwsp}法第九十一条{zwj}の規定を準用する。</Sentence></ParagraphSentence></Paragraph>
+</Article>
+</MainProvision>
+</LawBody>
+</Law>"#,
+    zwsp = '\u{200B}',
+    zwj = '\u{200D}'
+  );
+  let minpou = minpou(Date::new_ad(2025, 11, 26));
+  let mut law_map = HashMap::new();
+  law_map.insert(String::from("民法"), minpou);
+  let mut finds = analyze(
+    xml.as_bytes(),
+    Date::new_ad(2025, 11, 26),
+    None,
+    String::from("507AC0000000001"),
+    None,
+    &law_map,
+    &ParseConfig::default(),
+  )
+  .unwrap();
+  finds.sort_by_key(|f| f.from.paragraph_number_str());
+  assert_eq!(finds.len(), 2);
+  // ルビは親文字だけが残る
+  assert_eq!(
+    finds[0].to.article_number_str(),
+    Some(String::from("article90"))
+  );
+  assert_eq!(finds[0].position, Position { start: 0, end: 6 });
+  // ゼロ幅文字は検索時に取り除かれ，位置は元のテキストのもの
+  assert_eq!(
+    finds[1].to.article_number_str(),
+    Some(String::from("article91"))
+  );
+  assert_eq!(finds[1].position, Position { start: 9, end: 17 });
+}
+
+// 各charの始まりに該当するバイト位置をcharの位置に変換するためのマップ
+fn byte_to_char_index_map(text: &str) -> Vec<usize> {
+  // 各バイト位置に対する char インデックス
+  let mut map = vec![0; text.len() + 1];
+  let mut char_index = 0;
+  for (byte_index, _) in text.char_indices() {
+    map[byte_index] = char_index;
+    char_index += 1;
+  }
+  map[text.len()] = char_index;
+  map
+}
+
+/// 法令名の位置と参照先の法令IDの一覧から，残すものを選ぶ
+/// 「明治二十九年法律第八十九号（民法）」・「民法（以下「法」という。）」のように，
+/// 法令名の直後の括弧書きの中で同じ法令を指す法令名は，括弧書きの前の法令名と同じ参照なので残さない
+/// 括弧書きの後の条項番号に続く括弧書き（「第九十条（同法第九十一条において準用する場合を含む。）」など）の中のものは別の参照として残す
+fn keep_outer_names(text: &str, names: &[(Position, &str)], parens: &[(char, char)]) -> Vec<bool> {
+  let text_chars = text.chars().collect::<Vec<_>>();
+  // 法令名の直後に続く括弧書きの終わり
+  let note_ends = names
+    .iter()
+    .map(|(position, _)| {
+      let mut end = position.end;
+      while let Some(&(_, close)) = text_chars
+        .get(end)
+        .and_then(|c| parens.iter().find(|(open, _)| open == c))
+      {
+        let mut closers = vec![close];
+        let mut i = end + 1;
+        while let Some(c) = text_chars.get(i) {
+          i += 1;
+          if let Some(&(_, close)) = parens.iter().find(|(open, _)| open == c) {
+            closers.push(close);
+          } else if let Some(depth) = closers.iter().rposition(|close| close == c) {
+            closers.truncate(depth);
+            if closers.is_empty() {
+              break;
+            }
+          }
+        }
+        end = i;
+      }
+      end
+    })
+    .collect::<Vec<_>>();
+  names
+    .iter()
+    .enumerate()
+    .map(|(j, (position, law_id))| {
+      !names.iter().zip(note_ends.iter()).enumerate().any(
+        |(i, ((outer, outer_law_id), note_end))| {
+          i != j
+            && law_id == outer_law_id
+            && outer.end <= position.start
+            && position.end <= *note_end
+        },
+      )
+    })
+    .collect()
+}
+
+/// 範囲が重複した法令について、重複を解消する
+/// 原則として範囲が大きい方が優先
+/// 同じ範囲だった場合は後から見つかったものを優先（法令名中の略称の場合なので優先される）
+/// 入力のfind_lstは常に解消済みのものであるとする
+/// 同じ範囲のもの同士を除けば，どの順で追加しても同じ結果になる
+fn resolve_duplicates(find_lst: &[FindLawName], find: &FindLawName) -> Vec<FindLawName> {
+  // 見つかったものの方が大きかったので置き換えない
+  if find_lst
+    .iter()
+    .any(|f| f.position != find.position && f.position.contains(&find.position))
+  {
+    return find_lst.to_vec();
+  }
+  // 見つかったものが同じか小さかったものは，すべて置き換える
+  let mut lst = Vec::new();
+  let mut replaced = false;
+  for f in find_lst.iter() {
+    if !find.position.contains(&f.position) {
+      lst.push(f.clone());
+    } else if !replaced {
+      lst.push(find.clone());
+      replaced = true;
+    }
+  }
+  // 重複は無かったので追加
+  if !replaced {
+    lst.push(find.clone())
+  }
+  lst
+}
+
+#[test]
+fn check_resolve_duplicates_order() {
+  let candidate = |start, end| FindLawName {
+    position: Position { start, end },
+    match_string: String::new(),
+    kind: MatchKind::FullName,
+    find_law: None,
+  };
+  // 二つの法令名を含む長い法令名と，それと一部だけ重なる法令名
+  let candidates = [
+    candidate(0, 2),
+    candidate(2, 4),
+    candidate(0, 6),
+    candidate(1, 3),
+    candidate(5, 8),
+  ];
+  let resolve = |order: &[FindLawName]| {
+    let mut lst = order
+      .iter()
+      .fold(Vec::new(), |lst, find| resolve_duplicates(&lst, find));
+    sort_by_position(&mut lst);
+    lst.iter().map(|f| f.position).collect::<Vec<_>>()
+  };
+  let expected = vec![Position { start: 0, end: 6 }, Position { start: 5, end: 8 }];
+  for reverse in [false, true] {
+    for rotation in 0..candidates.len() {
+      let mut order = candidates.to_vec();
+      if reverse {
+        order.reverse();
+      }
+      order.rotate_left(rotation);
+      assert_eq!(resolve(&order), expected, "{order:?}");
+    }
+  }
+  // 同じ範囲の場合は後から見つかったものを優先する
+  let mut abb = candidate(0, 6);
+  abb.kind = MatchKind::Abbreviation;
+  let lst = resolve_duplicates(&[candidate(0, 6), candidate(5, 8)], &abb);
+  assert_eq!(lst[0].kind, MatchKind::Abbreviation);
+  assert_eq!(lst.len(), 2);
+}
+
+// find_abb_defとsearch_douhouの実行結果と、find_law_nameの実行結果を比較して、抽出位置が直前のものを紐づける
+fn linking_abb_and_full_name(
+  abb_info: &FindLawName,
+  full_name_info_list: &[FindLawName],
+) -> Option<FindLawName> {
+  let mut result: Option<FindLawName> = None;
+  for full_name_info in full_name_info_list.iter() {
+    if full_name_info.position.end <= abb_info.position.start {
+      if let Some(ref old_result) = result {
+        // すでに見つかったものよりも遠い場合は上書きしない
+        if full_name_info.position.end < old_result.position.end {
+          continue;
+        }
+        // 「租税特別措置法」の中の「措置法」のように終わりが同じ場合は，長い方の法令名を優先する
+        if full_name_info.position.end == old_result.position.end
+          && full_name_info.position.start > old_result.position.start
+        {
+          continue;
+        }
+      }
+      result = Some(full_name_info.clone())
+    }
+  }
+  if let Some(result) = result {
+    Some(FindLawName {
+      find_law: result.find_law.clone(),
+      ..abb_info.clone()
+    })
+  } else {
+    None
+  }
+}
+
+#[test]
+fn check_linking() {
+  let f = FindLawName {
+    position: Position { start: 20, end: 27 },
+    match_string: String::new(),
+    kind: MatchKind::FullName,
+    find_law: None,
+  };
+  let lst = vec![
+    FindLawName {
+      position: Position { start: 5, end: 7 },
+      match_string: String::new(),
+      kind: MatchKind::FullName,
+      find_law: Some(Law::new(
+        Date::new_ad(2025, 11, 26),
+        None,
+        String::from("test1"),
+        String::new(),
+        LawType::Act,
+      )),
+    },
+    FindLawName {
+      position: Position { start: 8, end: 10 },
+      match_string: String::new(),
+      kind: MatchKind::FullName,
+      find_law: Some(Law::new(
+        Date::new_ad(2025, 11, 26),
+        None,
+        String::from("test2"),
+        String::new(),
+        LawType::Act,
+      )),
+    },
+    FindLawName {
+      position: Position { start: 29, end: 31 },
+      match_string: String::new(),
+      kind: MatchKind::FullName,
+      find_law: Some(Law::new(
+        Date::new_ad(2025, 11, 26),
+        None,
+        String::from("test3"),
+        String::new(),
+        LawType::Act,
+      )),
+    },
+  ];
+  let result = linking_abb_and_full_name(&f, &lst);
+  assert!(result.is_some());
+  assert_eq!(
+    result.unwrap().find_law.map(|l| l.get_law_id()),
+    Some(String::from("test2"))
+  );
+}
+
+#[test]
+fn check_linking_overlapping_names() {
+  let candidate = |start, end, law_id: &str| FindLawName {
+    position: Position { start, end },
+    match_string: String::new(),
+    kind: MatchKind::FullName,
+    find_law: Some(Law::new(
+      Date::new_ad(2025, 11, 26),
+      None,
+      String::from(law_id),
+      String::new(),
+      LawType::Act,
+    )),
+  };
+  let abb = FindLawName {
+    position: Position { start: 20, end: 22 },
+    match_string: String::new(),
+    kind: MatchKind::Abbreviation,
+    find_law: None,
+  };
+  // 「租税特別措置法」の中の「措置法」のように，終わりが同じ二つの法令名
+  let long = candidate(2, 9, "long");
+  let short = candidate(6, 9, "short");
+  for lst in [
+    vec![long.clone(), short.clone()],
+    vec![short.clone(), long.clone()],
+  ] {
+    let result = linking_abb_and_full_name(&abb, &lst).unwrap();
+    assert_eq!(
+      result.find_law.map(|l| l.get_law_id()),
+      Some(String::from("long"))
+    );
+  }
+  // 終わりが後ろにある方が近いので，短くてもそちらを選ぶ
+  let lst = vec![long, candidate(8, 10, "nearer")];
+  let result = linking_abb_and_full_name(&abb, &lst).unwrap();
+  assert_eq!(
+    result.find_law.map(|l| l.get_law_id()),
+    Some(String::from("nearer"))
+  );
+}
+
+#[test]
+fn check_citation_text() {
+  let mut law = minpou(Date::new_ad(2025, 11, 26));
+  assert_eq!(law.citation_text(), "民法");
+  law.article_number = parse_article_number("第九十条");
+  law.paragraph_number = parse_article_number("第一項");
+  assert_eq!(law.citation_text(), "民法第九十条第一項");
+  law.article_number = parse_article_number("第三条の二");
+  law.paragraph_number = None;
+  assert_eq!(law.citation_text(), "民法第三条の二");
+  law.set_name(None);
+  assert_eq!(law.citation_text(), "明治二十九年法律第八十九号第三条の二");
+  law.article_number = None;
+  law.part_number = parse_article_number("第一編");
+  law.chapter_number = parse_article_number("第二章");
+  assert_eq!(
+    law.citation_text(),
+    "明治二十九年法律第八十九号第一編第二章"
+  );
+}
+
+#[test]
+fn check_law_display() {
+  let mut law = minpou(Date::new_ad(2025, 11, 26));
+  law.article_number = parse_article_number("第九十条");
+  // 条項番号は含めない
+  assert_eq!(law.to_string(), "民法（129AC0000000089）");
+  law.set_name(None);
+  assert_eq!(
+    law.to_string(),
+    "明治二十九年法律第八十九号（129AC0000000089）"
+  );
+}
+
+#[test]
+fn check_published() {
+  use eli::Eli;
+  let mut law = minpou(Date::new_ad(2025, 4, 1));
+  law.set_patch_id(String::from("505AC0000000053"));
+  let uri = |l: &Law| match l.published() {
+    eli::Published::Uri(s) => s,
+    _ => unreachable!(),
+  };
+  // https://laws.e-gov.go.jp/law/129AC0000000089/20250401_505AC0000000053
+  assert_eq!(
+    uri(&law),
+    "https://laws.e-gov.go.jp/law/129AC0000000089/20250401_505AC0000000053"
+  );
+  law.article_number = parse_article_number("第九十条");
+  assert_eq!(
+    uri(&law),
+    "https://laws.e-gov.go.jp/law/129AC0000000089/20250401_505AC0000000053#Mp-At_90"
+  );
+  // 法令全体のページは条項のアンカーを付けない
+  assert_eq!(
+    law.egov_law_url(EGOV_LAW_BASE_URL),
+    "https://laws.e-gov.go.jp/law/129AC0000000089"
+  );
+  assert_eq!(
+    law.egov_version_url(EGOV_LAW_BASE_URL),
+    "https://laws.e-gov.go.jp/law/129AC0000000089/20250401_505AC0000000053"
+  );
+
+  let original = Law::new(
+    Date::new_ad(1947, 5, 3),
+    Some(String::from("日本国憲法")),
+    String::from("321CONSTITUTION"),
+    String::from("昭和二十一年憲法"),
+    LawType::Constitution,
+  );
+  assert_eq!(
+    uri(&original),
+    "https://laws.e-gov.go.jp/law/321CONSTITUTION/19470503_000000000000000"
+  );
+  // ミラーなどのベースURLは呼び出し側で渡す
+  let mirror = "http://localhost:8080/law/";
+  let eli::Published::Uri(mirror_uri) = original.published_at(mirror) else {
+    unreachable!()
+  };
+  assert_eq!(
+    mirror_uri,
+    "http://localhost:8080/law/321CONSTITUTION/19470503_000000000000000"
+  );
+  assert_eq!(
+    original.egov_law_url(mirror),
+    "http://localhost:8080/law/321CONSTITUTION"
+  );
+  assert_eq!(original.egov_version_url(mirror), mirror_uri);
+}
+
+#[test]
+fn check_constitution() {
+  let constitution = Law::new(
+    Date::new_ad(1947, 5, 3),
+    Some(String::from("日本国憲法")),
+    String::from("321CONSTITUTION"),
+    String::from("昭和二十一年憲法"),
+    LawType::Constitution,
+  );
+  let mut law_map = HashMap::new();
+  law_map.insert(String::from("日本国憲法"), constitution.clone());
+  law_map.insert(String::from("憲法"), constitution);
+  let finds = refs(
+    "憲法第九条及び日本国憲法第二十五条第二項の規定の趣旨にのっとり、大日本帝国憲法第一条は参照しない。憲法審査会は、憲法改正原案を審査する。",
+    &law_map,
+  );
+  let uris = finds
+    .iter()
+    .map(|f| {
+      let eli::Published::Uri(published) = eli::Eli::published(&f.to) else {
+        unreachable!()
+      };
+      published
+    })
+    .collect::<Vec<_>>();
+  assert_eq!(
+    uris,
+    vec![
+      "https://laws.e-gov.go.jp/law/321CONSTITUTION/19470503_000000000000000#Mp-Ch_2-At_9",
+      "https://laws.e-gov.go.jp/law/321CONSTITUTION/19470503_000000000000000#Mp-Ch_3-At_25-Pr_2",
+    ]
+  );
+  let eli_uri = eli::Eli::eli_uri(&finds[0].to);
+  assert!(eli_uri.contains("/1947/05/03/constitution/321CONSTITUTION/"));
+  assert!(eli_uri.ends_with("/article9"));
+}
+
+#[test]
+fn check_parse_ref_with() {
+  let law_data = egov_xml_parse(
+    TEST_XML.as_bytes(),
+    Date::new_ad(2025, 4, 1),
+    None,
+    String::from("507AC0000000001"),
+    None,
+  )
+  .unwrap()
+  .parts;
+  let minpou = minpou(Date::new_ad(2025, 11, 26));
+  let mut law_map = HashMap::new();
+  law_map.insert(String::from("民法"), minpou);
+  let finds = parse_ref(&law_data, &law_map);
+  let mut visited = Vec::new();
+  parse_ref_with(&law_data, &law_map, &ParseConfig::default(), |find| {
+    visited.push(find);
+    ControlFlow::Continue(())
+  });
+  assert!(finds.len() > 2);
+  assert_eq!(visited, finds);
+  // 途中で打ち切る
+  let mut count = 0;
+  parse_ref_with(&law_data, &law_map, &ParseConfig::default(), |_| {
+    count += 1;
+    if count == 2 {
+      ControlFlow::Break(())
+    } else {
+      ControlFlow::Continue(())
+    }
+  });
+  assert_eq!(count, 2);
+}
+
+#[test]
+fn check_find_refs_in_paragraph() {
+  let minpou = minpou(Date::new_ad(2025, 11, 26));
+  let mut law_map = HashMap::new();
+  law_map.insert(String::from("民法"), minpou);
+  let contract = Law::new(
+    Date::new_ad(2025, 11, 26),
+    Some(String::from("売買契約書")),
+    String::from("contract"),
+    String::new(),
+    LawType::Misc,
+  );
+  let config = ParseConfig {
+    abbreviations: true,
+    ..Default::default()
+  };
+  let mut carry = ReferenceContext::new(&law_map, &config, contract);
+  let first = find_refs_in_paragraph(
+    "本契約に定めのない事項は、民法（以下「法」という。）第五百五十五条の定めによる。",
+    &law_map,
+    &mut carry,
+  );
+  // 前の段落で定義された略称を使える
+  let second = find_refs_in_paragraph(
+    "法第五百六十二条の規定は、適用しない。",
+    &law_map,
+    &mut carry,
+  );
+  let to = first
+    .iter()
+    .chain(second.iter())
+    .map(|f| {
+      (
+        f.get_from().get_law_id(),
+        f.get_from().get_paragraph_text().is_some(),
+        f.to.get_law_id(),
+        f.to.article_number_str(),
+      )
+    })
+    .collect::<Vec<_>>();
+  assert_eq!(
+    to,
+    vec![
+      (
+        String::from("contract"),
+        true,
+        String::from("129AC0000000089"),
+        Some(String::from("article555"))
+      ),
+      (
+        String::from("contract"),
+        true,
+        String::from("129AC0000000089"),
+        Some(String::from("article562"))
+      ),
+    ]
+  );
+  let report = carry.into_report();
+  assert_eq!(report.stats.total(), 2);
+  assert_eq!(report.abbreviations.len(), 1);
+}
+
+#[test]
+fn check_amend_target() {
+  let minpou = minpou(Date::new_ad(2025, 11, 26));
+  let mut law_map = HashMap::new();
+  law_map.insert(String::from("民法"), minpou);
+  let mut target = HashMap::new();
+  for (i, text) in [
+    "民法（明治二十九年法律第八十九号）の一部を次のように改正する。",
+    "第二章を次のように改める。",
+    "第三編第一章第三節を削る。",
+    "第九十条中「民法第一条」を「民法第二条」に改める。",
+    "第五章を第四章とする。",
+  ]
+  .iter()
+  .enumerate()
+  {
+    let mut paragraph = Law::new(
+      Date::new_ad(2025, 11, 26),
+      Some(String::from("民法の一部を改正する法律")),
+      String::from("test"),
+      String::new(),
+      LawType::Act,
+    );
+    paragraph.article_number = parse_article_number("第一条");
+    paragraph.paragraph_number = ArticleNumber::from_num_str(&(i + 1).to_string()).ok();
+    paragraph.set_paragraph_text(text.to_string());
+    target.insert(format!("第一条{i}"), paragraph);
+  }
+  let (finds, report) = parse_ref_with_report(&target, &law_map, &ParseConfig::default());
+  let base = |n: &Option<ArticleNumber>| n.as_ref().map_or(0, |n| n.base_number);
+  let result = finds
+    .iter()
+    .map(|f| {
+      (
+        [
+          base(&f.to.part_number),
+          base(&f.to.chapter_number),
+          base(&f.to.section_number),
+          base(&f.to.article_number),
+        ],
+        f.get_relation(),
+      )
+    })
+    .collect::<Vec<_>>();
+  assert!(finds.iter().all(|f| f.to.get_law_id() == "129AC0000000089"));
+  assert_eq!(
+    result,
+    vec![
+      ([0, 0, 0, 0], Relation::Changes),
+      ([0, 2, 0, 0], Relation::Changes),
+      ([3, 1, 3, 0], Relation::Repeals),
+      // 「」の中は改正される法令の文言の引用
+      ([0, 0, 0, 1], Relation::Cites),
+      ([0, 0, 0, 2], Relation::Cites),
+      ([0, 0, 0, 90], Relation::Changes),
+      // 「第四章とする」の「第四章」は改正の対象ではない
+      ([0, 5, 0, 0], Relation::Changes),
+    ]
+  );
+  assert!(finds[3].is_amendment_quote());
+  assert_eq!(report.stats.amend_target, 4);
+}
+
+#[test]
+fn check_item_amendment() {
+  let minpou = minpou(Date::new_ad(2025, 11, 26));
+  let mut law_map = HashMap::new();
+  law_map.insert(String::from("民法"), minpou);
+  let mut target = HashMap::new();
+  for (i, text) in [
+    "民法（明治二十九年法律第八十九号）の一部を次のように改正する。",
+    "第九十条第二項第三号を削る。",
+    "第九十一条第二項第三号を次のように改める。",
+    "第九十二条中「甲」を「乙」に改め、同条第二項第三号を削る。",
+    "第九十三条第一項中「甲」を「乙」に改め、同項第二号を次のように改める。",
+  ]
+  .iter()
+  .enumerate()
+  {
+    let mut paragraph = Law::new(
+      Date::new_ad(2025, 11, 26),
+      Some(String::from("民法の一部を改正する法律")),
+      String::from("test"),
+      String::new(),
+      LawType::Act,
+    );
+    paragraph.article_number = parse_article_number("第一条");
+    paragraph.paragraph_number = ArticleNumber::from_num_str(&(i + 1).to_string()).ok();
+    paragraph.set_paragraph_text(text.to_string());
+    target.insert(format!("第一条{i}"), paragraph);
+  }
+  let finds = parse_ref_with_config(&target, &law_map, &ParseConfig::default());
+  let result = finds
+    .iter()
+    .skip(1)
+    .map(|f| (f.to.number_text(), f.get_relation(), f.get_kind()))
+    .collect::<Vec<_>>();
+  assert!(finds.iter().all(|f| f.to.get_law_id() == "129AC0000000089"));
+  assert_eq!(
+    result,
+    vec![
+      (
+        String::from("第九十条２第三号"),
+        Relation::Repeals,
+        MatchKind::AmendTarget
+      ),
+      (
+        String::from("第九十一条２第三号"),
+        Relation::Changes,
+        MatchKind::AmendTarget
+      ),
+      (
+        String::from("第九十二条"),
+        Relation::Changes,
+        MatchKind::AmendTarget
+      ),
+      // 「同条」・「同項」の後の号も改正の対象になる
+      (
+        String::from("第九十二条２第三号"),
+        Relation::Repeals,
+        MatchKind::Doujou
+      ),
+      (
+        String::from("第九十三条１"),
+        Relation::Changes,
+        MatchKind::AmendTarget
+      ),
+      (
+        String::from("第九十三条１第二号"),
+        Relation::Changes,
+        MatchKind::Doujou
+      ),
+    ]
+  );
+  assert!(
+    eli::Eli::eli_uri(&finds[4].to).ends_with("/article92/paragraph2/item3"),
+    "{}",
+    eli::Eli::eli_uri(&finds[4].to)
+  );
+}
+
+#[test]
+fn check_law_key() {
+  let mut law = minpou(Date::new_ad(2025, 11, 26));
+  law.article_number = parse_article_number("第九十条");
+  let mut with_text = law.clone();
+  with_text.set_paragraph_text(String::from(
+    "公の秩序又は善良の風俗に反する法律行為は、無効とする。",
+  ));
+  with_text.set_egov_link(String::from("https://example.com"));
+  let mut other = law.clone();
+  other.article_number = parse_article_number("第九十一条");
+  let set = [&law, &with_text, &other]
+    .into_iter()
+    .map(LawKey::from)
+    .collect::<std::collections::HashSet<_>>();
+  assert_eq!(set.len(), 2);
+  assert!(set.contains(&LawKey::from(&with_text)));
+}
+
+#[test]
+fn check_match_stats() {
+  let minpou = minpou(Date::new_ad(2025, 11, 26));
+  let mut law_map = HashMap::new();
+  law_map.insert(String::from("民法"), minpou.clone());
+  law_map.insert(String::from("明治二十九年法律第八十九号"), minpou);
+  let mut target = HashMap::new();
+  let texts = [
+    "この法律は、民法（明治二十九年法律第八十九号。以下「法」という。）の特例を定める。",
+    "法第九十条及び同法第九十一条の規定は、適用しない。",
+  ];
+  for (i, text) in texts.iter().enumerate() {
+    let mut paragraph = paragraph(text);
+    paragraph.set_paragraph_number(parse_article_number(&format!("{}", i + 1)).unwrap());
+    target.insert(format!("{i}"), paragraph);
+  }
+  let (finds, report) = parse_ref_with_report(&target, &law_map, &ParseConfig::default());
+  let stats = report.stats;
+  assert_eq!(stats.total(), finds.len());
+  assert_eq!(stats.full_name, 1);
+  assert_eq!(stats.law_number, 0);
+  // 略称の定義の「法」は「民法」の参照と重なるので数えない
+  assert_eq!(stats.single_char, 1);
+  assert_eq!(stats.douhou, 1);
+}
+
+#[test]
+fn check_keywords() {
+  let minpou = minpou(Date::new_ad(2025, 11, 26));
+  let mut law_map = HashMap::new();
+  law_map.insert(String::from("民法"), minpou);
+  let mut target = HashMap::new();
+  let texts = [
+    "この法律は、民法（以下単に「法」という。）の特例を定める。",
+    "法第九十条の規定は、適用しない。",
+  ];
+  for (i, text) in texts.iter().enumerate() {
+    let mut paragraph = paragraph(text);
+    paragraph.set_paragraph_number(parse_article_number(&format!("{}", i + 1)).unwrap());
+    target.insert(format!("{i}"), paragraph);
+  }
+  let count_second = |config: &ParseConfig| {
+    parse_ref_with_config(&target, &law_map, config)
+      .iter()
+      .filter(|f| f.from.paragraph_text.as_deref() == Some(texts[1]))
+      .count()
+  };
+  // 「以下単に」は既定では略称の定義とみなさない
+  assert_eq!(count_second(&ParseConfig::default()), 0);
+  let mut config = ParseConfig::default();
+  config
+    .keywords
+    .abb_def_prefix
+    .push(String::from("以下単に"));
+  assert_eq!(count_second(&config), 1);
+  // 空の一覧はどこにでも一致するのではなく，その手がかりを使わないことを表す
+  let mut config = ParseConfig::default();
+  config.keywords.douhou.clear();
+  config.keywords.self_reference.clear();
+  config.keywords.abb_def_prefix.clear();
+  config.keywords.based_on.clear();
+  config.keywords.amendment = vec![String::new()];
+  let finds = parse_ref_with_config(&target, &law_map, &config);
+  assert_eq!(finds.len(), 1);
+  assert_eq!(finds[0].match_string, "民法");
+  assert_eq!(finds[0].relation, Relation::Cites);
+}
+
+#[test]
+fn check_strict_diagnostics() {
+  let minpou = minpou(Date::new_ad(2025, 11, 26));
+  let mut law_map = HashMap::new();
+  law_map.insert(String::from("民法"), minpou);
+  let target = HashMap::from([(
+    String::new(),
+    paragraph("民法第十八条第三の規定は、適用しない。"),
+  )]);
+  let (finds, report) = parse_ref_with_report(&target, &law_map, &ParseConfig::default());
+  assert_eq!(finds.len(), 1);
+  assert!(report.diagnostics.is_empty());
+  let config = ParseConfig {
+    strict: true,
+    ..Default::default()
+  };
+  let (finds, report) = parse_ref_with_report(&target, &law_map, &config);
+  // 読めなかった部分があっても参照自体は返す
+  assert_eq!(
+    finds[0].to.article_number_str(),
+    Some(String::from("article18"))
+  );
+  assert_eq!(report.diagnostics.len(), 1);
+  assert_eq!(report.diagnostics[0].token, "第三");
+  assert_eq!(report.diagnostics[0].position, finds[0].position);
+}
+
+#[test]
+fn check_item_reference() {
+  use eli::Eli;
+  let minpou = minpou(Date::new_ad(2025, 11, 26));
+  let mut law_map = HashMap::new();
+  law_map.insert(String::from("民法"), minpou);
+  let finds = refs(
+    "民法第九百条第一号及び民法第九十条第二項第三号に掲げる者並びに同項第四号に掲げる者",
+    &law_map,
+  );
+  let to = finds
+    .iter()
+    .map(|f| {
+      (
+        f.position,
+        f.to.number_text(),
+        f.to.citation_text(),
+        f.to.egov_xml_id(),
+        f.to.eli_uri(),
+      )
+    })
+    .collect::<Vec<_>>();
+  let uri = |s: &str| {
+    format!("https://github.com/puripuri2100-research/eli/2025/11/26/act/129AC0000000089/{s}")
+  };
+  assert_eq!(
+    to,
+    vec![
+      (
+        Position { start: 0, end: 9 },
+        String::from("第九百条第一号"),
+        String::from("民法第九百条第一号"),
+        Some(String::from("Mp-At_900-Pr_1-It_1")),
+        uri("/article900/paragraph1/item1"),
+      ),
+      (
+        Position { start: 11, end: 23 },
+        String::from("第九十条２第三号"),
+        String::from("民法第九十条第二項第三号"),
+        Some(String::from("Mp-At_90-Pr_2-It_3")),
+        uri("/article90/paragraph2/item3"),
+      ),
+      // 「同項」は号を引き継がない
+      (
+        Position { start: 31, end: 36 },
+        String::from("第九十条２第四号"),
+        String::from("民法第九十条第二項第四号"),
+        Some(String::from("Mp-At_90-Pr_2-It_4")),
+        uri("/article90/paragraph2/item4"),
+      ),
+    ]
+  );
+  let key = LawKey::from(&finds[1].to);
+  assert_eq!(key.item_number, ArticleNumber::from_num_str("3").ok());
+  assert_eq!(finds[1].to.parent().number_text(), "第九十条２");
+}
+
+#[test]
+fn check_doujou() {
+  let minpou = minpou(Date::new_ad(2025, 11, 26));
+  let mut law_map = HashMap::new();
+  law_map.insert(String::from("民法"), minpou);
+  let target = HashMap::from([(
+    String::new(),
+    paragraph(
+      "民法第九十条の規定及び同条第二項の規定並びに同項ただし書の規定は、同号に掲げる者には適用しない。",
+    ),
+  )]);
+  let (finds, report) = parse_ref_with_report(&target, &law_map, &ParseConfig::default());
+  let to = finds
+    .iter()
+    .map(|f| {
+      (
+        f.position.start,
+        f.to.get_law_id(),
+        f.to.article_number_str(),
+        f.to.paragraph_number_str(),
+      )
+    })
+    .collect::<Vec<_>>();
+  let article90 = Some(String::from("article90"));
+  let paragraph2 = Some(String::from("paragraph2"));
+  assert_eq!(
+    to,
+    vec![
+      (0, String::from("129AC0000000089"), article90.clone(), None),
+      (
+        11,
+        String::from("129AC0000000089"),
+        article90.clone(),
+        paragraph2.clone()
+      ),
+      (
+        22,
+        String::from("129AC0000000089"),
+        article90.clone(),
+        paragraph2.clone()
+      ),
+      (
+        33,
+        String::from("129AC0000000089"),
+        article90.clone(),
+        paragraph2.clone()
+      ),
+    ]
+  );
+  assert_eq!(finds[1].position, Position { start: 11, end: 16 });
+  assert_eq!(report.stats.doujou, 3);
+
+  // 「同条」は同じ項の中の参照だけを指し，前の項の参照は引き継がない
+  let mut target = HashMap::new();
+  let texts = [
+    "民法第九十一条の規定は、適用しない。",
+    "この場合において、同条第二項の規定及び民法第九十二条並びに同条第三項の規定を準用する。",
+  ];
+  for (i, text) in texts.iter().enumerate() {
+    let mut paragraph = paragraph(text);
+    paragraph.article_number = parse_article_number("第一条");
+    paragraph.set_paragraph_number(parse_article_number(&format!("{}", i + 1)).unwrap());
+    target.insert(paragraph.number_text(), paragraph);
+  }
+  let (finds, report) = parse_ref_with_report(&target, &law_map, &ParseConfig::default());
+  let to = finds
+    .iter()
+    .map(|f| {
+      (
+        f.from.paragraph_number_str(),
+        f.position.start,
+        f.to.article_number_str(),
+        f.to.paragraph_number_str(),
+      )
+    })
+    .collect::<Vec<_>>();
+  let paragraph1 = Some(String::from("paragraph1"));
+  let paragraph2 = Some(String::from("paragraph2"));
+  assert_eq!(
+    to,
+    vec![
+      (paragraph1, 0, Some(String::from("article91")), None),
+      (
+        paragraph2.clone(),
+        19,
+        Some(String::from("article92")),
+        None
+      ),
+      (
+        paragraph2,
+        29,
+        Some(String::from("article92")),
+        Some(String::from("paragraph3"))
+      ),
+    ]
+  );
+  assert_eq!(report.stats.doujou, 1);
+}
+
+#[test]
+fn check_same_article_paragraph() {
+  let minpou = minpou(Date::new_ad(2025, 11, 26));
+  let mut law_map = HashMap::new();
+  law_map.insert(String::from("民法"), minpou);
+  let root = Law::new(
+    Date::new_ad(2025, 11, 26),
+    Some(String::from("テスト法")),
+    String::from("test"),
+    String::new(),
+    LawType::Act,
+  );
+  let mut target = HashMap::new();
+  target.insert(String::new(), root.clone());
+  let texts = [
+    "この法律は、民法の特例を定める。",
+    "第一項第二号に掲げる場合には、第三条第二項及び民法第九十条第二項の規定並びに第四項の規定は、適用しない。",
+    "民法第九十条第一項及び第二項の規定は、適用しない。",
+  ];
+  for (i, text) in texts.iter().enumerate() {
+    let mut paragraph = root.clone();
+    paragraph.article_number = parse_article_number("第一条");
+    paragraph.set_paragraph_number(parse_article_number(&format!("{}", i + 1)).unwrap());
+    paragraph.set_paragraph_text(text.to_string());
+    target.insert(paragraph.number_text(), paragraph);
+  }
+  let (finds, report) = parse_ref_with_report(&target, &law_map, &ParseConfig::default());
+  let to = finds
+    .iter()
+    .map(|f| {
+      (
+        f.get_kind(),
+        f.to.get_law_id(),
+        f.to.article_number_str(),
+        f.to.paragraph_number_str(),
+        f.to.item_number_str(),
+      )
+    })
+    .collect::<Vec<_>>();
+  // 「第三条第二項」は条番号を伴うので同じ条の項とはみなさず，存在しない第四項も参照とみなさない
+  // 「民法第九十条第一項及び第二項」の「第二項」も同じ条の項とはみなさない
+  assert!(to.contains(&(
+    MatchKind::SameArticle,
+    String::from("test"),
+    Some(String::from("article1")),
+    Some(String::from("paragraph1")),
+    Some(String::from("item2")),
+  )));
+  assert!(to.contains(&(
+    MatchKind::FullName,
+    String::from("129AC0000000089"),
+    Some(String::from("article90")),
+    Some(String::from("paragraph2")),
+    None,
+  )));
+  assert_eq!(report.stats.same_article, 1);
+  assert_eq!(report.stats.total(), finds.len());
+  let same_article = finds
+    .iter()
+    .find(|f| f.get_kind() == MatchKind::SameArticle)
+    .unwrap();
+  assert_eq!(same_article.position, Position { start: 0, end: 6 });
+}
+
+#[test]
+fn check_unresolved() {
+  let target = HashMap::from([(
+    String::new(),
+    paragraph("同法第三条の規定により、同条第二項に定める。"),
+  )]);
+  let law_map = HashMap::new();
+  // 指定しなければ記録しない
+  let (_, report) = parse_ref_with_report(&target, &law_map, &ParseConfig::default());
+  assert!(report.unresolved.is_empty());
+  let config = ParseConfig {
+    unresolved: true,
+    ..Default::default()
+  };
+  let (finds, report) = parse_ref_with_report(&target, &law_map, &config);
+  assert!(finds.is_empty());
+  let unresolved = report
+    .unresolved
+    .iter()
+    .map(|r| (r.token.as_str(), r.position))
+    .collect::<Vec<_>>();
+  assert_eq!(
+    unresolved,
+    vec![
+      ("同法", Position { start: 0, end: 2 }),
+      ("同条", Position { start: 12, end: 14 }),
+    ]
+  );
+  assert_eq!(report.unresolved[0].from.get_paragraph_text(), None);
+}
+
+#[test]
+fn check_debug_candidates() {
+  let minpou = minpou(Date::new_ad(2025, 11, 26));
+  let mut law_map = HashMap::new();
+  law_map.insert(String::from("民法"), minpou.clone());
+  law_map.insert(String::from("明治二十九年法律第八十九号"), minpou);
+  let target = HashMap::from([(
+    String::new(),
+    paragraph("明治二十九年法律第八十九号（民法）第九十条及び同法第九十一条の規定を準用する。"),
+  )]);
+  // 指定しなければ記録しない
+  let (_, report) = parse_ref_with_report(&target, &law_map, &ParseConfig::default());
+  assert!(report.candidates.is_empty());
+  let config = ParseConfig {
+    debug_candidates: true,
+    ..Default::default()
+  };
+  let (finds, report) = parse_ref_with_report(&target, &law_map, &config);
+  // 括弧書きの「民法」は候補には残るが，参照にはならない
+  assert_eq!(finds.len(), 2);
+  let candidates = report
+    .candidates
+    .iter()
+    .map(|c| {
+      (
+        c.match_string.as_str(),
+        c.position,
+        c.kind,
+        c.law_id.as_deref(),
+      )
+    })
+    .collect::<Vec<_>>();
+  assert_eq!(
+    candidates,
+    vec![
+      (
+        "明治二十九年法律第八十九号",
+        Position { start: 0, end: 13 },
+        MatchKind::LawNumber,
+        Some("129AC0000000089")
+      ),
+      (
+        "民法",
+        Position { start: 14, end: 16 },
+        MatchKind::FullName,
+        Some("129AC0000000089")
+      ),
+      (
+        "同法",
+        Position { start: 23, end: 25 },
+        MatchKind::Douhou,
+        Some("129AC0000000089")
+      ),
+    ]
+  );
+  assert_eq!(report.candidates[0].from.get_paragraph_text(), None);
+}
+
+#[test]
+fn check_case_law() {
+  let target = HashMap::from([(
+    String::new(),
+    paragraph(
+      "最高裁判所平成二十年六月四日大法廷判決及び東京高判令和元年五月一日の趣旨を踏まえ、平成二十年六月四日までに定める。",
+    ),
+  )]);
+  let law_map = HashMap::new();
+  // 指定しなければ探さない
+  let (_, report) = parse_ref_with_report(&target, &law_map, &ParseConfig::default());
+  assert!(report.case_law_refs.is_empty());
+  let config = ParseConfig {
+    case_law: true,
+    ..Default::default()
+  };
+  let (finds, report) = parse_ref_with_report(&target, &law_map, &config);
+  assert!(finds.is_empty());
+  let refs = report
+    .case_law_refs
+    .iter()
+    .map(|r| (r.court.as_str(), r.date, r.text.as_str(), r.position))
+    .collect::<Vec<_>>();
+  assert_eq!(
+    refs,
+    vec![
+      (
+        "最高裁判所",
+        Some(Date::new_ad(2008, 6, 4)),
+        "最高裁判所平成二十年六月四日大法廷判決",
+        Position { start: 0, end: 19 }
+      ),
+      (
+        "東京高判",
+        Some(Date::new_ad(2019, 5, 1)),
+        "東京高判令和元年五月一日",
+        Position { start: 21, end: 33 }
+      ),
+    ]
+  );
+  let triple = report.case_law_refs[0].triple();
+  assert_eq!(
+    triple.predicate,
+    EliOntology::CitedByCaseLawReference.named_node()
+  );
+  assert_eq!(
+    triple.object,
+    Literal::new_simple_literal("最高裁判所平成二十年六月四日大法廷判決").into()
+  );
+}
+
+#[test]
+fn check_version_select() {
+  let version = |year| minpou(Date::new_ad(year, 4, 1));
+  let mut versions = LawVersions::new();
+  versions.insert(version(2025));
+  versions.insert(version(2020));
+  let mut law_map = HashMap::new();
+  law_map.insert(String::from("民法"), version(2025));
+  let to_dates = |year, config: &ParseConfig| {
+    let mut paragraph = paragraph("民法第九十条の規定は、適用しない。");
+    paragraph.date = Date::new_ad(year, 1, 1);
+    let mut target = HashMap::new();
+    target.insert(String::new(), paragraph);
+    parse_ref_with_config(&target, &law_map, config)
+      .iter()
+      .map(|f| f.to.get_date())
+      .collect::<Vec<_>>()
+  };
+  let latest = ParseConfig::default();
+  let at_date = ParseConfig {
+    version_select: VersionSelect::AtDate(versions),
+    ..Default::default()
+  };
+  assert_eq!(to_dates(2022, &latest), vec![Date::new_ad(2025, 4, 1)]);
+  assert_eq!(to_dates(2022, &at_date), vec![Date::new_ad(2020, 4, 1)]);
+  assert_eq!(to_dates(2026, &at_date), vec![Date::new_ad(2025, 4, 1)]);
+  // 知っている版より前の日付の場合は最も古い版
+  assert_eq!(to_dates(2010, &at_date), vec![Date::new_ad(2020, 4, 1)]);
+}
+
+#[test]
+fn check_version_select_article() {
+  let version = |year| minpou(Date::new_ad(year, 4, 1));
+  let articles = |nums: &[&str]| {
+    nums
+      .iter()
+      .map(|n| parse_article_number(n).unwrap())
+      .collect::<Vec<_>>()
+  };
+  let mut versions = LawVersions::new();
+  versions.insert(version(2020));
+  versions.insert(version(2025));
+  // 第三条の五は2025年の版で追加された
+  versions.insert_articles(&version(2020), articles(&["第三条", "第三条の四"]));
+  versions.insert_articles(
+    &version(2025),
+    articles(&["第三条", "第三条の四", "第三条の五"]),
+  );
+  let mut law_map = HashMap::new();
+  law_map.insert(String::from("民法"), version(2025));
+  let config = ParseConfig {
+    version_select: VersionSelect::AtDate(versions),
+    ..Default::default()
+  };
+  let to = |text: &str| {
+    let mut paragraph = paragraph(text);
+    paragraph.date = Date::new_ad(2022, 1, 1);
+    let mut target = HashMap::new();
+    target.insert(String::new(), paragraph);
+    parse_ref_with_config(&target, &law_map, &config)
+      .iter()
+      .map(|f| (f.to.citation_text(), f.to.get_date()))
+      .collect::<Vec<_>>()
+  };
+  assert_eq!(
+    to("民法第三条の四の規定は、適用しない。"),
+    vec![(String::from("民法第三条の四"), Date::new_ad(2020, 4, 1))]
+  );
+  // 参照元の日付の版に無い条は，その条を持つ版を使う
+  assert_eq!(
+    to("民法第三条の五の規定は、適用しない。"),
+    vec![(String::from("民法第三条の五"), Date::new_ad(2025, 4, 1))]
+  );
+  // どの版にも無い条は参照元の日付の版を使う
+  assert_eq!(
+    to("民法第三条の六の規定は、適用しない。"),
+    vec![(String::from("民法第三条の六"), Date::new_ad(2020, 4, 1))]
+  );
+  // 法令全体の参照は参照元の日付の版
+  assert_eq!(
+    to("民法の規定は、適用しない。"),
+    vec![(String::from("民法"), Date::new_ad(2020, 4, 1))]
+  );
+}
+
+#[test]
+fn check_version_triples() {
+  let version = |year, patch_id: &str| {
+    let mut law = minpou(Date::new_ad(year, 4, 1));
+    law.set_patch_id(String::from(patch_id));
+    law
+  };
+  let old = version(2020, "429AC0000000044");
+  let new = version(2025, "505AC0000000053");
+  let mut versions = LawVersions::new();
+  versions.insert(new.clone());
+  versions.insert(old.clone());
+  let work = format!("{ELI_BASE_URI}/act/129AC0000000089/");
+  assert_eq!(new.work_uri(), work);
+  let triple = |s: &str, p: EliOntology, o: &str| {
+    Triple::new(
+      NamedNode::new(s).unwrap(),
+      p.named_node(),
+      NamedNode::new(o).unwrap(),
+    )
+  };
+  let old_uri = eli::Eli::eli_uri(&old);
+  let new_uri = eli::Eli::eli_uri(&new);
+  // 最も古い版は抽象的な法令に属するだけ
+  assert_eq!(
+    versions.version_triples(&old),
+    vec![
+      triple(&old_uri, EliOntology::IsMemberOf, &work),
+      triple(&work, EliOntology::HasMember, &old_uri),
+    ]
+  );
+  assert_eq!(
+    versions.version_triples(&new),
+    vec![
+      triple(&new_uri, EliOntology::IsMemberOf, &work),
+      triple(&work, EliOntology::HasMember, &new_uri),
+      triple(&new_uri, EliOntology::Consolidates, &old_uri),
+      triple(&old_uri, EliOntology::ConsolidatedBy, &new_uri),
+    ]
+  );
+  // 登録していない版は何も返さない
+  assert!(
+    versions
+      .version_triples(&version(2022, "503AC0000000001"))
+      .is_empty()
+  );
+}
+
+#[test]
+fn check_amendment_quote() {
+  let minpou = minpou(Date::new_ad(2025, 11, 26));
+  let mut law_map = HashMap::new();
+  law_map.insert(String::from("民法"), minpou);
+  let finds = |text: &str| {
+    refs(text, &law_map)
+      .iter()
+      .map(|f| (f.position.start, f.is_amendment_quote()))
+      .collect::<Vec<_>>()
+  };
+  assert_eq!(
+    finds("第三条中「民法第九十条」を「民法第九十一条」に改め、民法第九十二条の規定を適用する。"),
+    vec![(5, true), (14, true), (26, false)]
+  );
+  // 改正文でなければ「」の中でも通常の参照
+  assert_eq!(
+    finds("「民法第九十条の規定」とあるのは、読み替えるものとする。"),
+    vec![(1, false)]
+  );
+}
+
+#[test]
+fn check_paragraph_order() {
+  let minpou = minpou(Date::new_ad(2025, 11, 26));
+  let mut law_map = HashMap::new();
+  law_map.insert(String::from("民法"), minpou);
+  let amend_laws = [
+    "",
+    "令和二年法律第一号",
+    "令和三年法律第二号",
+    "令和四年法律第三号",
+  ];
+  let mut first_order = None;
+  for _ in 0..10 {
+    // HashMapの走査順は作るたびに変わる
+    let mut target = HashMap::new();
+    for (i, amend_law) in amend_laws.iter().enumerate() {
+      let mut paragraph = paragraph("民法第九十条の規定は、適用しない。");
+      // どの附則も同じ第一項だけを持つ
+      paragraph.set_suppl_provision(amend_law.to_string());
+      target.insert(format!("附則{i}"), paragraph);
+    }
+    let order = parse_ref(&target, &law_map)
+      .iter()
+      .map(|f| f.from.get_suppl_provision().unwrap())
+      .collect::<Vec<_>>();
+    // 制定時の附則が最初に来て，後は毎回同じ順番になる
+    assert_eq!(order[0], "");
+    assert_eq!(order.len(), amend_laws.len());
+    match &first_order {
+      Some(first_order) => assert_eq!(&order, first_order),
+      None => first_order = Some(order),
+    }
+  }
+}
+
+#[test]
+fn check_law_number() {
+  // 法令名でしか登録されていない政令を，法令番号だけで参照する
+  let order = Law::new(
+    Date::new_ad(2025, 11, 26),
+    Some(String::from("テスト法施行令")),
+    String::from("410CO0000000005"),
+    String::from("平成十年政令第五号"),
+    LawType::CabinetOrder,
+  );
+  let mut law_map = HashMap::new();
+  law_map.insert(String::from("テスト法施行令"), order);
+  let target = HashMap::from([(
+    String::new(),
+    paragraph("平成十年政令第五号第三条及び平成十一年政令第六号第一条の規定は、適用しない。"),
+  )]);
+  let config = ParseConfig {
+    strict: true,
+    ..Default::default()
+  };
+  let (finds, report) = parse_ref_with_report(&target, &law_map, &config);
+  assert_eq!(finds.len(), 1);
+  assert_eq!(finds[0].to.get_law_id(), "410CO0000000005");
+  assert_eq!(
+    finds[0].to.article_number_str(),
+    Some(String::from("article3"))
+  );
+  assert_eq!(finds[0].position, Position { start: 0, end: 12 });
+  assert_eq!(report.stats.law_number, 1);
+  // 一覧に無い法令番号は診断情報になる
+  assert_eq!(report.diagnostics.len(), 1);
+  assert_eq!(report.diagnostics[0].token, "平成十一年政令第六号");
+}
+
+#[test]
+fn check_target_counter() {
+  let from = Law::new(
+    Date::new_ad(2025, 11, 26),
+    Some(String::from("テスト法")),
+    String::from("test"),
+    String::new(),
+    LawType::Act,
+  );
+  let minpou = minpou(Date::new_ad(2025, 11, 26));
+  let mut article90 = minpou.clone();
+  article90.article_number = parse_article_number("第九十条");
+  let mut article1 = minpou.clone();
+  article1.article_number = parse_article_number("第一条");
+  let find = |to: &Law| Find {
+    to: to.clone(),
+    from: from.clone(),
+    position: Position { start: 0, end: 1 },
+    relation: Relation::Cites,
+    amendment_quote: false,
+    from_item: Vec::new(),
+    kind: MatchKind::FullName,
+    match_string: String::from("民法"),
+    repealed_date: None,
+  };
+  let mut counter = TargetCounter::new();
+  counter.add(&[find(&article90), find(&minpou), find(&article90)]);
+  counter.add(&[find(&article1)]);
+  let ranking = counter.ranking(None);
+  // 別々に集計してから合わせても同じになる
+  let mut merged = TargetCounter::new();
+  merged.add(&[find(&article90), find(&minpou)]);
+  let mut other = TargetCounter::new();
+  other.add(&[find(&article90), find(&article1)]);
+  merged.merge(other);
+  assert_eq!(merged.ranking(None), ranking);
+  assert_eq!(ranking.len(), 3);
+  assert_eq!(ranking[0].citation, "民法第九十条");
+  assert_eq!(ranking[0].count, 2);
+  assert_eq!(ranking[1].article, None);
+  assert_eq!(ranking[2].article, Some(String::from("article1")));
+  // 章単位の参照は章ごとに分けて数える
+  let mut chapter1 = minpou.clone();
+  chapter1.chapter_number = parse_article_number("第一章");
+  let mut chapter2 = minpou.clone();
+  chapter2.chapter_number = parse_article_number("第二章");
+  let mut chapter_counter = TargetCounter::new();
+  chapter_counter.add(&[find(&chapter1), find(&chapter2)]);
+  assert_eq!(chapter_counter.ranking(None).len(), 2);
+  // 参照先の版や号が違っても，同じ項への参照は一つの行にまとめる
+  let mut paragraph1 = article90.clone();
+  paragraph1.paragraph_number = parse_article_number("第一項");
+  let mut item1 = paragraph1.clone();
+  item1.item_number = parse_article_number("第一号");
+  let mut old = paragraph1.clone();
+  old.date = Date::new_ad(2020, 4, 1);
+  old.set_patch_id(String::from("429AC0000000044"));
+  let mut paragraph_counter = TargetCounter::new();
+  paragraph_counter.add(&[find(&paragraph1), find(&item1), find(&old)]);
+  let paragraph_ranking = paragraph_counter.ranking(None);
+  assert_eq!(paragraph_ranking.len(), 1);
+  assert_eq!(paragraph_ranking[0].citation, "民法第九十条第一項");
+  assert_eq!(paragraph_ranking[0].count, 3);
+  let top = counter.ranking(Some(1));
+  assert_eq!(top.len(), 1);
+  assert_eq!(
+    target_counts_to_csv(&top),
+    "law_id,citation,article,paragraph,count\n129AC0000000089,民法第九十条,article90,,2\n"
+  );
+}
+
+#[test]
+fn check_serde_round_trip() {
+  let mut law = Law::new(
+    Date::new_ad(2024, 12, 12),
+    Some(String::from("テスト省令")),
+    String::from("506M60000100140"),
+    String::from("令和六年総務省令第百四十号"),
+    LawType::MinisterialOrdinance,
+  );
+  law.set_patch_id(String::from("507M60000100001"));
+  law.part_number = parse_article_number("第一編");
+  law.chapter_number = parse_article_number("第二章");
+  law.section_number = parse_article_number("第三節");
+  law.subsection_number = parse_article_number("第四款");
+  law.division_number = parse_article_number("第五目");
+  law.article_number = parse_article_number("第六条の二");
+  law.set_paragraph_number(parse_article_number("第七項").unwrap());
+  law.set_paragraph_text(String::from("次に掲げる規定は、適用しない。\n民法第九十条"));
+  law.set_paragraph_items(vec![
+    ItemText {
+      item_numbers: Vec::new(),
+      text: String::from("次に掲げる規定は、適用しない。"),
+    },
+    ItemText {
+      item_numbers: vec![parse_article_number("第一号")],
+      text: String::from("民法第九十条"),
+    },
+  ]);
+  law.set_egov_link(String::from("https://laws.e-gov.go.jp/law/506M60000100140"));
+  let s = serde_json::to_string(&law).unwrap();
+  let law2: Law = serde_json::from_str(&s).unwrap();
+  assert_eq!(law, law2);
+
+  let find = Find {
+    to: law.parent(),
+    from: law.clone(),
+    position: Position { start: 3, end: 9 },
+    relation: Relation::BasedOn,
+    amendment_quote: true,
+    from_item: vec![Some(parse_article_number("第二条").unwrap()), None],
+    kind: MatchKind::Douhou,
+    match_string: String::from("同令"),
+    repealed_date: None,
+  };
+  let s = serde_json::to_string(&find).unwrap();
+  let find2: Find = serde_json::from_str(&s).unwrap();
+  assert_eq!(find, find2);
+}
+
+#[test]
+fn check_multiple_names_one_law() {
+  // `register_law_names`と同じく，一つの法令を法令名と法令番号の両方で登録する
+  let mut law_map = HashMap::new();
+  for name in ["民法", "明治二十九年法律第八十九号"] {
+    let minpou = minpou(Date::new_ad(2025, 11, 26));
+    law_map.insert(String::from(name), minpou);
+  }
+  let parse = |text: &str| {
+    parse_ref_with_report(
+      &HashMap::from([(String::new(), paragraph(text))]),
+      &law_map,
+      &ParseConfig::default(),
+    )
+  };
+  // 法令番号の後のかっこ書きの法令名は，同じ参照として長い方の法令番号だけを残す
+  let (finds, report) = parse("明治二十九年法律第八十九号（民法）第九十条の規定を準用する。");
+  assert_eq!(
+    finds
+      .iter()
+      .map(|f| (f.match_string.as_str(), f.to.citation_text()))
+      .collect::<Vec<_>>(),
+    vec![("明治二十九年法律第八十九号", String::from("民法第九十条"))]
+  );
+  assert_eq!(report.stats.total(), 1);
+  // 範囲が重ならなければ，同じ法令でも別の参照として残す
+  let (finds, _) = parse("民法第九十条及び明治二十九年法律第八十九号第九十一条の規定を準用する。");
+  assert_eq!(
+    finds
+      .iter()
+      .map(|f| f.to.citation_text())
+      .collect::<Vec<_>>(),
+    vec!["民法第九十条", "民法第九十一条"]
+  );
+  // 条項番号の後の括弧書きの中の参照は残す
+  let (finds, _) = parse(
+    "民法第九十条（同法第九十一条及び明治二十九年法律第八十九号第九十二条において準用する場合を含む。）の規定を準用する。",
+  );
+  assert_eq!(
+    finds
+      .iter()
+      .map(|f| f.to.citation_text())
+      .collect::<Vec<_>>(),
+    vec!["民法第九十条", "民法第九十一条", "民法第九十二条"]
+  );
+  // 別の法令を指す括弧書きの中の法令名は残す
+  assert_eq!(
+    keep_outer_names(
+      "甲法（乙法（丙法））第一条",
+      &[
+        (Position { start: 0, end: 2 }, "a"),
+        (Position { start: 3, end: 5 }, "b"),
+        (Position { start: 6, end: 8 }, "a"),
+      ],
+      &[('（', '）')],
+    ),
+    vec![true, true, false]
   );
 }
@@ -0,0 +1,31 @@
+//! `law`と`main`のテストで使う法令
+//!
+//! `main`からは`#[path]`で同じファイルを読み込むので，型は読み込んだ側のモジュールから取り込む
+
+use super::{Date, Law, LawType};
+use japanese_law_xml_schema::article_number::parse_article_number;
+
+/// テストで使う，`date`の時点の民法
+pub(crate) fn minpou(date: Date) -> Law {
+  Law::new(
+    date,
+    Some(String::from("民法")),
+    String::from("129AC0000000089"),
+    String::from("明治二十九年法律第八十九号"),
+    LawType::Act,
+  )
+}
+
+/// テストで使う，「テスト法」の第一項として`text`を持つ段落
+pub(crate) fn paragraph(text: &str) -> Law {
+  let mut paragraph = Law::new(
+    Date::new_ad(2025, 11, 26),
+    Some(String::from("テスト法")),
+    String::from("test"),
+    String::new(),
+    LawType::Act,
+  );
+  paragraph.set_paragraph_number(parse_article_number("第一項").unwrap());
+  paragraph.set_paragraph_text(text.to_string());
+  paragraph
+}
@@ -1,2 +1,3 @@
 pub mod eli;
+pub mod error;
 pub mod law;
@@ -1,67 +1,973 @@
-use anyhow::Result;
-use clap::Parser;
-use gen_eli::law::{egov_xml_parse, parse_ref};
+use anyhow::{Context, Result};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use gen_eli::eli::{ELI_ONTOLOGY_NAMESPACE, EliOntology};
+use gen_eli::law::{
+  ContextWindow, Diagnostic, Find, Law, LawIndex, LawIndexHeader, LawVersions, LintEntry,
+  MatchStats, NO_PATCH_ID, NamedNode, ParseConfig, ParsedLaw, REFERENCE_NAMESPACE, RepealDates,
+  TargetCounter, Triple, VersionSelect, collect_repeal_dates, egov_xml_parse, group_by_from,
+  lint_finds, parse_ref_with_report, provenance_graph, target_counts_to_csv, validate_find_jsonl,
+  with_provenance,
+};
 use japanese_law_id::Date;
+use japanese_law_xml_schema::law::LawType;
+use oxrdf::{NamedOrBlankNode, Term, vocab::xsd};
 use regex::Regex;
-use std::collections::HashMap;
-use std::path::Path;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Instant;
 use tokio::fs::{self, File};
-use tokio::io::AsyncWriteExt;
-use tokio_stream::StreamExt;
-use tracing::{info, trace};
-
-async fn get_all_folder_names(path: &str) -> Result<Vec<String>> {
-  let mut dirs = tokio_stream::wrappers::ReadDirStream::new(fs::read_dir(path).await?);
-  let mut v = Vec::new();
-  while let Some(Ok(dir_entry)) = dirs.next().await {
-    if dir_entry.file_type().await?.is_dir() {
-      let s = dir_entry
+use tokio::io::{AsyncWriteExt, BufWriter};
+use tokio::sync::mpsc;
+use tokio::task::{JoinHandle, JoinSet};
+use tokio_stream::{Stream, StreamExt};
+use tracing::{info, trace, warn};
+
+#[cfg(test)]
+#[path = "law/test_fixtures.rs"]
+mod test_fixtures;
+#[cfg(test)]
+use test_fixtures::{minpou, paragraph};
+
+/// e-govデータの一つの版のフォルダ
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct LawFolder {
+  /// `<法令ID>_<日付>_<改正法令ID>`の形のフォルダ名
+  name: String,
+  law_id: String,
+  date: Date,
+  /// 改正法令IDが`NO_PATCH_ID`の場合は`None`
+  patch_id: Option<String>,
+}
+
+/// e-govデータのフォルダを，フォルダを読み進めながら順に返すストリーム
+/// `parse_folder_name`で読めないフォルダ（`.`で始まるものなど）は除く
+/// 順序はファイルシステムが返した順のままで，並べ替えない
+async fn law_folder_stream(path: &str) -> Result<impl Stream<Item = Result<LawFolder>> + use<>> {
+  let dirs = tokio_stream::wrappers::ReadDirStream::new(fs::read_dir(path).await?);
+  let stream = dirs
+    .then(|dir_entry| async move {
+      let dir_entry = dir_entry?;
+      let is_dir = dir_entry.file_type().await?.is_dir();
+      let name = dir_entry
         .file_name()
         .to_str()
         .unwrap_or_default()
         .to_string();
-      v.push(s)
+      Ok((is_dir, name))
+    })
+    .filter_map(|entry: std::io::Result<(bool, String)>| match entry {
+      Ok((false, _)) => None,
+      Ok((true, name)) => match parse_folder_name(&name) {
+        Ok(folder) => Some(Ok(folder)),
+        Err(err) => {
+          trace!("skip folder: {err}");
+          None
+        }
+      },
+      Err(err) => Some(Err(err.into())),
+    });
+  Ok(stream)
+}
+
+/// e-govデータのフォルダ一覧を名前順で取得する
+async fn get_all_folder_names(path: &str) -> Result<Vec<LawFolder>> {
+  let mut v = law_folder_stream(path)
+    .await?
+    .collect::<Result<Vec<_>>>()
+    .await?;
+  v.sort_by(|a, b| a.name.cmp(&b.name));
+  Ok(v)
+}
+
+/// 読み込むe-govデータのフォルダ
+/// `limit`がある場合は名前順で最初の`limit`個だけを返し，無い場合はフォルダを読み進めながら並べ替えずに返す
+/// 法令名の登録はどの順で行っても同じ結果になるので，並べ替えは`limit`で切るときにだけ必要になる
+async fn law_folders(
+  path: &str,
+  limit: Option<usize>,
+) -> Result<Pin<Box<dyn Stream<Item = Result<LawFolder>> + Send>>> {
+  match limit {
+    Some(limit) => {
+      let mut folders = get_all_folder_names(path).await?;
+      folders.truncate(limit);
+      Ok(Box::pin(tokio_stream::iter(folders.into_iter().map(Ok))))
     }
+    None => Ok(Box::pin(law_folder_stream(path).await?)),
   }
-  Ok(v)
+}
+
+/// 改正法令IDとして正しい形式か（英大文字と数字の15文字）
+fn is_valid_patch_id(s: &str) -> bool {
+  s.len() == NO_PATCH_ID.len()
+    && s
+      .chars()
+      .all(|c| c.is_ascii_digit() || c.is_ascii_uppercase())
+}
+
+/// `<法令ID>_<日付>_<改正法令ID>`の形のフォルダ名を法令ID・日付・改正法令IDに分ける
+/// 法令IDと改正法令IDが英数字でない場合や，日付がASCIIの8桁の数字でない場合はエラーにする
+/// 改正法令IDが`NO_PATCH_ID`の場合は`None`とし，英大文字と数字の15文字でない場合は警告して`None`とする
+fn parse_folder_name(folder_name: &str) -> Result<LawFolder> {
+  let [law_id, date_s, patch_s] = *folder_name.split('_').collect::<Vec<_>>() else {
+    anyhow::bail!("not a law folder: {folder_name}");
+  };
+  let is_alphanumeric = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_alphanumeric());
+  if !is_alphanumeric(law_id) || !is_alphanumeric(patch_s) {
+    anyhow::bail!("not a law folder: {folder_name}");
+  }
+  // 「２０２３０６１４」のような全角数字ではバイト位置で切り分けられないので，ASCIIの8桁だけを受け付ける
+  if date_s.len() != 8 || !date_s.bytes().all(|b| b.is_ascii_digit()) {
+    anyhow::bail!("invalid date in folder name: {folder_name}");
+  }
+  let year = date_s[0..4].parse::<usize>()?;
+  let month = date_s[4..6].parse::<usize>()?;
+  let day = date_s[6..8].parse::<usize>()?;
+  let patch_id = match patch_s {
+    NO_PATCH_ID => None,
+    s if is_valid_patch_id(s) => Some(s.to_string()),
+    s => {
+      warn!("invalid patch id in folder name: {s} ({folder_name})");
+      None
+    }
+  };
+  Ok(LawFolder {
+    name: folder_name.to_string(),
+    law_id: law_id.to_string(),
+    date: Date::new_ad(year, month, day),
+    patch_id,
+  })
+}
+
+#[test]
+fn check_parse_folder_name() {
+  let folder = parse_folder_name("129AC0000000089_20230614_505AC0000000053").unwrap();
+  assert_eq!(folder.name, "129AC0000000089_20230614_505AC0000000053");
+  assert_eq!(folder.law_id, "129AC0000000089");
+  assert_eq!(folder.date, Date::new_ad(2023, 6, 14));
+  assert_eq!(folder.patch_id, Some(String::from("505AC0000000053")));
+  // 改正の無い版
+  let folder = parse_folder_name("321CONSTITUTION_19470503_000000000000000").unwrap();
+  assert_eq!(folder.patch_id, None);
+  // 形式が正しくない改正法令IDは使わない
+  for name in [
+    "129AC0000000089_20230614_505ac0000000053",
+    "129AC0000000089_20230614_505AC",
+    "129AC0000000089_20230614_505AC00000000530",
+  ] {
+    let folder = parse_folder_name(name).unwrap();
+    assert_eq!(folder.law_id, "129AC0000000089");
+    assert_eq!(folder.patch_id, None);
+  }
+  assert!(parse_folder_name("129AC0000000089_2023061_505AC0000000053").is_err());
+  // 8バイトでも数字でない日付や，ASCIIでない日付
+  assert!(parse_folder_name("129AC0000000089_2023061a_505AC0000000053").is_err());
+  assert!(parse_folder_name("129AC0000000089_ab２cde_505AC0000000053").is_err());
+  assert!(parse_folder_name("129AC0000000089_20２３_505AC0000000053").is_err());
+  // 法令のフォルダでないもの
+  for name in [
+    ".129AC0000000089_20230614_505AC0000000053",
+    "129AC0000000089_20230614",
+    "129AC0000000089_20230614_505AC0000000053_tmp",
+    "_20230614_505AC0000000053",
+    ".git",
+  ] {
+    assert!(parse_folder_name(name).is_err());
+  }
+}
+
+#[tokio::test]
+async fn check_get_all_folder_names() {
+  let dir = std::env::temp_dir().join(format!("gen_eli_folder_test_{}", std::process::id()));
+  for name in [
+    "321CONSTITUTION_19470503_000000000000000",
+    "129AC0000000089_20250401_505AC0000000053",
+    ".git",
+    ".129AC0000000089_20250401_505AC0000000053",
+    "tmp",
+  ] {
+    fs::create_dir_all(dir.join(name)).await.unwrap();
+  }
+  fs::write(dir.join("all_law_list.csv"), "").await.unwrap();
+  let folders = get_all_folder_names(dir.to_str().unwrap()).await.unwrap();
+  // `--limit`のときは名前順で切り，無いときは順序を問わず全てのフォルダを返す
+  let limited = law_folders(dir.to_str().unwrap(), Some(1))
+    .await
+    .unwrap()
+    .collect::<Result<Vec<_>>>()
+    .await
+    .unwrap();
+  let mut streamed = law_folders(dir.to_str().unwrap(), None)
+    .await
+    .unwrap()
+    .collect::<Result<Vec<_>>>()
+    .await
+    .unwrap();
+  streamed.sort_by(|a, b| a.name.cmp(&b.name));
+  fs::remove_dir_all(&dir).await.unwrap();
+  assert_eq!(
+    folders.iter().map(|f| f.name.as_str()).collect::<Vec<_>>(),
+    vec![
+      "129AC0000000089_20250401_505AC0000000053",
+      "321CONSTITUTION_19470503_000000000000000"
+    ]
+  );
+  assert_eq!(limited, folders[..1]);
+  assert_eq!(streamed, folders);
+}
+
+/// RDFの出力形式
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum RdfFormat {
+  /// N-Triples（`--rdf-provenance`のときはN-Quads）
+  Ntriples,
+  /// `eli:`の接頭辞を`@context`に持つJSON-LD
+  Jsonld,
+}
+
+/// 書き出し途中のファイルのパス（`<path>.tmp`）
+/// 書き終わってから`path`に名前を変えることで，`path`にあるファイルは常に書き終わったものになる
+fn tmp_path(path: &Path) -> PathBuf {
+  let mut s = path.as_os_str().to_owned();
+  s.push(".tmp");
+  PathBuf::from(s)
+}
+
+/// 一時ファイルに書き出してから名前を変える
+async fn write_atomically(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+  let tmp = tmp_path(path);
+  fs::write(&tmp, contents).await?;
+  fs::rename(&tmp, path).await?;
+  Ok(())
+}
+
+/// JSON Lines形式のファイルに少しずつ書き足していく
+/// 書き終わるまでは一時ファイルに書き，`finish`で名前を変える
+struct JsonlWriter {
+  path: PathBuf,
+  writer: BufWriter<File>,
+}
+
+impl JsonlWriter {
+  async fn create(path: &Path) -> Result<Self> {
+    Ok(JsonlWriter {
+      path: path.to_path_buf(),
+      writer: BufWriter::new(File::create(tmp_path(path)).await?),
+    })
+  }
+
+  async fn write<T: Serialize>(&mut self, items: &[T]) -> Result<()> {
+    for item in items.iter() {
+      let s = serde_json::to_string(item)?;
+      self.writer.write_all(format!("{s}\n").as_bytes()).await?;
+    }
+    Ok(())
+  }
+
+  async fn finish(&mut self) -> Result<()> {
+    self.writer.flush().await?;
+    fs::rename(tmp_path(&self.path), &self.path).await?;
+    Ok(())
+  }
+}
+
+/// 参照情報や略称の定義をJSON Lines形式で書き出す
+async fn write_jsonl<T: Serialize>(path: &Path, items: Vec<T>) -> Result<()> {
+  let mut writer = JsonlWriter::create(path).await?;
+  writer.write(&items).await?;
+  writer.finish().await
+}
+
+#[tokio::test]
+async fn check_write_atomically() {
+  let dir = std::env::temp_dir().join(format!("gen_eli_atomic_test_{}", std::process::id()));
+  fs::create_dir_all(&dir).await.unwrap();
+  let path = dir.join("test.jsonl");
+  // 名前を変える前に止まった書き込み
+  fs::write(tmp_path(&path), "{\"to\":").await.unwrap();
+  assert!(!fs::try_exists(&path).await.unwrap());
+  // やり直すと書き終わったものだけが残る
+  write_atomically(&path, "{}\n").await.unwrap();
+  let contents = fs::read_to_string(&path).await.unwrap();
+  let tmp_exists = fs::try_exists(tmp_path(&path)).await.unwrap();
+  fs::remove_dir_all(&dir).await.unwrap();
+  assert_eq!(contents, "{}\n");
+  assert!(!tmp_exists);
+}
+
+/// トリプルを書き出す
+/// 全体を溜め込まずに法令ごとに書き出すことで，メモリ使用量を抑える
+struct RdfWriter {
+  path: PathBuf,
+  writer: BufWriter<File>,
+  format: RdfFormat,
+  /// JSON-LDで`@graph`の最初の要素を書いたかどうか
+  has_node: bool,
+}
+
+impl RdfWriter {
+  async fn create(path: &str, format: RdfFormat) -> Result<Self> {
+    let path = PathBuf::from(path);
+    let mut writer = BufWriter::new(File::create(tmp_path(&path)).await?);
+    if format == RdfFormat::Jsonld {
+      let context = serde_json::json!({ "eli": ELI_ONTOLOGY_NAMESPACE });
+      writer
+        .write_all(format!("{{\"@context\":{context},\"@graph\":[\n").as_bytes())
+        .await?;
+    }
+    Ok(RdfWriter {
+      path,
+      writer,
+      format,
+      has_node: false,
+    })
+  }
+
+  /// `graph`がある場合はそれを名前付きグラフとして書き出す
+  async fn write(&mut self, triples: &[Triple], graph: Option<&NamedNode>) -> Result<()> {
+    match (self.format, graph) {
+      (RdfFormat::Ntriples, Some(graph)) => {
+        for quad in with_provenance(triples, graph).iter() {
+          self
+            .writer
+            .write_all(format!("{quad} .\n").as_bytes())
+            .await?;
+        }
+      }
+      (RdfFormat::Ntriples, None) => {
+        for triple in triples.iter() {
+          self
+            .writer
+            .write_all(format!("{triple} .\n").as_bytes())
+            .await?;
+        }
+      }
+      (RdfFormat::Jsonld, _) => {
+        let nodes = triples.iter().map(jsonld_node).collect::<Vec<_>>();
+        let nodes = match graph {
+          Some(graph) => {
+            vec![serde_json::json!({ "@id": graph.as_str(), "@graph": nodes })]
+          }
+          None => nodes,
+        };
+        for node in nodes.iter() {
+          let separator = if self.has_node { ",\n" } else { "" };
+          self
+            .writer
+            .write_all(format!("{separator}{node}").as_bytes())
+            .await?;
+          self.has_node = true;
+        }
+      }
+    }
+    Ok(())
+  }
+
+  async fn finish(&mut self) -> Result<()> {
+    if self.format == RdfFormat::Jsonld {
+      self.writer.write_all(b"\n]}\n").await?;
+    }
+    self.writer.flush().await?;
+    fs::rename(tmp_path(&self.path), &self.path).await?;
+    Ok(())
+  }
+}
+
+/// `--rdf`で書き出す参照関係のトリプル
+/// `reify`のときは，参照ごとに`Find::triple`の後に`Find::reified_triples`を続ける
+fn reference_triples(finds: &[Find], reify: bool) -> Vec<Triple> {
+  finds
+    .iter()
+    .flat_map(|find| {
+      let mut triples = vec![find.triple()];
+      if reify {
+        triples.append(&mut find.reified_triples());
+      }
+      triples
+    })
+    .collect()
+}
+
+/// トリプルをJSON-LDのノードにする
+/// ELI Ontologyの述語は`eli:`の接頭辞で短くする
+/// リテラルは`xsd:string`以外の型を`@type`に，言語タグを`@language`に書く
+fn jsonld_node(triple: &Triple) -> serde_json::Value {
+  let predicate = triple.predicate.as_str();
+  let predicate = match predicate.strip_prefix(ELI_ONTOLOGY_NAMESPACE) {
+    Some(local) => format!("eli:{local}"),
+    None => predicate.to_string(),
+  };
+  let object = match &triple.object {
+    Term::NamedNode(node) => serde_json::json!({ "@id": node.as_str() }),
+    Term::BlankNode(node) => serde_json::json!({ "@id": node.to_string() }),
+    Term::Literal(literal) => match literal.language() {
+      Some(language) => serde_json::json!({ "@value": literal.value(), "@language": language }),
+      None if literal.datatype() == xsd::STRING => serde_json::json!({ "@value": literal.value() }),
+      None => {
+        serde_json::json!({ "@value": literal.value(), "@type": literal.datatype().as_str() })
+      }
+    },
+  };
+  let subject = match &triple.subject {
+    NamedOrBlankNode::NamedNode(node) => node.as_str().to_string(),
+    NamedOrBlankNode::BlankNode(node) => node.to_string(),
+  };
+  serde_json::json!({ "@id": subject, predicate: object })
+}
+
+#[test]
+fn check_jsonld_node() {
+  let triple = Triple::new(
+    NamedNode::new_unchecked("https://example.com/a"),
+    NamedNode::new_unchecked(format!("{ELI_ONTOLOGY_NAMESPACE}cites")),
+    NamedNode::new_unchecked("https://example.com/b"),
+  );
+  assert_eq!(
+    jsonld_node(&triple),
+    serde_json::json!({
+      "@id": "https://example.com/a",
+      "eli:cites": { "@id": "https://example.com/b" }
+    })
+  );
+  // 型や言語の付いたリテラルは，それを落とさずに書き出し，元のリテラルに戻せる
+  use oxrdf::Literal;
+  let from_jsonld = |value: &serde_json::Value| {
+    let s = value["@value"].as_str().unwrap();
+    match (value.get("@language"), value.get("@type")) {
+      (Some(language), _) => {
+        Literal::new_language_tagged_literal(s, language.as_str().unwrap()).unwrap()
+      }
+      (None, Some(datatype)) => {
+        Literal::new_typed_literal(s, NamedNode::new(datatype.as_str().unwrap()).unwrap())
+      }
+      (None, None) => Literal::new_simple_literal(s),
+    }
+  };
+  let predicate = format!("{ELI_ONTOLOGY_NAMESPACE}title");
+  for literal in [
+    Literal::new_simple_literal("民法"),
+    Literal::new_typed_literal("2023-06-14", xsd::DATE),
+    Literal::new_typed_literal("6", xsd::INTEGER),
+    Literal::new_language_tagged_literal("民法", "ja").unwrap(),
+  ] {
+    let triple = Triple::new(
+      NamedNode::new_unchecked("https://example.com/a"),
+      NamedNode::new_unchecked(&predicate),
+      literal.clone(),
+    );
+    let node = jsonld_node(&triple);
+    assert_eq!(from_jsonld(&node["eli:title"]), literal);
+  }
+  assert_eq!(
+    jsonld_node(&Triple::new(
+      NamedNode::new_unchecked("https://example.com/a"),
+      NamedNode::new_unchecked(&predicate),
+      Literal::new_typed_literal("2023-06-14", xsd::DATE),
+    ))["eli:title"],
+    serde_json::json!({
+      "@value": "2023-06-14",
+      "@type": "http://www.w3.org/2001/XMLSchema#date"
+    })
+  );
+}
+
+/// 被参照数の出力形式
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum CountsFormat {
+  Json,
+  Csv,
+}
+
+/// 法令ごとの結果ファイルの形
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum OutputShape {
+  /// 参照を一行ずつ書き出す
+  Flat,
+  /// 参照元の項ごとに，その項から出ている参照の配列を一行にまとめて書き出す
+  Nested,
+}
+
+/// 法令ごとの結果ファイルを出力フォルダの中でどう分けるか
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum Shard {
+  /// 分けずに出力フォルダの直下に置く
+  None,
+  /// 法令の種類（`act`・`co`など）ごとのフォルダに分ける
+  Type,
+  /// 版の日付の年（西暦）ごとのフォルダに分ける
+  Year,
+  /// 法令IDの先頭3文字（元号と年を表す部分）ごとのフォルダに分ける
+  #[value(alias = "law_id_prefix")]
+  LawIdPrefix,
+}
+
+/// 法令ごとの結果ファイルのパス（`<出力フォルダ>/<分けたフォルダ>/<id>.jsonl`）
+fn output_file_path(output_folder: &str, shard: Shard, id: &str, law: &Law) -> PathBuf {
+  let folder = Path::new(output_folder);
+  let folder = match shard {
+    Shard::None => folder.to_path_buf(),
+    Shard::Type => folder.join(law.law_type_str()),
+    Shard::Year => folder.join(law.get_date().get_ad_year().to_string()),
+    Shard::LawIdPrefix => folder.join(law.get_law_id().chars().take(3).collect::<String>()),
+  };
+  folder.join(id).with_extension("jsonl")
+}
+
+#[test]
+fn check_output_file_path() {
+  let law = minpou(Date::new_ad(2023, 6, 14));
+  let id = "129AC0000000089_20230614_505AC0000000053";
+  let path = |shard| output_file_path("out", shard, id, &law);
+  let file = format!("{id}.jsonl");
+  assert_eq!(path(Shard::None), Path::new("out").join(&file));
+  assert_eq!(path(Shard::Type), Path::new("out/act").join(&file));
+  assert_eq!(path(Shard::Year), Path::new("out/2023").join(&file));
+  assert_eq!(path(Shard::LawIdPrefix), Path::new("out/129").join(&file));
+}
+
+/// 参照の前後のテキストの切り出し方
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum ContextUnitArg {
+  /// 前後`--width`文字まで
+  Chars,
+  /// 参照を含む文（前後の「。」まで）．長い文は前後`--width`文字までに切り詰める
+  Sentence,
+}
+
+impl ContextUnitArg {
+  fn window(self, width: usize) -> ContextWindow {
+    match self {
+      ContextUnitArg::Chars => ContextWindow::Chars(width),
+      ContextUnitArg::Sentence => ContextWindow::Sentence(width),
+    }
+  }
+}
+
+/// 参照先の法令の版の選び方
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum VersionSelectArg {
+  /// 最新の版
+  Latest,
+  /// 参照元の法令の日付の時点で施行されている版
+  AtDate,
 }
 
 #[derive(Clone, Parser)]
-#[clap(author, version, about)]
+#[clap(author, version, about, args_conflicts_with_subcommands = true)]
+struct Cli {
+  #[command(subcommand)]
+  command: Option<Command>,
+  #[command(flatten)]
+  verbosity: clap_verbosity_flag::Verbosity,
+  // `Arg`の中に`verbosity`を入れると，`Option`のときに他の引数があっても`None`になってしまう
+  #[command(flatten)]
+  run: Option<Arg>,
+}
+
+#[derive(Clone, Subcommand)]
+enum Command {
+  /// 結果のファイルから誤検出の疑いがある参照を，前後のテキストと一緒にJSON Lines形式で標準出力に書き出す
+  ///
+  /// 一文字の法令名（「法」・「令」など）で見つけた参照と，
+  /// 「同法」・「同令」の紐付け先の法令の種類が合わない参照を対象にする。
+  /// `--no-text`で出力した結果には前後のテキストが含まれない
+  Lint {
+    /// 結果のファイル（`.jsonl`）か，それを含むフォルダのパス
+    #[clap(required = true)]
+    inputs: Vec<PathBuf>,
+    /// 参照の前後何文字までのテキストを出力するか
+    #[clap(long, default_value_t = 20)]
+    width: usize,
+    /// 前後のテキストを文字数で切るか，文で切るか
+    #[clap(long, value_enum, default_value_t = ContextUnitArg::Chars)]
+    context_unit: ContextUnitArg,
+  },
+  /// 結果のファイルの各行が参照として読めるかを確かめ，ファイルごとの結果をJSON Lines形式で標準出力に書き出す
+  ///
+  /// 取り込む前に壊れた行が無いかを確かめるのに使う。壊れた行が一つでもあれば失敗する
+  ValidateJsonl {
+    /// 結果のファイル（`.jsonl`）か，それを含むフォルダのパス
+    #[clap(required = true)]
+    inputs: Vec<PathBuf>,
+  },
+  /// `--rdf`や`--include-internal-structure`で書き出したRDFを検査し，問題をJSON Lines形式で標準出力に書き出す
+  ///
+  /// ELI Ontologyに無い述語と，IRIとして正しくない主語・述語・目的語を報告する。
+  /// N-Triples・N-Quads（`.nt`・`.nq`）とJSON-LD（`.jsonld`）を読める。問題が一つでもあれば失敗する
+  ValidateRdf {
+    /// RDFのファイルか，それを含むフォルダのパス
+    #[clap(required = true)]
+    inputs: Vec<PathBuf>,
+  },
+  /// e-govデータの法令名を登録するだけの解析を行い，法令名から法令への索引をJSON Lines形式で書き出す
+  ///
+  /// 書き出したファイルは`--law-index`で読み込める
+  BuildIndex {
+    /// e-govデータが入ったフォルダのパス
+    #[clap(short, long)]
+    egov_folder: String,
+    /// 索引を書き出すファイルのパス
+    #[clap(short, long)]
+    output: PathBuf,
+  },
+}
+
+#[derive(Clone, Args)]
 struct Arg {
   #[clap(short, long, default_value_t = 2)]
   /// jobs
   jobs: usize,
-  #[command(flatten)]
-  verbosity: clap_verbosity_flag::Verbosity,
   /// e-govデータが入ったフォルダのパス
   #[clap(short, long)]
   egov_folder: String,
   /// 結果を出力するフォルダのパス
   #[clap(short, long)]
   output_folder: String,
+  /// 参照先の条項ごとの被参照数を多い順に書き出すファイルのパス
+  #[clap(long)]
+  counts: Option<String>,
+  /// 被参照数の出力形式
+  #[clap(long, value_enum, default_value_t = CountsFormat::Json)]
+  counts_format: CountsFormat,
+  /// 被参照数を上位何件まで出力するか
+  #[clap(long)]
+  top: Option<usize>,
+  /// 条項番号のように見えて読み取れなかった箇所を警告し，一つでもあればエラーで終了する
+  #[clap(long)]
+  strict: bool,
+  /// 法令ごとの解析時間などをCSVで書き出すファイルのパス
+  #[clap(long)]
+  timing: Option<String>,
+  /// 同じ法令の中への参照（附則から本則への参照など）を出力しない
+  #[clap(long)]
+  external_only: bool,
+  /// 改正文の「」の中（改正される法令の文言の引用）にある参照を出力しない
+  #[clap(long)]
+  skip_amendment_quotes: bool,
+  /// 法令名の見つけ方（法令名・略称・同法など）ごとの参照数をJSONで書き出すファイルのパス
+  #[clap(long)]
+  match_stats: Option<String>,
+  /// 法令の構造と参照関係のトリプルをN-Triples形式で書き出すファイルのパス
+  #[clap(long)]
+  rdf: Option<String>,
+  /// `--rdf`の出力を元の法令とツールのバージョンを表す名前付きグラフに入れる（N-TriplesはN-Quads形式になる）
+  #[clap(long)]
+  rdf_provenance: bool,
+  /// `--rdf`の出力形式
+  #[clap(long, value_enum, default_value_t = RdfFormat::Ntriples)]
+  rdf_format: RdfFormat,
+  /// `--rdf`の参照関係のトリプルの後に，それを`rdf:Statement`の空白ノードとして表し，
+  /// 参照元の項の中での位置（`start`・`end`）を付けたトリプルも書き出す
+  ///
+  /// 参照ごとに7個のトリプルになるので出力は大きくなるが，参照関係に根拠となるテキストの位置を結び付けられる
+  #[clap(long)]
+  rdf_reify: bool,
+  /// 「○○法施行令」・「○○法施行規則」が見つからないときに「○○法」の名前から探す
+  #[clap(long)]
+  expand_enforcement_orders: bool,
+  /// 出力フォルダに既に結果がある法令の解析を飛ばし，中断した実行の続きから始める
+  ///
+  /// 参照が一つも無かった法令は結果のファイルが無いので再び解析する。
+  /// `--counts`などの集計は，今回解析した法令の分だけになる
+  #[clap(long)]
+  resume: bool,
+  /// 参照先の法令にどの時点の版を使うか
+  ///
+  /// latestは最新の版を，at-dateは参照元の法令の日付の時点で施行されている版を使う。
+  /// at-dateで条まで参照している場合，その時点の版に無い条（後の改正で追加された条など）は，その条を持つ版を使う。
+  /// 全ての参照の参照先の版が変わるので，出力を比べるときは同じ設定にすること
+  #[clap(long, value_enum, default_value_t = VersionSelectArg::Latest)]
+  version_select: VersionSelectArg,
+  /// 解析する法令の種類をカンマ区切りで指定する（指定しなければ全て）
+  ///
+  /// 種類はconstitution（憲法）・act（法律）・co（政令）・io（勅令）・mo（府省令）・rule（規則）・misc（その他）。
+  /// 他の種類の法令も参照先としては使う
+  #[clap(long, value_delimiter = ',', value_parser = ["constitution", "act", "co", "io", "mo", "rule", "misc"])]
+  law_type: Vec<String>,
+  /// 参照先の法令の種類がこれらのものである参照だけを出力する（指定しなければ全て）
+  ///
+  /// 種類は`--law-type`と同じで，カンマ区切りで指定する。
+  /// `--law-type`と合わせると，参照元と参照先の両方の種類で絞り込める
+  #[clap(long, value_delimiter = ',', value_parser = ["constitution", "act", "co", "io", "mo", "rule", "misc"])]
+  target_law_type: Vec<String>,
+  /// 法令ごとに，題名と参照元の条ごとの参照の一覧を人が読める形で，結果のファイルと同じフォルダの`<id>.txt`に書き出す
+  ///
+  /// 参照元の項のテキストがあれば，参照の前後のテキストも書く
+  #[clap(long)]
+  report: bool,
+  /// 法令ごとの結果ファイルを，法令の種類・年・法令IDの先頭ごとのフォルダに分けて出力する
+  #[clap(long, value_enum, default_value_t = Shard::None)]
+  shard: Shard,
+  /// 結果のファイルの形
+  ///
+  /// nestedは参照元の項ごとに`{"from": 参照元, "finds": [参照元を除いた参照]}`の形で書き出す。
+  /// `lint`はflatの結果だけを読める
+  #[clap(long, value_enum, default_value_t = OutputShape::Flat)]
+  output_shape: OutputShape,
+  /// 結果のファイルに参照元・参照先の項のテキストを含めない
+  #[clap(long)]
+  no_text: bool,
+  /// 参照の代わりに「以下「○○」という。」による略称の定義を法令ごとに書き出す
+  ///
+  /// `--counts`や`--rdf`の参照関係は出力されない
+  #[clap(long)]
+  abbreviations_only: bool,
+  /// 「○○法は、廃止する」のような規定から法令の廃止日を集め，廃止された後の版からの参照を警告する
+  ///
+  /// 廃止日は廃止する側の法令の版の日付とし，結果の`repealed_date`に書き出す。
+  /// `--law-type`で選ばなかった法令の中の廃止の規定は使わない
+  #[clap(long)]
+  repealed_laws: bool,
+  /// `build-index`で作った法令名の索引ファイルのパス
+  ///
+  /// 指定すると，法令名の登録には`-e`のフォルダの法令ではなく索引の法令を使う
+  #[clap(long)]
+  law_index: Option<PathBuf>,
+  /// 「最判平成○年○月○日」のような判例の引用も探し，JSON Lines形式で書き出すファイルのパス
+  ///
+  /// `--rdf`を指定した場合は，引用している項に引用のテキストを
+  /// `eli:cited_by_case_law_reference`で付けたトリプルも書き出す。
+  /// 裁判所と日付の並びだけで探すので，法令の参照よりも誤検出が多い
+  #[clap(long)]
+  case_law: Option<String>,
+  /// 法令ごとに，法令の構造（編・章・条・項などの`eli:has_part`・`eli:is_part_of`）と
+  /// 題名・法令番号・版の日付のトリプルを，結果のファイルと同じフォルダに`--rdf-format`の形式で書き出す
+  ///
+  /// e-govのページを公式のXMLの公開先（`eli:legal_value`・`eli:format`・`eli:media_type`）とするトリプルも加える。
+  /// ファイル名は`<id>.nt`か`<id>.jsonld`になる。`--rdf`を指定した場合は，その出力にも題名などのトリプルと，
+  /// 同じ法令の版どうしを結ぶトリプル（`eli:is_member_of`・`eli:consolidates`など）を加える
+  #[clap(long)]
+  include_internal_structure: bool,
+  /// 紐付け先が見つからずに捨てた「同法」・「同令」・「同条」などの出現を，JSON Lines形式で書き出すファイルのパス
+  ///
+  /// 直前の法令名を見つけられなかった箇所なので，法令名の検索漏れを探すのに使える
+  #[clap(long)]
+  include_unresolved: Option<String>,
+  /// 条項番号を読み取る前の法令名の候補を，項ごとにJSON Lines形式で書き出すファイルのパス
+  ///
+  /// 重複の解消や略称・「同法」の紐付けを終えた後の候補で，位置・一致した文字列・紐付けた法令IDを含む。
+  /// 参照が見つかった理由・見つからなかった理由を調べるためのもの
+  #[clap(long, value_name = "FILE")]
+  debug_candidates: Option<String>,
+  /// 見つかった参照のうち最初のN件を，前後のテキストと一緒に標準出力に書き出す
+  ///
+  /// 解析がうまくいっているかをすぐに確かめるためのもので，多くても20件までにする
+  #[clap(long, value_name = "N")]
+  sample_output: Option<usize>,
+  /// フォルダ名の順で最初のN個の法令の版だけを読み込む
+  ///
+  /// 試しに動かすためのもので，法令名の登録もこの中の法令だけで行う。
+  /// 指定しない場合はフォルダを並べ替えず，読み込んだものから順に処理する
+  #[clap(long, value_name = "N")]
+  limit: Option<usize>,
 }
 
-async fn run(args: Arg) -> Result<()> {
-  let subscriber = tracing_subscriber::fmt()
-    .with_max_level(args.verbosity)
-    .finish();
-  tracing::subscriber::set_global_default(subscriber)?;
+/// `--sample-output`で書き出す参照の数の上限
+const MAX_SAMPLE_OUTPUT: usize = 20;
 
-  info!("start");
+/// `--sample-output`で書き出す，参照の前後のテキストの文字数
+const SAMPLE_CONTEXT_WIDTH: usize = 20;
 
-  fs::create_dir_all(&args.output_folder).await?;
+/// `--sample-output`で書き出す一件分のテキスト
+/// 一行目に参照元と参照先，二行目に前後のテキストを書く
+fn sample_text(find: &Find) -> String {
+  let mut s = format!(
+    "{} -> {}",
+    find.get_from().citation_text(),
+    find.get_to().citation_text()
+  );
+  if let Some(context) = find.context(SAMPLE_CONTEXT_WIDTH) {
+    s.push_str(&format!("\n  {context}"));
+  }
+  s
+}
 
-  trace!("[START] get all folder name");
-  let folders = get_all_folder_names(&args.egov_folder).await?;
-  trace!("[END] get all folder name");
+/// `--report`で書き出す，人が読むための法令ごとの参照の一覧
+/// 題名・法令ID（ログと同じ`Law`の表記）・法令番号の後に，参照元の条ごとに見出しを付けて参照を並べる
+/// 参照元の項のテキストがあれば，参照の前後のテキストも書く
+fn report_text(law: &Law, finds: &[Find]) -> String {
+  let mut s = format!("{law}\n{}\n", law.get_law_id_text());
+  if finds.is_empty() {
+    s.push_str("\n参照なし\n");
+    return s;
+  }
+  let mut article = None;
+  for find in finds.iter() {
+    let from = find.get_from();
+    let heading = from.article_locator_text();
+    if article.as_ref() != Some(&heading) {
+      let title = if heading.is_empty() {
+        "法令全体"
+      } else {
+        &heading
+      };
+      s.push_str(&format!("\n{title}\n"));
+      article = Some(heading);
+    }
+    s.push_str(&format!(
+      "  {} → {}\n",
+      from.locator_text(),
+      find.get_to().citation_text()
+    ));
+    if let Some(context) = find.context(SAMPLE_CONTEXT_WIDTH) {
+      s.push_str(&format!("    {context}\n"));
+    }
+  }
+  s
+}
+
+/// 法令ごとの解析の途中経過
+/// 解析する側はこれを集計役に送るだけにし，集計の状態を共有しない
+#[derive(Debug)]
+enum AnalysisEvent {
+  /// 法令名の登録で，違う法令が同じ法令名を持っていた
+  NameCollision(NameCollision),
+  /// `--resume`で解析を飛ばした
+  Skipped { id: String },
+  /// 解析を終えた
+  Analyzed {
+    id: String,
+    law_id: String,
+    paragraph_count: usize,
+    find_count: usize,
+    millis: u128,
+    stats: Box<MatchStats>,
+    diagnostics: Vec<Diagnostic>,
+    /// `--counts`のときの，この法令の参照の被参照数
+    targets: Option<TargetCounter>,
+  },
+}
+
+/// 解析全体の集計結果
+#[derive(Debug, Default)]
+struct AnalysisSummary {
+  analyzed: usize,
+  skipped: usize,
+  find_count: usize,
+  match_stats: MatchStats,
+  diagnostic_count: usize,
+  target_counter: TargetCounter,
+  name_collisions: NameCollisions,
+  /// 法令ごとの解析時間などのCSVの行（ヘッダ無し）
+  timing_rows: String,
+}
+
+/// `AnalysisEvent`を受け取って集計するタスクを起動する
+/// 送り手が全て閉じると集計結果を返す
+fn spawn_aggregator(mut receiver: mpsc::Receiver<AnalysisEvent>) -> JoinHandle<AnalysisSummary> {
+  tokio::spawn(async move {
+    let mut summary = AnalysisSummary::default();
+    while let Some(event) = receiver.recv().await {
+      match event {
+        AnalysisEvent::NameCollision(collision) => {
+          trace!("law name collision: {collision:?}");
+          summary.name_collisions.insert(collision);
+        }
+        AnalysisEvent::Skipped { id } => {
+          trace!("skip analysis: {id}");
+          summary.skipped += 1;
+        }
+        AnalysisEvent::Analyzed {
+          id,
+          law_id,
+          paragraph_count,
+          find_count,
+          millis,
+          stats,
+          diagnostics,
+          targets,
+        } => {
+          trace!("analysis time: {id} {millis}ms");
+          for diagnostic in diagnostics.iter() {
+            warn!(
+              "{} {:?} {:?}: {}",
+              diagnostic.law_id, diagnostic.article, diagnostic.paragraph, diagnostic.message
+            );
+          }
+          summary.analyzed += 1;
+          summary.find_count += find_count;
+          summary.match_stats.merge(&stats);
+          summary.diagnostic_count += diagnostics.len();
+          if let Some(targets) = targets {
+            summary.target_counter.merge(targets);
+          }
+          summary.timing_rows.push_str(&format!(
+            "{law_id},{paragraph_count},{find_count},{millis}\n"
+          ));
+        }
+      }
+    }
+    summary
+  })
+}
 
+#[tokio::test]
+async fn check_aggregator() {
+  let (sender, receiver) = mpsc::channel(4);
+  let aggregator = spawn_aggregator(receiver);
+  // 複数の送り手から並行に送っても全て数えられる
+  let workers = (0..3)
+    .map(|worker| {
+      let sender = sender.clone();
+      tokio::spawn(async move {
+        let collision = NameCollision {
+          name: String::from("テスト法"),
+          law_ids: [format!("{worker}"), String::from("3")],
+        };
+        sender
+          .send(AnalysisEvent::NameCollision(collision))
+          .await
+          .unwrap();
+        for i in 0..10 {
+          let id = format!("{worker}_{i}");
+          let event = if i % 5 == 0 {
+            AnalysisEvent::Skipped { id }
+          } else {
+            let mut stats = MatchStats::new();
+            stats.full_name = 2;
+            AnalysisEvent::Analyzed {
+              id,
+              law_id: format!("{worker}"),
+              paragraph_count: 1,
+              find_count: 2,
+              millis: 0,
+              stats: Box::new(stats),
+              diagnostics: Vec::new(),
+              targets: Some(TargetCounter::new()),
+            }
+          };
+          sender.send(event).await.unwrap();
+        }
+      })
+    })
+    .collect::<Vec<_>>();
+  drop(sender);
+  for worker in workers {
+    worker.await.unwrap();
+  }
+  let summary = aggregator.await.unwrap();
+  assert_eq!(summary.skipped, 6);
+  assert_eq!(summary.analyzed, 24);
+  assert_eq!(summary.find_count, 48);
+  assert_eq!(summary.match_stats.total(), 48);
+  assert_eq!(summary.timing_rows.lines().count(), 24);
+  assert_eq!(summary.name_collisions.len(), 1);
+  assert_eq!(
+    summary.name_collisions.law_ids("テスト法"),
+    Some(vec!["0", "1", "2", "3"])
+  );
+}
+
+/// `all_law_list.csv`から，法令IDごとの法令名（法令番号・旧法令名を含む）の一覧を読み込む
+/// ファイルが無い場合は空の一覧を返す
+async fn read_law_name_list(egov_folder: &str) -> HashMap<String, Vec<String>> {
   // 法令名が法令番号だけだった時に備える
   let re_fix_name = Regex::new("^(?<name>.+号)（.+）$").unwrap();
 
   let mut law_name_list = HashMap::new();
-  if let Ok(all_law_list_text) =
-    fs::read_to_string(format!("{}/all_law_list.csv", args.egov_folder)).await
+  if let Ok(all_law_list_text) = fs::read_to_string(format!("{egov_folder}/all_law_list.csv")).await
   {
     let mut all_law_list_lines = all_law_list_text.lines();
     all_law_list_lines.next();
@@ -97,87 +1003,1196 @@ async fn run(args: Arg) -> Result<()> {
         law_name_list.insert(law_id.clone(), v);
       }
     }
+    if law_name_list.is_empty() {
+      warn!(
+        "all_law_list.csv has no entries: only the titles in the XML files are used as law names"
+      );
+    }
+  } else {
+    warn!(
+      "{}/all_law_list.csv is not found: only the titles in the XML files are used as law names",
+      egov_folder
+    );
   };
+  law_name_list
+}
+
+/// 違う法令が同じ法令名で登録されようとしたこと
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct NameCollision {
+  name: String,
+  /// 登録されていた法令と，登録しようとした法令の法令ID
+  law_ids: [String; 2],
+}
+
+/// 法令名ごとの，その法令名を持っていた法令の法令ID
+/// どの順で`NameCollision`を加えても同じ内容になる
+#[derive(Debug, Default)]
+struct NameCollisions(BTreeMap<String, BTreeSet<String>>);
+
+impl NameCollisions {
+  fn insert(&mut self, collision: NameCollision) {
+    self
+      .0
+      .entry(collision.name)
+      .or_default()
+      .extend(collision.law_ids);
+  }
+  /// 重なりのあった法令名の数
+  fn len(&self) -> usize {
+    self.0.len()
+  }
+  fn is_empty(&self) -> bool {
+    self.0.is_empty()
+  }
+  #[cfg(test)]
+  fn law_ids(&self, name: &str) -> Option<Vec<&str>> {
+    self
+      .0
+      .get(name)
+      .map(|ids| ids.iter().map(|id| id.as_str()).collect())
+  }
+  /// 法令名の順に警告する
+  fn warn(&self) {
+    for (name, law_ids) in self.0.iter() {
+      let law_ids = law_ids.iter().cloned().collect::<Vec<_>>();
+      warn!(
+        "law name {name} is used by {}: {} is used",
+        law_ids.join(", "),
+        law_ids.last().unwrap()
+      );
+    }
+  }
+}
+
+/// 法令名`name`に`law`を登録する
+/// 同じ法令どうしでは日付と改正法令IDが後の版を，違う法令どうしでは法令IDが大きい（後に制定された）法令を使う
+/// どの順で登録しても同じ結果になり，違う法令と重なった場合は`collisions`に加える
+fn register_law_name(
+  law_map: &mut HashMap<String, Law>,
+  name: String,
+  law: &Law,
+  collisions: &mut Vec<NameCollision>,
+) {
+  let key = |l: &Law| (l.get_law_id(), l.get_date(), l.get_patch_id());
+  if let Some(registered) = law_map.get(&name) {
+    if registered.get_law_id() != law.get_law_id() {
+      collisions.push(NameCollision {
+        name: name.clone(),
+        law_ids: [registered.get_law_id(), law.get_law_id()],
+      });
+    }
+    if key(registered) > key(law) {
+      return;
+    }
+  }
+  law_map.insert(name, law.clone());
+}
 
-  let mut law_map = HashMap::new();
-  let mut target_map = HashMap::new();
-  let mut folder_stream = tokio_stream::iter(folders);
+/// 法令の題名・法令番号と`law_name_list`の法令名を`law_map`に登録する
+/// どちらの法令名も`register_law_name`の規則で登録する
+/// `law_name_list`の法令名がある場合は，最後の法令名を法令名とした解析結果を返す
+fn register_law_names(
+  law_map: &mut HashMap<String, Law>,
+  law_name_list: &HashMap<String, Vec<String>>,
+  xml_file: &str,
+  title_law: &Law,
+  date: Date,
+  patch_id: &Option<String>,
+  collisions: &mut Vec<NameCollision>,
+) -> Result<Option<ParsedLaw>> {
+  let law_id = title_law.get_law_id();
+  let mut content = None;
+  if let Some(names) = law_name_list.get(&law_id) {
+    if let Some(title) = title_law.get_name()
+      && !names.contains(&title)
+    {
+      trace!("title is not in all_law_list.csv: {title} ({law_id})");
+    }
+    for law_name in names {
+      let law_content = egov_xml_parse(
+        xml_file.as_bytes(),
+        date,
+        Some(law_name.clone()),
+        law_id.clone(),
+        patch_id.clone(),
+      )?;
+      register_law_name(law_map, law_name.clone(), law_content.root(), collisions);
+      content = Some(law_content);
+    }
+  }
+  // CSVが無い場合でも参照を解決できるように，題名と法令番号も登録しておく
+  // 日本国憲法は「憲法」とだけ書かれることが多いので，その名前でも登録する
+  // 「憲法審査会」のような語の一部は`find_law_name`の規則で除く
+  let short_name =
+    (title_law.get_law_type() == LawType::Constitution).then(|| String::from("憲法"));
+  let title_names = [
+    title_law.get_name(),
+    Some(title_law.get_law_id_text()),
+    short_name,
+  ];
+  for name in title_names.into_iter().flatten() {
+    if !name.is_empty() {
+      register_law_name(law_map, name, title_law, collisions);
+    }
+  }
+  Ok(content)
+}
+
+#[test]
+fn check_register_law_name() {
+  let law = |law_id: &str, year| {
+    Law::new(
+      Date::new_ad(year, 4, 1),
+      Some(String::from("テスト法")),
+      law_id.to_string(),
+      String::new(),
+      LawType::Act,
+    )
+  };
+  let old = law("129AC0000000089", 2020);
+  let new = law("129AC0000000089", 2023);
+  let other = law("505AC0000000001", 2021);
+  // どの順で登録しても，後に制定された法令の法令名になる
+  for order in [
+    [&old, &new, &other],
+    [&other, &new, &old],
+    [&new, &other, &old],
+  ] {
+    let mut law_map = HashMap::new();
+    let mut collisions = Vec::new();
+    for law in order {
+      register_law_name(&mut law_map, String::from("テスト法"), law, &mut collisions);
+    }
+    assert_eq!(law_map["テスト法"], other);
+    assert!(!collisions.is_empty());
+  }
+  // 同じ法令では最新の版になり，重なりとはしない
+  for order in [[&old, &new], [&new, &old]] {
+    let mut law_map = HashMap::new();
+    let mut collisions = Vec::new();
+    for law in order {
+      register_law_name(&mut law_map, String::from("テスト法"), law, &mut collisions);
+    }
+    assert_eq!(law_map["テスト法"], new);
+    assert!(collisions.is_empty());
+  }
+}
+
+/// 法令名の索引を，索引ファイル（JSON Lines）の文字列にする
+/// 一行目に`LawIndexHeader`を書き，以降は一行ずつ`LawIndexEntry`を書く
+fn law_index_to_jsonl(index: &LawIndex) -> Result<String> {
+  let mut body = String::new();
+  for entry in index.entries().iter() {
+    body.push_str(&serde_json::to_string(entry)?);
+    body.push('\n');
+  }
+  let mut s = serde_json::to_string(&LawIndexHeader::new(&body))?;
+  s.push('\n');
+  s.push_str(&body);
+  Ok(s)
+}
+
+/// 索引ファイルの文字列から法令名の索引を読み込む
+/// 先頭行が無い場合や，形式・クレートの版が合わない場合，内容が書き換わっている場合はエラーにする
+fn law_index_from_jsonl(text: &str) -> Result<LawIndex> {
+  let (header, body) = text.split_once('\n').unwrap_or((text, ""));
+  if header.is_empty() {
+    anyhow::bail!("law index is empty");
+  }
+  let header: LawIndexHeader =
+    serde_json::from_str(header).context("law index has no valid header")?;
+  header.validate(body)?;
+  let mut index = LawIndex::new();
+  for line in body.lines().filter(|line| !line.is_empty()) {
+    index.insert_entry(serde_json::from_str(line)?);
+  }
+  Ok(index)
+}
+
+/// 法令の解析の前半として，e-govデータの法令名を登録した索引を作る
+/// 参照の解析は行わないので，`run`よりも早く終わる
+async fn build_law_index(egov_folder: &str) -> Result<LawIndex> {
+  let mut folders = law_folders(egov_folder, None).await?;
+  let law_name_list = read_law_name_list(egov_folder).await;
+  let mut index = LawIndex::new();
+  let mut collisions = Vec::new();
+  info!("[START] build law index");
+  while let Some(folder) = folders.next().await {
+    let LawFolder {
+      name: folder_name,
+      law_id,
+      date,
+      patch_id,
+    } = folder?;
+    let xml_path = Path::new(egov_folder)
+      .join(&folder_name)
+      .join(&folder_name)
+      .with_extension("xml");
+    let xml_file = fs::read_to_string(xml_path).await?;
+    let title_content = egov_xml_parse(
+      xml_file.as_bytes(),
+      date,
+      None,
+      law_id.clone(),
+      patch_id.clone(),
+    )?;
+    register_law_names(
+      &mut index.law_map,
+      &law_name_list,
+      &xml_file,
+      title_content.root(),
+      date,
+      &patch_id,
+      &mut collisions,
+    )?;
+    index.versions.insert(title_content.root().clone());
+    index
+      .versions
+      .insert_articles(title_content.root(), title_content.main_articles());
+  }
+  let mut name_collisions = NameCollisions::default();
+  for collision in collisions {
+    name_collisions.insert(collision);
+  }
+  name_collisions.warn();
+  info!("[END] build law index");
+  Ok(index)
+}
+
+/// 法令名の索引を作り，`output`に書き出す
+async fn build_index(egov_folder: &str, output: &Path) -> Result<()> {
+  let index = build_law_index(egov_folder).await?;
+  write_atomically(output, law_index_to_jsonl(&index)?).await?;
+  info!("write law index: {}", output.display());
+  Ok(())
+}
+
+async fn run(args: Arg) -> Result<()> {
+  info!("start");
+
+  fs::create_dir_all(&args.output_folder).await?;
+
+  let law_name_list = read_law_name_list(&args.egov_folder).await;
+
+  let mut rdf_writer = match &args.rdf {
+    Some(rdf_path) => Some(RdfWriter::create(rdf_path, args.rdf_format).await?),
+    None => None,
+  };
+
+  let law_index_loaded = args.law_index.is_some();
+  let LawIndex {
+    mut law_map,
+    versions: mut law_versions,
+  } = match &args.law_index {
+    Some(path) => {
+      info!("load law index: {}", path.display());
+      let text = fs::read_to_string(path).await?;
+      law_index_from_jsonl(&text).with_context(|| format!("{}", path.display()))?
+    }
+    None => LawIndex::new(),
+  };
+  // 法令名の重なりと解析の途中経過は集計役に送る
+  let (sender, receiver) = mpsc::channel(args.jobs.max(1) * 4);
+  let aggregator = spawn_aggregator(receiver);
+  // 解析する法令は，出力の順序が毎回同じになるようフォルダ名の順に並べる
+  let mut target_map = BTreeMap::new();
+  // `--rdf`と`--include-internal-structure`で版どうしの関係を書き出す，解析する法令の版
+  let mut rdf_versions = LawVersions::new();
+  // `--include-internal-structure`で`--rdf`に書き出す，XMLの題名を法令名とした題名などのトリプル
+  let mut title_metadata = BTreeMap::new();
+  // 法令の種類ごとの，解析する法令と`--law-type`で飛ばした法令の数
+  let mut law_type_counts: BTreeMap<String, (usize, usize)> = BTreeMap::new();
+  // フォルダは読み込んだものから順に処理し，出力の順序は`target_map`で決める
+  let mut folder_stream = law_folders(&args.egov_folder, args.limit).await?;
   info!("[START] parse law files");
-  while let Some(folder_name) = folder_stream.next().await {
-    trace!("[START] parse law: {folder_name}");
-    let law_id_and_patch_id = folder_name;
-    let mut law_id = String::new();
-    let mut date_s = String::new();
-    let mut patch_id = None;
-    for (i, s) in law_id_and_patch_id.split("_").enumerate() {
-      if i == 0 {
-        law_id = s.to_string();
-      }
-      if i == 1 {
-        date_s = s.to_string();
-      }
-      if i == 2 && s != "000000000000000" {
-        patch_id = Some(s.to_string())
-      }
-    }
-    let year = date_s[0..4].parse::<usize>()?;
-    let month = date_s[4..6].parse::<usize>()?;
-    let day = date_s[6..8].parse::<usize>()?;
+  while let Some(folder) = folder_stream.next().await {
+    let LawFolder {
+      name: law_id_and_patch_id,
+      law_id,
+      date,
+      patch_id,
+    } = folder?;
+    trace!("[START] parse law: {law_id_and_patch_id}");
     let xml_path = Path::new(&args.egov_folder)
       .join(&law_id_and_patch_id)
       .join(&law_id_and_patch_id)
       .with_extension("xml");
-    let xml_file = fs::read_to_string(xml_path).await?;
-    let mut content = None;
-    if let Some(names) = law_name_list.get(&law_id) {
-      for law_name in names {
-        let (law_content, _triple) = egov_xml_parse(
-          xml_file.as_bytes(),
-          Date::new_ad(year, month, day),
-          Some(law_name.clone()),
-          law_id.clone(),
-          patch_id.clone(),
-        )?;
-        let law_info = law_content.get("").unwrap();
-        law_map.insert(law_name.clone(), law_info.clone());
-        content = Some(law_content);
+    let xml_file = fs::read_to_string(&xml_path)
+      .await
+      .with_context(|| format!("failed to read {}", xml_path.display()))?;
+    // XMLの題名を法令名とした解析結果
+    let title_content = egov_xml_parse(
+      xml_file.as_bytes(),
+      date,
+      None,
+      law_id.clone(),
+      patch_id.clone(),
+    )
+    .with_context(|| format!("failed to parse {law_id_and_patch_id}"))?;
+    let law_type = title_content.root().law_type_str();
+    let selected = args.law_type.is_empty() || args.law_type.contains(&law_type);
+    let counts = law_type_counts.entry(law_type).or_default();
+    if selected {
+      counts.0 += 1;
+    } else {
+      counts.1 += 1;
+    }
+    if selected && rdf_writer.is_some() && args.include_internal_structure {
+      title_metadata.insert(
+        law_id_and_patch_id.clone(),
+        title_content.metadata_triples(),
+      );
+      rdf_versions.insert(title_content.root().clone());
+    }
+    let title_law = title_content.root().clone();
+    // 索引を読み込んだ場合は，解析する法令の内容を作るためだけに法令名を登録する
+    let mut unused_law_map = HashMap::new();
+    let names_map = if law_index_loaded {
+      &mut unused_law_map
+    } else {
+      &mut law_map
+    };
+    let mut collisions = Vec::new();
+    let content = register_law_names(
+      names_map,
+      &law_name_list,
+      &xml_file,
+      &title_law,
+      date,
+      &patch_id,
+      &mut collisions,
+    )
+    .with_context(|| format!("failed to register law names: {title_law}"))?;
+    if !law_index_loaded {
+      for collision in collisions {
+        sender.send(AnalysisEvent::NameCollision(collision)).await?;
       }
     }
-    if let Some(c) = content {
-      target_map.insert(law_id_and_patch_id, c);
+    // 索引を読み込んだ場合は，版と本則の条番号も索引に入っている
+    if args.version_select == VersionSelectArg::AtDate && !law_index_loaded {
+      law_versions.insert(title_law.clone());
+      law_versions.insert_articles(&title_law, title_content.main_articles());
     }
+    // 種類が選ばれなかった法令も，参照先として使えるように法令名の登録までは行う
+    if !selected {
+      trace!("skip law type: {law_id_and_patch_id}");
+      continue;
+    }
+    let content = content.unwrap_or(title_content);
+    target_map.insert(law_id_and_patch_id, content);
   }
   info!("[END] parse law files");
+  if let Some(writer) = &mut rdf_writer {
+    // 法令の構造のトリプルは，フォルダを読んだ順ではなくフォルダ名の順に書き出す
+    for (id, target) in target_map.iter() {
+      let graph = args.rdf_provenance.then(|| provenance_graph(target.root()));
+      writer.write(&target.triples, graph.as_ref()).await?;
+      if let Some(metadata) = title_metadata.get(id) {
+        writer.write(metadata, graph.as_ref()).await?;
+        writer
+          .write(&target.manifestation_triples(), graph.as_ref())
+          .await?;
+      }
+    }
+    // 全ての版が揃ってから，各版と前の版・抽象的な法令との関係を書き出す
+    for law in rdf_versions.iter() {
+      let graph = args.rdf_provenance.then(|| provenance_graph(law));
+      writer
+        .write(&rdf_versions.version_triples(law), graph.as_ref())
+        .await?;
+    }
+  }
+  for (law_type, (analyzed, skipped)) in law_type_counts.iter() {
+    info!("law type {law_type}: {analyzed} analyzed, {skipped} skipped");
+  }
+
+  let mut config = ParseConfig {
+    expand_enforcement_orders: args.expand_enforcement_orders,
+    strict: args.strict,
+    version_select: match args.version_select {
+      VersionSelectArg::Latest => VersionSelect::Latest,
+      VersionSelectArg::AtDate => VersionSelect::AtDate(law_versions),
+    },
+    case_law: args.case_law.is_some(),
+    unresolved: args.include_unresolved.is_some(),
+    debug_candidates: args.debug_candidates.is_some(),
+    abbreviations: args.abbreviations_only,
+    ..Default::default()
+  };
 
-  let mut target_stream = tokio_stream::iter(target_map);
+  if args.repealed_laws {
+    info!("[START] collect repeal dates");
+    let mut repeal_dates = RepealDates::new();
+    for target in target_map.values() {
+      collect_repeal_dates(&target.parts, &law_map, &config, &mut repeal_dates);
+    }
+    config.repeal_dates = repeal_dates;
+    info!("[END] collect repeal dates");
+  }
 
+  // `--case-law`で書き出す，全ての法令の判例の引用
+  let mut case_law_refs = Vec::new();
+  // `--include-unresolved`で書き出す，全ての法令の紐付けられなかった「同法」など
+  let mut unresolved = Vec::new();
+  // `--debug-candidates`の法令名の候補は，全ての法令の分を溜めずに法令ごとに書き出す
+  let mut candidates_writer = match &args.debug_candidates {
+    Some(candidates_path) => Some(JsonlWriter::create(Path::new(candidates_path)).await?),
+    None => None,
+  };
+  let mut candidate_count = 0;
+  // `--sample-output`でまだ書き出せる参照の数
+  let mut sample_remaining = args.sample_output.unwrap_or(0).min(MAX_SAMPLE_OUTPUT);
+  // 参照の解析は`--jobs`個まで並行に行い，書き出しはフォルダ名の順にここで行う
+  // 先に終わった解析は，それより前の法令の解析が終わるまで`finished`に置いておく
+  let law_map = Arc::new(law_map);
+  let config = Arc::new(config);
+  let jobs = args.jobs.max(1);
+  let total = target_map.len();
+  let mut done = 0;
+  let mut targets = target_map.into_iter();
+  let mut analyses = JoinSet::new();
+  let mut finished = BTreeMap::new();
+  let mut spawned = 0;
+  let mut next_index = 0;
   info!("[START] analysis");
-  while let Some((id, target)) = target_stream.next().await {
-    trace!("[START] analysis: {id}",);
-    let finds = parse_ref(&target, &law_map);
+  loop {
+    while analyses.len() < jobs
+      && finished.len() < jobs * 4
+      && let Some((id, target)) = targets.next()
+    {
+      let output_file_path = output_file_path(&args.output_folder, args.shard, &id, target.root());
+      if args.resume && fs::try_exists(&output_file_path).await? {
+        sender.send(AnalysisEvent::Skipped { id }).await?;
+        done += 1;
+        continue;
+      }
+      trace!("[START] analysis: {id}",);
+      let law_map = Arc::clone(&law_map);
+      let config = Arc::clone(&config);
+      let index = spawned;
+      spawned += 1;
+      analyses.spawn_blocking(move || {
+        let start = Instant::now();
+        let (finds, report) = parse_ref_with_report(&target.parts, &law_map, &config);
+        let millis = start.elapsed().as_millis();
+        (index, id, target, output_file_path, finds, report, millis)
+      });
+    }
+    let Some(analysis) = finished.remove(&next_index) else {
+      let Some(analysis) = analyses.join_next().await else {
+        break;
+      };
+      let analysis = analysis?;
+      finished.insert(analysis.0, analysis);
+      continue;
+    };
+    next_index += 1;
+    done += 1;
+    trace!("progress: {done}/{total}");
+    let (_, id, target, output_file_path, finds, mut report, millis) = analysis;
+    if args.include_internal_structure {
+      let extension = match args.rdf_format {
+        RdfFormat::Ntriples => "nt",
+        RdfFormat::Jsonld => "jsonld",
+      };
+      let structure_path = output_file_path.with_extension(extension);
+      if let Some(dir) = structure_path.parent() {
+        fs::create_dir_all(dir).await?;
+      }
+      let mut writer =
+        RdfWriter::create(&structure_path.to_string_lossy(), args.rdf_format).await?;
+      let graph = args.rdf_provenance.then(|| provenance_graph(target.root()));
+      writer.write(&target.triples, graph.as_ref()).await?;
+      writer
+        .write(&target.metadata_triples(), graph.as_ref())
+        .await?;
+      writer
+        .write(&target.manifestation_triples(), graph.as_ref())
+        .await?;
+      writer.finish().await?;
+    }
+    if let Some(writer) = &mut rdf_writer
+      && !report.case_law_refs.is_empty()
+    {
+      let triples = report
+        .case_law_refs
+        .iter()
+        .map(|r| r.triple())
+        .collect::<Vec<_>>();
+      let graph = args.rdf_provenance.then(|| provenance_graph(target.root()));
+      writer.write(&triples, graph.as_ref()).await?;
+    }
+    case_law_refs.append(&mut report.case_law_refs);
+    unresolved.append(&mut report.unresolved);
+    if let Some(writer) = &mut candidates_writer {
+      candidate_count += report.candidates.len();
+      writer.write(&report.candidates).await?;
+    }
+    for find in finds.iter() {
+      if let Some(date) = find.get_repealed_date() {
+        warn!(
+          "{id}: {} cites a law repealed on {date:?}: {}",
+          find.get_from().citation_text(),
+          find.get_to().citation_text()
+        );
+      }
+    }
+    let paragraph_count = target
+      .parts
+      .values()
+      .filter(|l| l.get_paragraph_text().is_some())
+      .count();
+    let mut event = AnalysisEvent::Analyzed {
+      id: id.clone(),
+      law_id: target.root().get_law_id(),
+      paragraph_count,
+      find_count: finds.len(),
+      millis,
+      stats: Box::new(report.stats),
+      diagnostics: report.diagnostics,
+      targets: None,
+    };
+    if args.abbreviations_only {
+      sender.send(event).await?;
+      if !report.abbreviations.is_empty() {
+        if let Some(dir) = output_file_path.parent() {
+          fs::create_dir_all(dir).await?;
+        }
+        write_jsonl(&output_file_path, report.abbreviations).await?;
+      }
+      trace!("[END] analysis: {id}",);
+      continue;
+    }
+    let finds = finds
+      .into_iter()
+      .filter(|f| !(args.external_only && f.is_self_reference()))
+      .filter(|f| !(args.skip_amendment_quotes && f.is_amendment_quote()))
+      .filter(|f| {
+        args.target_law_type.is_empty() || args.target_law_type.contains(&f.get_to_law_type_str())
+      })
+      .inspect(|f| {
+        if sample_remaining > 0 {
+          sample_remaining -= 1;
+          println!("{}", sample_text(f));
+        }
+      })
+      .map(|f| if args.no_text { f.without_text() } else { f })
+      .collect::<Vec<_>>();
+    if args.counts.is_some()
+      && let AnalysisEvent::Analyzed { targets, .. } = &mut event
+    {
+      let mut counter = TargetCounter::new();
+      counter.add(&finds);
+      *targets = Some(counter);
+    }
+    sender.send(event).await?;
+    if let Some(writer) = &mut rdf_writer {
+      let triples = reference_triples(&finds, args.rdf_reify);
+      let graph = args.rdf_provenance.then(|| provenance_graph(target.root()));
+      writer.write(&triples, graph.as_ref()).await?;
+    }
+    if args.report {
+      let report_path = output_file_path.with_extension("txt");
+      if let Some(dir) = report_path.parent() {
+        fs::create_dir_all(dir).await?;
+      }
+      write_atomically(&report_path, report_text(target.root(), &finds))
+        .await
+        .with_context(|| format!("failed to write report: {}", target.root()))?;
+    }
     if !finds.is_empty() {
       trace!("[START] write: {id}");
-      let output_file_path = Path::new(&args.output_folder)
-        .join(&id)
-        .with_extension("jsonl");
-      let mut output_file = File::create(output_file_path).await?;
-      let mut find_stream = tokio_stream::iter(finds);
-      while let Some(result) = find_stream.next().await {
-        let s = serde_json::to_string(&result)?;
-        output_file.write_all(format!("{s}\n").as_bytes()).await?;
-      }
-      output_file.flush().await?;
+      if let Some(dir) = output_file_path.parent() {
+        fs::create_dir_all(dir).await?;
+      }
+      match args.output_shape {
+        OutputShape::Flat => write_jsonl(&output_file_path, finds).await,
+        OutputShape::Nested => write_jsonl(&output_file_path, group_by_from(finds)).await,
+      }
+      .with_context(|| format!("failed to write finds: {}", target.root()))?;
       trace!("[END] write: {id}");
     }
     trace!("[END] analysis: {id}",);
   }
-  info!("[END] analysis");
+  // 送り手を閉じて集計を終わらせる
+  drop(sender);
+  let summary = aggregator.await?;
+  info!(
+    "[END] analysis: {} analyzed, {} skipped, {} finds",
+    summary.analyzed, summary.skipped, summary.find_count
+  );
+  if !summary.name_collisions.is_empty() {
+    summary.name_collisions.warn();
+    info!(
+      "law names used by more than one law: {}",
+      summary.name_collisions.len()
+    );
+  }
+
+  if let Some(writer) = &mut rdf_writer {
+    writer.finish().await?;
+  }
+
+  if let Some(case_law_path) = &args.case_law {
+    info!("case law references: {}", case_law_refs.len());
+    write_jsonl(Path::new(case_law_path), case_law_refs).await?;
+  }
+
+  if let Some(unresolved_path) = &args.include_unresolved {
+    info!("unresolved references: {}", unresolved.len());
+    write_jsonl(Path::new(unresolved_path), unresolved).await?;
+  }
+
+  if let Some(writer) = &mut candidates_writer {
+    info!("law name candidates: {candidate_count}");
+    writer.finish().await?;
+  }
+
+  if let Some(counts_path) = &args.counts {
+    trace!("[START] write counts: {counts_path}");
+    let ranking = summary.target_counter.ranking(args.top);
+    let s = match args.counts_format {
+      CountsFormat::Json => serde_json::to_string_pretty(&ranking)?,
+      CountsFormat::Csv => target_counts_to_csv(&ranking),
+    };
+    write_atomically(Path::new(counts_path), s).await?;
+    trace!("[END] write counts: {counts_path}");
+  }
+
+  if let Some(timing_path) = &args.timing {
+    let timing_csv = format!(
+      "law_id,paragraph_count,find_count,millis\n{}",
+      summary.timing_rows
+    );
+    write_atomically(Path::new(timing_path), timing_csv).await?;
+  }
+
+  if let Some(match_stats_path) = &args.match_stats {
+    info!(
+      "matches: {} (single char: {})",
+      summary.match_stats.total(),
+      summary.match_stats.single_char
+    );
+    write_atomically(
+      Path::new(match_stats_path),
+      serde_json::to_string_pretty(&summary.match_stats)?,
+    )
+    .await?;
+  }
+
+  if summary.diagnostic_count > 0 {
+    anyhow::bail!(
+      "{} article numbers could not be parsed",
+      summary.diagnostic_count
+    );
+  }
 
   Ok(())
 }
 
+/// `inputs`のファイルと，フォルダの中（下のフォルダも含む）の拡張子が`extensions`のどれかのファイルを名前順に並べる
+fn collect_files(inputs: &[PathBuf], extensions: &[&str]) -> Result<Vec<PathBuf>> {
+  let mut files = Vec::new();
+  for input in inputs.iter() {
+    if input.is_dir() {
+      let mut entries = std::fs::read_dir(input)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+      entries.retain(|path| {
+        path.is_dir()
+          || path
+            .extension()
+            .is_some_and(|ext| extensions.iter().any(|e| ext == *e))
+      });
+      entries.sort();
+      files.append(&mut collect_files(&entries, extensions)?);
+    } else {
+      files.push(input.clone());
+    }
+  }
+  Ok(files)
+}
+
+/// 結果のファイルを読み，誤検出の疑いがある参照を集める
+fn lint_files(inputs: &[PathBuf], window: ContextWindow) -> Result<Vec<LintEntry>> {
+  let mut entries = Vec::new();
+  for path in collect_files(inputs, &["jsonl"])?.iter() {
+    let finds = std::fs::read_to_string(path)?
+      .lines()
+      .filter(|line| !line.is_empty())
+      .map(serde_json::from_str::<Find>)
+      .collect::<serde_json::Result<Vec<_>>>()
+      .with_context(|| format!("failed to read finds: {}", path.display()))?;
+    entries.append(&mut lint_finds(&finds, window));
+  }
+  Ok(entries)
+}
+
+/// `validate-rdf`で見つけた問題の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum RdfIssueReason {
+  /// ELI Ontologyに無い述語
+  UnknownPredicate,
+  /// IRIとして正しくない主語・述語・目的語
+  MalformedIri,
+  /// トリプルとして読めない行やノード
+  Syntax,
+}
+
+/// `validate-rdf`で見つけた問題
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+struct RdfIssue {
+  path: String,
+  /// N-Triples・N-Quadsでは行番号，JSON-LDでは`@graph`の中のノードの番号（どちらも1から）
+  line: usize,
+  reason: RdfIssueReason,
+  /// 問題のあったIRIや行
+  value: String,
+}
+
+/// N-Triples・N-Quadsの項を一つ読み，IRIであればそれを，空白ノードやリテラルであれば`None`を返す
+fn nt_term(s: &str) -> Option<(Option<String>, &str)> {
+  let s = s.trim_start();
+  if let Some(rest) = s.strip_prefix('<') {
+    let end = rest.find('>')?;
+    return Some((Some(rest[..end].to_string()), &rest[end + 1..]));
+  }
+  if s.starts_with("_:") {
+    let end = s.find(char::is_whitespace).unwrap_or(s.len());
+    return Some((None, &s[end..]));
+  }
+  let rest = s.strip_prefix('"')?;
+  // エスケープされていない閉じ引用符を探す
+  let mut escaped = false;
+  let end = rest.char_indices().find_map(|(i, c)| {
+    let close = c == '"' && !escaped;
+    escaped = c == '\\' && !escaped;
+    close.then_some(i)
+  })?;
+  let rest = &rest[end + 1..];
+  let rest = match rest.strip_prefix("^^") {
+    Some(datatype) => nt_term(datatype)?.1,
+    None if rest.starts_with('@') => &rest[rest.find(char::is_whitespace).unwrap_or(rest.len())..],
+    None => rest,
+  };
+  Some((None, rest))
+}
+
+/// 主語・述語・目的語のIRIを検査する
+/// 空白ノードやリテラルは`None`で渡す
+fn check_triple_iris(
+  subject: Option<&str>,
+  predicate: &str,
+  object: Option<&str>,
+  mut issue: impl FnMut(RdfIssueReason, &str),
+) {
+  for iri in [subject, Some(predicate), object].into_iter().flatten() {
+    if NamedNode::new(iri).is_err() {
+      issue(RdfIssueReason::MalformedIri, iri);
+    }
+  }
+  if NamedNode::new(predicate).is_ok() && !is_known_predicate(predicate) {
+    issue(RdfIssueReason::UnknownPredicate, predicate);
+  }
+}
+
+/// 書き出す述語かどうか
+/// ELI Ontologyの述語と，`--rdf-reify`で使う`rdf:Statement`・参照の位置の述語
+fn is_known_predicate(predicate: &str) -> bool {
+  use oxrdf::vocab::rdf;
+  EliOntology::from_uri(predicate).is_some()
+    || [rdf::TYPE, rdf::SUBJECT, rdf::PREDICATE, rdf::OBJECT]
+      .iter()
+      .any(|p| p.as_str() == predicate)
+    || predicate
+      .strip_prefix(REFERENCE_NAMESPACE)
+      .is_some_and(|local| ["start", "end"].contains(&local))
+}
+
+/// N-Triples・N-Quadsのテキストを検査する
+fn validate_ntriples(path: &str, text: &str) -> Vec<RdfIssue> {
+  let mut issues = Vec::new();
+  for (i, line) in text.lines().enumerate() {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+      continue;
+    }
+    let mut issue = |reason, value: &str| {
+      issues.push(RdfIssue {
+        path: path.to_string(),
+        line: i + 1,
+        reason,
+        value: value.to_string(),
+      })
+    };
+    let terms = nt_term(line).and_then(|(subject, rest)| {
+      let (predicate, rest) = nt_term(rest)?;
+      let (object, rest) = nt_term(rest)?;
+      // N-Quadsの場合はグラフ名が続く
+      let rest = match rest.trim_start() {
+        r if r.starts_with('<') || r.starts_with("_:") => nt_term(r)?.1,
+        r => r,
+      };
+      (rest.trim() == ".").then_some((subject, predicate?, object))
+    });
+    match terms {
+      Some((subject, predicate, object)) => check_triple_iris(
+        subject.as_deref(),
+        &predicate,
+        object.as_deref(),
+        &mut issue,
+      ),
+      None => issue(RdfIssueReason::Syntax, line),
+    }
+  }
+  issues
+}
+
+/// `RdfWriter`で書き出したJSON-LDを検査する
+/// `eli:`の接頭辞は名前空間に戻し，`--rdf-provenance`の名前付きグラフの中のノードも調べる
+fn validate_jsonld(path: &str, text: &str) -> Vec<RdfIssue> {
+  let mut issues = Vec::new();
+  let mut issue = |line, reason, value: &str| {
+    issues.push(RdfIssue {
+      path: path.to_string(),
+      line,
+      reason,
+      value: value.to_string(),
+    })
+  };
+  let Ok(document) = serde_json::from_str::<serde_json::Value>(text) else {
+    issue(0, RdfIssueReason::Syntax, "");
+    return issues;
+  };
+  let mut nodes = Vec::new();
+  for node in document["@graph"].as_array().into_iter().flatten() {
+    match node.get("@graph").and_then(|g| g.as_array()) {
+      Some(graph) => nodes.extend(graph),
+      None => nodes.push(node),
+    }
+  }
+  for (i, node) in nodes.iter().enumerate() {
+    let Some(fields) = node.as_object() else {
+      issue(i + 1, RdfIssueReason::Syntax, &node.to_string());
+      continue;
+    };
+    let subject = fields.get("@id").and_then(|id| id.as_str());
+    let Some(subject) = subject else {
+      issue(i + 1, RdfIssueReason::Syntax, &node.to_string());
+      continue;
+    };
+    let subject = (!subject.starts_with("_:")).then_some(subject);
+    for (key, value) in fields.iter().filter(|(key, _)| *key != "@id") {
+      let predicate = match key.strip_prefix("eli:") {
+        Some(local) => format!("{ELI_ONTOLOGY_NAMESPACE}{local}"),
+        None => key.clone(),
+      };
+      let object = value
+        .get("@id")
+        .and_then(|id| id.as_str())
+        .filter(|id| !id.starts_with("_:"));
+      check_triple_iris(subject, &predicate, object, |reason, value| {
+        issue(i + 1, reason, value)
+      });
+    }
+  }
+  issues
+}
+
+/// RDFのファイルを読み，述語とIRIの問題を集める
+fn validate_rdf_files(inputs: &[PathBuf]) -> Result<Vec<RdfIssue>> {
+  let mut issues = Vec::new();
+  for path in collect_files(inputs, &["nt", "nq", "jsonld"])?.iter() {
+    let text = std::fs::read_to_string(path)?;
+    let name = path.display().to_string();
+    if path.extension().is_some_and(|ext| ext == "jsonld") {
+      issues.append(&mut validate_jsonld(&name, &text));
+    } else {
+      issues.append(&mut validate_ntriples(&name, &text));
+    }
+  }
+  Ok(issues)
+}
+
+#[tokio::test]
+async fn check_rdf_reify() {
+  use gen_eli::law::parse_ref;
+  let minpou = minpou(Date::new_ad(2023, 6, 14));
+  let law_map = HashMap::from([(String::from("民法"), minpou)]);
+  let paragraph = paragraph("この法律は、民法第九十条の特例を定める。");
+  let finds = parse_ref(&HashMap::from([(String::new(), paragraph)]), &law_map);
+  let finds = &finds[1..];
+  assert_eq!(reference_triples(finds, false), vec![finds[0].triple()]);
+  let dir = std::env::temp_dir().join(format!("gen_eli_rdf_reify_test_{}", std::process::id()));
+  fs::create_dir_all(&dir).await.unwrap();
+  let mut texts = Vec::new();
+  for (name, format) in [
+    ("reify.nt", RdfFormat::Ntriples),
+    ("reify.jsonld", RdfFormat::Jsonld),
+  ] {
+    let path = dir.join(name);
+    let mut writer = RdfWriter::create(&path.to_string_lossy(), format)
+      .await
+      .unwrap();
+    writer
+      .write(&reference_triples(finds, true), None)
+      .await
+      .unwrap();
+    writer.finish().await.unwrap();
+    texts.push(fs::read_to_string(&path).await.unwrap());
+  }
+  fs::remove_dir_all(&dir).await.unwrap();
+  let lines = texts[0].lines().collect::<Vec<_>>();
+  assert_eq!(lines.len(), 7);
+  assert_eq!(lines[0], format!("{} .", finds[0].triple()));
+  // 参照関係の後に，同じ空白ノードを主語とするトリプルが続く
+  let statement = lines[1].split(' ').next().unwrap();
+  assert!(statement.starts_with("_:"));
+  assert!(lines[1..].iter().all(|line| line.starts_with(statement)));
+  assert_eq!(
+    lines[1],
+    format!(
+      "{statement} <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> \
+       <http://www.w3.org/1999/02/22-rdf-syntax-ns#Statement> ."
+    )
+  );
+  // 「民法第九十条」は6文字目から
+  assert_eq!(
+    lines[5],
+    format!(
+      "{statement} <{REFERENCE_NAMESPACE}start> \"6\"^^<http://www.w3.org/2001/XMLSchema#integer> ."
+    )
+  );
+  // 検査で未知の述語として扱わない
+  assert!(validate_ntriples("reify.nt", &texts[0]).is_empty());
+  assert!(validate_jsonld("reify.jsonld", &texts[1]).is_empty());
+}
+
+#[tokio::test]
+async fn check_validate_rdf_files() {
+  let parsed = egov_xml_parse(
+    include_bytes!("../tests/fixtures/129AC0000000089_20230614.xml"),
+    Date::new_ad(2023, 6, 14),
+    None,
+    String::from("129AC0000000089"),
+    Some(String::from("505AC0000000053")),
+  )
+  .unwrap();
+  let dir = std::env::temp_dir().join(format!("gen_eli_validate_rdf_test_{}", std::process::id()));
+  fs::create_dir_all(&dir).await.unwrap();
+  // 出力した全ての形式のRDFに問題が無い
+  let graph = provenance_graph(parsed.root());
+  for (name, format, graph) in [
+    ("law.nt", RdfFormat::Ntriples, None),
+    ("law.nq", RdfFormat::Ntriples, Some(&graph)),
+    ("law.jsonld", RdfFormat::Jsonld, None),
+    ("graph.jsonld", RdfFormat::Jsonld, Some(&graph)),
+  ] {
+    let path = dir.join(name);
+    let mut writer = RdfWriter::create(&path.to_string_lossy(), format)
+      .await
+      .unwrap();
+    for triples in [
+      parsed.triples.clone(),
+      parsed.metadata_triples(),
+      parsed.manifestation_triples(),
+    ] {
+      writer.write(&triples, graph).await.unwrap();
+    }
+    writer.finish().await.unwrap();
+  }
+  let clean = validate_rdf_files(std::slice::from_ref(&dir));
+  // 綴り間違いの述語，正しくないIRI，読めない行
+  let eli = ELI_ONTOLOGY_NAMESPACE;
+  std::fs::write(
+    dir.join("bad.nt"),
+    format!(
+      "<https://example.com/a> <{eli}correccts> <https://example.com/b> .\n\
+       <https://example.com/a b> <{eli}cites> \"x\\\"y\"@ja .\n\
+       <https://example.com/a> <{eli}cites>\n"
+    ),
+  )
+  .unwrap();
+  let issues = validate_rdf_files(&[dir.join("bad.nt")]);
+  fs::remove_dir_all(&dir).await.unwrap();
+  assert_eq!(clean.unwrap(), Vec::new());
+  let issues = issues
+    .unwrap()
+    .into_iter()
+    .map(|i| (i.line, i.reason, i.value))
+    .collect::<Vec<_>>();
+  assert_eq!(
+    issues,
+    vec![
+      (
+        1,
+        RdfIssueReason::UnknownPredicate,
+        format!("{eli}correccts")
+      ),
+      (
+        2,
+        RdfIssueReason::MalformedIri,
+        String::from("https://example.com/a b")
+      ),
+      (
+        3,
+        RdfIssueReason::Syntax,
+        format!("<https://example.com/a> <{eli}cites>")
+      ),
+    ]
+  );
+}
+
+#[test]
+fn check_sample_text() {
+  use gen_eli::law::parse_ref;
+  let minpou = minpou(Date::new_ad(2023, 6, 14));
+  let law_map = HashMap::from([(String::from("民法"), minpou)]);
+  let paragraph = paragraph("民法第九十条の規定による。");
+  let finds = parse_ref(&HashMap::from([(String::new(), paragraph)]), &law_map);
+  let text = sample_text(&finds[0]);
+  let (first, second) = text.split_once('\n').unwrap();
+  assert!(first.contains(" -> 民法第九十条"), "{first}");
+  assert_eq!(second, "  【民法第九十条】の規定による。");
+  // テキストを持たない場合は一行だけ
+  assert_eq!(sample_text(&finds[0].without_text()), first);
+}
+
+#[test]
+fn check_report_text() {
+  use gen_eli::law::{JoukouRef, parse_ref};
+  use japanese_law_xml_schema::article_number::parse_article_number;
+  let minpou = minpou(Date::new_ad(2023, 6, 14));
+  let law_map = HashMap::from([(String::from("民法"), minpou)]);
+  let law = Law::new(
+    Date::new_ad(2023, 6, 14),
+    Some(String::from("テスト法")),
+    String::from("test"),
+    String::from("令和五年法律第一号"),
+    LawType::Act,
+  );
+  let mut target = HashMap::new();
+  for (key, article, paragraph, text) in [
+    ("a", "第一条", "第一項", "民法第九十条の規定による。"),
+    ("b", "第一条", "第二項", "民法第九十一条の規定による。"),
+    ("c", "第二条", "第一項", "民法第九十二条の規定による。"),
+  ] {
+    let mut p = law.with_locator(JoukouRef {
+      article_number: parse_article_number(article),
+      paragraph_number: parse_article_number(paragraph),
+      ..Default::default()
+    });
+    p.set_paragraph_text(String::from(text));
+    target.insert(String::from(key), p);
+  }
+  let mut finds = parse_ref(&target, &law_map);
+  finds.sort_by_key(|f| f.get_from().locator_text());
+  assert_eq!(
+    report_text(&law, &finds),
+    concat!(
+      "テスト法（test）\n",
+      "令和五年法律第一号\n",
+      "\n",
+      "第一条\n",
+      "  第一条第一項 → 民法第九十条\n",
+      "    【民法第九十条】の規定による。\n",
+      "  第一条第二項 → 民法第九十一条\n",
+      "    【民法第九十一条】の規定による。\n",
+      "\n",
+      "第二条\n",
+      "  第二条第一項 → 民法第九十二条\n",
+      "    【民法第九十二条】の規定による。\n",
+    )
+  );
+  assert!(report_text(&law, &[]).ends_with("\n参照なし\n"));
+}
+
+#[test]
+fn check_lint_files() {
+  use gen_eli::law::{LintReason, parse_ref};
+  let minpou = minpou(Date::new_ad(2023, 6, 14));
+  let law_map = HashMap::from([(String::from("民法"), minpou)]);
+  let paragraph = paragraph("民法第九十条及び同令第一条");
+  let finds = parse_ref(&HashMap::from([(String::new(), paragraph)]), &law_map);
+  let dir = std::env::temp_dir().join(format!("gen_eli_lint_test_{}", std::process::id()));
+  std::fs::create_dir_all(dir.join("act")).unwrap();
+  let jsonl = finds
+    .iter()
+    .map(|find| format!("{}\n", serde_json::to_string(find).unwrap()))
+    .collect::<String>();
+  std::fs::write(dir.join("act/test.jsonl"), jsonl).unwrap();
+  // 結果のファイル以外は読まない
+  std::fs::write(dir.join("act/test.jsonl.tmp"), "{").unwrap();
+  let entries = lint_files(std::slice::from_ref(&dir), ContextWindow::Chars(4));
+  std::fs::remove_dir_all(&dir).unwrap();
+  let entries = entries.unwrap();
+  assert_eq!(entries.len(), 1);
+  assert_eq!(entries[0].reason, LintReason::DouhouMismatch);
+  assert_eq!(
+    entries[0].context.as_deref(),
+    Some("十条及び【同令第一条】")
+  );
+}
+
 fn main() -> Result<()> {
-  let args = Arg::parse();
+  let cli = Cli::parse();
+  let subscriber = tracing_subscriber::fmt()
+    .with_max_level(cli.verbosity)
+    .finish();
+  tracing::subscriber::set_global_default(subscriber)?;
+  let args = match (cli.command, cli.run) {
+    (
+      Some(Command::Lint {
+        inputs,
+        width,
+        context_unit,
+      }),
+      _,
+    ) => {
+      for entry in lint_files(&inputs, context_unit.window(width))?.iter() {
+        println!("{}", serde_json::to_string(entry)?);
+      }
+      return Ok(());
+    }
+    (Some(Command::ValidateJsonl { inputs }), _) => {
+      let mut invalid = 0;
+      for path in collect_files(&inputs, &["jsonl"])?.iter() {
+        let report = validate_find_jsonl(path)?;
+        invalid += report.invalid.len();
+        println!("{}", serde_json::to_string(&report)?);
+      }
+      if invalid > 0 {
+        anyhow::bail!("{invalid} malformed records found");
+      }
+      return Ok(());
+    }
+    (Some(Command::ValidateRdf { inputs }), _) => {
+      let issues = validate_rdf_files(&inputs)?;
+      for issue in issues.iter() {
+        println!("{}", serde_json::to_string(issue)?);
+      }
+      if !issues.is_empty() {
+        anyhow::bail!("{} problems found in RDF", issues.len());
+      }
+      return Ok(());
+    }
+    (
+      Some(Command::BuildIndex {
+        egov_folder,
+        output,
+      }),
+      _,
+    ) => {
+      return tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?
+        .block_on(async { build_index(&egov_folder, &output).await });
+    }
+    (None, Some(args)) => args,
+    (None, None) => anyhow::bail!("--egov-folder and --output-folder are required"),
+  };
   let threds = args.jobs;
   tokio::runtime::Builder::new_multi_thread()
     .worker_threads(threds)
@@ -186,3 +2201,43 @@ fn main() -> Result<()> {
     .block_on(async { run(args).await })?;
   Ok(())
 }
+
+#[test]
+fn check_law_index() {
+  use gen_eli::law::LAW_INDEX_SCHEMA_VERSION;
+  use japanese_law_xml_schema::article_number::parse_article_number;
+  let version = |year| minpou(Date::new_ad(year, 4, 1));
+  let mut index = LawIndex::new();
+  index.law_map.insert(String::from("民法"), version(2025));
+  index
+    .law_map
+    .insert(String::from("明治二十九年法律第八十九号"), version(2025));
+  index.versions.insert(version(2025));
+  index.versions.insert(version(2020));
+  // 版ごとの本則の条番号も索引に入れる
+  let articles = ["第三条の四", "第三条"].map(|s| parse_article_number(s).unwrap());
+  index.versions.insert_articles(&version(2025), articles);
+  let text = law_index_to_jsonl(&index).unwrap();
+  assert_eq!(text.lines().count(), 5);
+  assert_eq!(law_index_from_jsonl(&text).unwrap(), index);
+  // 同じ内容からは同じ索引ができる
+  assert_eq!(law_index_to_jsonl(&index).unwrap(), text);
+  // 形式やクレートの版が違う索引，内容が書き換わった索引，先頭行の無い索引は読み込まない
+  let (header, body) = text.split_once('\n').unwrap();
+  let old_schema = text.replacen(
+    &format!("\"schema_version\":{LAW_INDEX_SCHEMA_VERSION}"),
+    "\"schema_version\":0",
+    1,
+  );
+  assert!(law_index_from_jsonl(&old_schema).is_err());
+  let old_crate = text.replacen(
+    &format!("\"crate_version\":\"{}\"", env!("CARGO_PKG_VERSION")),
+    "\"crate_version\":\"0.0.0\"",
+    1,
+  );
+  assert!(law_index_from_jsonl(&old_crate).is_err());
+  let stale = format!("{header}\n{}", body.replace("2020", "2021"));
+  assert!(law_index_from_jsonl(&stale).is_err());
+  assert!(law_index_from_jsonl("").is_err());
+  assert!(law_index_from_jsonl(body).is_err());
+}